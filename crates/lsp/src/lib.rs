@@ -19,7 +19,10 @@ use lsp_types::{
 };
 use noirc_driver::{check_crate, create_local_crate};
 use noirc_errors::{DiagnosticKind, FileDiagnostic};
-use noirc_frontend::{graph::CrateType, hir::Context};
+use noirc_frontend::{
+    graph::CrateType,
+    hir::{Context, FunctionNameMatch},
+};
 use serde_json::Value as JsonValue;
 use tower::Service;
 
@@ -143,7 +146,8 @@ fn on_code_lens_request(
 
     let fm = &context.file_manager;
     let files = fm.as_simple_files();
-    let tests = context.get_all_test_functions_in_crate_matching(&crate_id, "");
+    let tests =
+        context.get_all_test_functions_in_crate_matching(&crate_id, FunctionNameMatch::Anything);
 
     let mut lenses: Vec<CodeLens> = vec![];
     for func_id in tests {
@@ -235,7 +239,7 @@ fn on_did_save_text_document(
         let fm = &context.file_manager;
         let files = fm.as_simple_files();
 
-        for FileDiagnostic { file_id, diagnostic } in file_diagnostics {
+        for FileDiagnostic { file_id, diagnostic, .. } in file_diagnostics {
             // TODO(#1681): This file_id never be 0 because the "path" where it maps is the directory, not a file
             if file_id.as_usize() != 0 {
                 continue;
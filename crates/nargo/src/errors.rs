@@ -10,4 +10,26 @@ pub enum NargoError {
     /// ACIR circuit solving error
     #[error(transparent)]
     SolvingError(#[from] OpcodeResolutionError),
+
+    /// Error while resolving a foreign call made by the circuit
+    #[error(transparent)]
+    ForeignCallError(#[from] ForeignCallError),
+}
+
+#[derive(Debug, Error)]
+pub enum ForeignCallError {
+    #[error("No handler could be found for foreign call `{0}`")]
+    NoHandler(String),
+
+    #[error("Invalid oracle resolver url: {0}")]
+    InvalidResolverUrl(String),
+
+    #[error("Oracle resolver request failed: {0}")]
+    JsonRpc(#[from] jsonrpc::Error),
+
+    #[error("Oracle resolver returned `{0}`, which is not a hex encoded field element")]
+    InvalidField(String),
+
+    #[error("Oracle mock {0} does not exist, it may have been cleared")]
+    UnknownMock(usize),
 }
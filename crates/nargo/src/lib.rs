@@ -13,4 +13,4 @@ pub mod manifest;
 pub mod ops;
 mod perf_trace;
 
-pub use self::errors::NargoError;
+pub use self::errors::{ForeignCallError, NargoError};
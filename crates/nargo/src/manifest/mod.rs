@@ -8,6 +8,9 @@ pub use self::errors::InvalidPackageError;
 pub struct PackageManifest {
     pub package: PackageMetadata,
     pub dependencies: BTreeMap<String, Dependency>,
+    /// Options for `nargo fmt`, taken from the optional `[fmt]` section.
+    #[serde(default)]
+    pub fmt: nargo_fmt::Config,
 }
 
 impl PackageManifest {
@@ -64,3 +67,20 @@ fn parse_standard_toml() {
 
     assert!(PackageManifest::from_toml_str(src).is_ok());
 }
+
+#[test]
+fn parse_fmt_section() {
+    let src = r#"
+        [package]
+        authors = []
+
+        [dependencies]
+
+        [fmt]
+        max_width = 80
+    "#;
+
+    let manifest = PackageManifest::from_toml_str(src).unwrap();
+    assert_eq!(manifest.fmt.max_width, 80);
+    assert_eq!(manifest.fmt.tab_spaces, nargo_fmt::Config::default().tab_spaces);
+}
@@ -0,0 +1,199 @@
+use acvm::acir::brillig::Value;
+use acvm::acir::circuit::brillig::{Brillig, BrilligInputs};
+use acvm::acir::circuit::Opcode;
+use acvm::acir::native_types::{Expression, WitnessMap};
+use acvm::brillig_vm::{Registers, VMStatus, VM};
+use acvm::pwg::ACVM;
+use acvm::{BlackBoxFunctionSolver, FieldElement};
+
+use crate::NargoError;
+
+use super::execute::solve;
+use super::foreign_calls::ForeignCallExecutor;
+
+/// Executes a circuit one opcode at a time so that its execution can be inspected, optionally
+/// stepping through the bytecode of Brillig opcodes.
+pub struct DebugExecutor<'a, B: BlackBoxFunctionSolver, F: ForeignCallExecutor> {
+    backend: &'a B,
+    opcodes: &'a [Opcode],
+    witness_map: WitnessMap,
+    foreign_call_executor: F,
+    /// The index of the next opcode to be executed.
+    next_opcode: usize,
+    /// The Brillig VM executing the next opcode, if it is being stepped through.
+    brillig_vm: Option<VM<'a, B>>,
+}
+
+impl<'a, B: BlackBoxFunctionSolver + Default, F: ForeignCallExecutor> DebugExecutor<'a, B, F> {
+    pub fn new(
+        backend: &'a B,
+        opcodes: &'a [Opcode],
+        initial_witness: WitnessMap,
+        foreign_call_executor: F,
+    ) -> Self {
+        Self {
+            backend,
+            opcodes,
+            witness_map: initial_witness,
+            foreign_call_executor,
+            next_opcode: 0,
+            brillig_vm: None,
+        }
+    }
+
+    /// Returns the index of the next opcode to be executed, or None once execution is finished.
+    pub fn next_opcode(&self) -> Option<usize> {
+        (self.next_opcode < self.opcodes.len()).then_some(self.next_opcode)
+    }
+
+    pub fn witness_map(&self) -> &WitnessMap {
+        &self.witness_map
+    }
+
+    /// Evaluates `expression` against the witnesses solved so far, returning None if it depends
+    /// on a witness which hasn't been solved yet.
+    pub fn evaluate(&self, expression: &Expression) -> Option<FieldElement> {
+        let mut value = expression.q_c;
+        for (coefficient, lhs, rhs) in &expression.mul_terms {
+            let lhs = self.witness_map.get(lhs)?;
+            let rhs = self.witness_map.get(rhs)?;
+            value = value + *coefficient * *lhs * *rhs;
+        }
+        for (coefficient, witness) in &expression.linear_combinations {
+            value = value + *coefficient * *self.witness_map.get(witness)?;
+        }
+        Some(value)
+    }
+
+    /// Executes the next opcode in full, including the remainder of a Brillig opcode which is
+    /// being stepped through.
+    ///
+    /// If the opcode fails, execution stays at the failing opcode.
+    pub fn step_opcode(&mut self) -> Result<(), NargoError> {
+        let Some(index) = self.next_opcode() else { return Ok(()) };
+        self.brillig_vm = None;
+
+        // Each opcode is solved by a fresh ACVM which only knows about that opcode, so that
+        // execution can stop after any of them.
+        let opcode = self.opcodes[index].clone();
+        let mut acvm = ACVM::new(B::default(), vec![opcode], self.witness_map.clone());
+        solve(&mut acvm, &mut self.foreign_call_executor)?;
+
+        self.witness_map = acvm.finalize();
+        self.next_opcode += 1;
+        Ok(())
+    }
+
+    /// Executes a single bytecode instruction of the next opcode if it is a Brillig opcode,
+    /// otherwise executes the next opcode in full.
+    ///
+    /// The Brillig VM stepped through here is only used to inspect the opcode's execution. Once
+    /// it finishes or needs the result of a foreign call, the opcode is solved by the ACVM.
+    pub fn step_brillig_opcode(&mut self) -> Result<(), NargoError> {
+        if self.brillig_vm.is_none() {
+            self.brillig_vm = match self.next_opcode().map(|index| &self.opcodes[index]) {
+                Some(Opcode::Brillig(brillig)) => self.start_brillig_vm(brillig),
+                _ => None,
+            };
+        }
+
+        let Some(vm) = &mut self.brillig_vm else { return self.step_opcode() };
+        match vm.process_opcode() {
+            VMStatus::InProgress => Ok(()),
+            _ => self.step_opcode(),
+        }
+    }
+
+    /// Returns the program counter and registers of the Brillig VM, if the next opcode is being
+    /// stepped through.
+    pub fn brillig_state(&self) -> Option<(usize, &[Value])> {
+        let vm = self.brillig_vm.as_ref()?;
+        Some((vm.program_counter(), &vm.get_registers().inner))
+    }
+
+    /// Loads the inputs of `brillig` into a new VM the same way the ACVM does, or returns None
+    /// if they cannot all be evaluated yet or the opcode is disabled by its predicate.
+    fn start_brillig_vm(&self, brillig: &Brillig) -> Option<VM<'a, B>> {
+        if let Some(predicate) = &brillig.predicate {
+            if self.evaluate(predicate)?.is_zero() {
+                return None;
+            }
+        }
+
+        let mut registers = Vec::new();
+        let mut memory = Vec::new();
+        for input in &brillig.inputs {
+            match input {
+                BrilligInputs::Single(expression) => {
+                    registers.push(Value::from(self.evaluate(expression)?));
+                }
+                BrilligInputs::Array(expressions) => {
+                    // Arrays are passed as a pointer to their elements in memory.
+                    registers.push(Value::from(memory.len()));
+                    for expression in expressions {
+                        memory.push(Value::from(self.evaluate(expression)?));
+                    }
+                }
+            }
+        }
+
+        Some(VM::new(
+            Registers { inner: registers },
+            memory,
+            brillig.bytecode.clone(),
+            brillig.foreign_call_results.clone(),
+            self.backend,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::circuit::Opcode;
+    use acvm::acir::native_types::{Expression, Witness, WitnessMap};
+    use acvm::FieldElement;
+
+    use crate::ops::test_utils::DummyBlackBoxSolver;
+    use crate::ops::DefaultForeignCallExecutor;
+
+    use super::DebugExecutor;
+
+    /// Returns the opcode asserting `coefficient * input - output + constant == 0`.
+    fn linear(coefficient: u128, input: u32, output: u32, constant: u128) -> Opcode {
+        Opcode::Arithmetic(Expression {
+            mul_terms: Vec::new(),
+            linear_combinations: vec![
+                (FieldElement::from(coefficient), Witness(input)),
+                (-FieldElement::one(), Witness(output)),
+            ],
+            q_c: FieldElement::from(constant),
+        })
+    }
+
+    #[test]
+    fn steps_through_opcodes_one_at_a_time() {
+        // w2 = w1 + 1, w3 = 2 * w2
+        let opcodes = vec![linear(1, 1, 2, 1), linear(2, 2, 3, 0)];
+        let initial_witness = WitnessMap::from([(Witness(1), FieldElement::from(5u128))].into());
+        let executor = DefaultForeignCallExecutor::new(std::io::sink(), None, false).unwrap();
+        let mut debugger =
+            DebugExecutor::new(&DummyBlackBoxSolver, &opcodes, initial_witness, executor);
+
+        assert_eq!(debugger.next_opcode(), Some(0));
+        debugger.step_opcode().unwrap();
+        assert_eq!(debugger.witness_map().get(&Witness(2)), Some(&FieldElement::from(6u128)));
+        assert_eq!(debugger.witness_map().get(&Witness(3)), None);
+
+        // Opcodes which aren't Brillig are executed in full when stepping into them.
+        debugger.step_brillig_opcode().unwrap();
+        assert_eq!(debugger.witness_map().get(&Witness(3)), Some(&FieldElement::from(12u128)));
+        assert_eq!(debugger.next_opcode(), None);
+
+        let doubled = Expression {
+            mul_terms: Vec::new(),
+            linear_combinations: vec![(FieldElement::from(2u128), Witness(3))],
+            q_c: FieldElement::zero(),
+        };
+        assert_eq!(debugger.evaluate(&doubled), Some(FieldElement::from(24u128)));
+    }
+}
@@ -0,0 +1,495 @@
+use std::{io::Write, time::Duration};
+
+use acvm::{
+    acir::brillig::{ForeignCallOutput, ForeignCallResult, Value},
+    pwg::ForeignCallWaitInfo,
+    FieldElement,
+};
+use iter_extended::{try_vecmap, vecmap};
+use jsonrpc::{minreq_http::Builder, Client};
+use serde::Deserialize;
+
+use crate::errors::ForeignCallError;
+
+/// How long to wait for an oracle resolver to respond before giving up.
+const ORACLE_RESOLVER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Resolves the foreign calls a circuit makes while it is being executed, such as calls to
+/// `#[oracle]` functions.
+pub trait ForeignCallExecutor {
+    fn execute(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError>;
+}
+
+/// The foreign calls which nargo resolves itself.
+enum ForeignCall {
+    Print,
+    PrintArray,
+    Sequence,
+    ReverseSequence,
+}
+
+impl ForeignCall {
+    fn lookup(function: &str) -> Option<ForeignCall> {
+        match function {
+            "oracle_print_impl" => Some(ForeignCall::Print),
+            "oracle_print_array_impl" => Some(ForeignCall::PrintArray),
+            "get_number_sequence" => Some(ForeignCall::Sequence),
+            "get_reverse_number_sequence" => Some(ForeignCall::ReverseSequence),
+            _ => None,
+        }
+    }
+}
+
+/// The oracles through which `std::test::OracleMock` sets up and inspects mocked oracles.
+enum MockOracle {
+    Create,
+    SetParams,
+    SetReturns,
+    SetTimes,
+    Clear,
+    TimesCalled,
+}
+
+impl MockOracle {
+    fn lookup(function: &str) -> Option<MockOracle> {
+        match function {
+            "create_mock" => Some(MockOracle::Create),
+            "set_mock_params" => Some(MockOracle::SetParams),
+            "set_mock_returns" => Some(MockOracle::SetReturns),
+            "set_mock_times" => Some(MockOracle::SetTimes),
+            "clear_mock" => Some(MockOracle::Clear),
+            "get_mock_times_called" => Some(MockOracle::TimesCalled),
+            _ => None,
+        }
+    }
+}
+
+/// An oracle mocked by a test.
+struct OracleMock {
+    id: usize,
+    /// The name of the mocked oracle
+    name: String,
+    /// The inputs a call must be made with to be answered by this mock, any inputs if `None`
+    params: Option<Vec<Vec<Value>>>,
+    /// The outputs each call is answered with
+    returns: Vec<ForeignCallOutput>,
+    /// The number of calls this mock will still answer, unlimited if `None`
+    times_left: Option<u64>,
+    times_called: u64,
+}
+
+/// The oracles mocked by a single test.
+#[derive(Default)]
+struct OracleMocks {
+    mocks: Vec<OracleMock>,
+    next_id: usize,
+}
+
+impl OracleMocks {
+    fn configure(
+        &mut self,
+        oracle: MockOracle,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError> {
+        let inputs = &foreign_call.inputs;
+        if let MockOracle::Create = oracle {
+            let name =
+                inputs[0].iter().map(|byte| byte.to_field().to_u128() as u8 as char).collect();
+            let id = self.next_id;
+            self.next_id += 1;
+            self.mocks.push(OracleMock {
+                id,
+                name,
+                params: None,
+                returns: Vec::new(),
+                times_left: None,
+                times_called: 0,
+            });
+            return Ok(Value::from(id).into());
+        }
+
+        let id = inputs[0][0].to_field().to_u128() as usize;
+        let mock = self
+            .mocks
+            .iter_mut()
+            .find(|mock| mock.id == id)
+            .ok_or(ForeignCallError::UnknownMock(id))?;
+        match oracle {
+            MockOracle::Create => unreachable!("mocks are created above"),
+            MockOracle::SetParams => mock.params = Some(inputs[1..].to_vec()),
+            MockOracle::SetReturns => mock.returns = mock_returns(&inputs[1..]),
+            MockOracle::SetTimes => {
+                mock.times_left = Some(inputs[1][0].to_field().to_u128() as u64)
+            }
+            MockOracle::Clear => self.mocks.retain(|mock| mock.id != id),
+            MockOracle::TimesCalled => {
+                return Ok(Value::from(mock.times_called as u128).into());
+            }
+        }
+        Ok(ForeignCallResult { values: Vec::new() })
+    }
+
+    /// Answers the call with the first mock of the called oracle which accepts its inputs.
+    fn answer(&mut self, foreign_call: &ForeignCallWaitInfo) -> Option<ForeignCallResult> {
+        let mock = self.mocks.iter_mut().find(|mock| {
+            mock.name == foreign_call.function
+                && mock.times_left != Some(0)
+                && mock.params.as_ref().map_or(true, |params| params == &foreign_call.inputs)
+        })?;
+
+        mock.times_called += 1;
+        if let Some(times_left) = &mut mock.times_left {
+            *times_left -= 1;
+        }
+        Some(ForeignCallResult { values: mock.returns.clone() })
+    }
+}
+
+/// Converts the inputs of a `set_mock_returns` call into the outputs the mock answers with.
+///
+/// The inputs of a foreign call don't record whether a value was an array, so the call ends with
+/// a flag which is set if the returned values are a single array. Otherwise, inputs of a single
+/// value are returned as that value.
+fn mock_returns(inputs: &[Vec<Value>]) -> Vec<ForeignCallOutput> {
+    let (is_array, returns) = inputs.split_last().expect("set_mock_returns has an array flag");
+    if !is_array[0].to_field().is_zero() {
+        return vec![ForeignCallOutput::Array(returns.concat())];
+    }
+    vecmap(returns, |values| match values.as_slice() {
+        [value] => ForeignCallOutput::Single(*value),
+        _ => ForeignCallOutput::Array(values.clone()),
+    })
+}
+
+/// Resolves nargo's built-in foreign calls, writing anything printed to `output`.
+///
+/// When `enable_mocks` is set, tests can mock oracles through `std::test::OracleMock`, and calls
+/// to the mocked oracles are answered by those mocks.
+///
+/// All other foreign calls are forwarded to an external oracle resolver over JSON-RPC, if one
+/// has been given. Each input of the call is sent as an array of hex encoded fields, and the
+/// resolver must reply with an array holding either a field or an array of fields per output.
+pub struct DefaultForeignCallExecutor<W: Write> {
+    output: W,
+    mocks: Option<OracleMocks>,
+    external_resolver: Option<Client>,
+}
+
+impl<W: Write> DefaultForeignCallExecutor<W> {
+    pub fn new(
+        output: W,
+        resolver_url: Option<&str>,
+        enable_mocks: bool,
+    ) -> Result<Self, ForeignCallError> {
+        let external_resolver = resolver_url
+            .map(|url| {
+                let transport = Builder::new()
+                    .url(url)
+                    .map_err(|error| ForeignCallError::InvalidResolverUrl(error.to_string()))?
+                    .timeout(ORACLE_RESOLVER_TIMEOUT)
+                    .build();
+                Ok(Client::with_transport(transport))
+            })
+            .transpose()?;
+
+        let mocks = enable_mocks.then(OracleMocks::default);
+        Ok(DefaultForeignCallExecutor { output, mocks, external_resolver })
+    }
+
+    fn execute_builtin(
+        &mut self,
+        builtin: ForeignCall,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> ForeignCallResult {
+        // TODO(#1615): Nargo only supports "oracle_print_**_impl" functions  that print a singular value or an array and nothing else
+        // This should be expanded in a general logging refactor
+        match builtin {
+            ForeignCall::Print => {
+                let values = &foreign_call.inputs[0];
+                writeln!(self.output, "{:?}", values[0].to_field().to_hex())
+                    .expect("failed to print");
+                values[0].into()
+            }
+            ForeignCall::PrintArray => {
+                let mut outputs_hex = Vec::new();
+                for values in &foreign_call.inputs {
+                    for value in values {
+                        outputs_hex.push(value.to_field().to_hex());
+                    }
+                }
+                // Join all of the hex strings using a comma
+                let comma_separated_elements = outputs_hex.join(", ");
+                let output_witnesses_string = "[".to_owned() + &comma_separated_elements + "]";
+                writeln!(self.output, "{output_witnesses_string}").expect("failed to print");
+
+                foreign_call.inputs[0][0].into()
+            }
+            ForeignCall::Sequence => {
+                let sequence_length: u128 = foreign_call.inputs[0][0].to_field().to_u128();
+
+                vecmap(0..sequence_length, Value::from).into()
+            }
+            ForeignCall::ReverseSequence => {
+                let sequence_length: u128 = foreign_call.inputs[0][0].to_field().to_u128();
+
+                vecmap((0..sequence_length).rev(), Value::from).into()
+            }
+        }
+    }
+}
+
+impl<W: Write> ForeignCallExecutor for DefaultForeignCallExecutor<W> {
+    fn execute(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError> {
+        if let Some(builtin) = ForeignCall::lookup(&foreign_call.function) {
+            return Ok(self.execute_builtin(builtin, foreign_call));
+        }
+
+        if let Some(mocks) = &mut self.mocks {
+            if let Some(oracle) = MockOracle::lookup(&foreign_call.function) {
+                return mocks.configure(oracle, foreign_call);
+            }
+            if let Some(result) = mocks.answer(foreign_call) {
+                return Ok(result);
+            }
+        }
+
+        match &self.external_resolver {
+            Some(resolver) => resolve_externally(resolver, foreign_call),
+            None => Err(ForeignCallError::NoHandler(foreign_call.function.clone())),
+        }
+    }
+}
+
+/// A single output of a foreign call as returned by an oracle resolver.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ResolvedOutput {
+    Single(String),
+    Array(Vec<String>),
+}
+
+fn resolve_externally(
+    resolver: &Client,
+    foreign_call: &ForeignCallWaitInfo,
+) -> Result<ForeignCallResult, ForeignCallError> {
+    let params = vecmap(&foreign_call.inputs, |input| {
+        jsonrpc::arg(vecmap(input, |value| format!("0x{}", value.to_field().to_hex())))
+    });
+    let request = resolver.build_request(&foreign_call.function, &params);
+    let outputs: Vec<ResolvedOutput> = resolver.send_request(request)?.result()?;
+
+    let values = try_vecmap(outputs, |output| match output {
+        ResolvedOutput::Single(field) => parse_field(&field).map(ForeignCallOutput::Single),
+        ResolvedOutput::Array(fields) => {
+            try_vecmap(fields, |field| parse_field(&field)).map(ForeignCallOutput::Array)
+        }
+    })?;
+    Ok(ForeignCallResult { values })
+}
+
+fn parse_field(field: &str) -> Result<Value, ForeignCallError> {
+    FieldElement::from_hex(field)
+        .map(Value::from)
+        .ok_or_else(|| ForeignCallError::InvalidField(field.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use acvm::{
+        acir::brillig::{ForeignCallOutput, Value},
+        pwg::ForeignCallWaitInfo,
+    };
+
+    use super::{DefaultForeignCallExecutor, ForeignCallExecutor};
+    use crate::errors::ForeignCallError;
+
+    /// Serves a single JSON-RPC request by echoing the request's params back as its result,
+    /// returning the server's url and the request it received.
+    fn echo_server() -> (String, thread::JoinHandle<serde_json::Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": request["params"],
+            })
+            .to_string();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+
+            request
+        });
+
+        (url, server)
+    }
+
+    #[test]
+    fn unknown_foreign_calls_are_resolved_by_the_oracle_resolver() {
+        let (url, server) = echo_server();
+        let mut executor = DefaultForeignCallExecutor::new(Vec::new(), Some(&url), false).unwrap();
+
+        let foreign_call = ForeignCallWaitInfo {
+            function: "my_oracle".to_owned(),
+            inputs: vec![vec![Value::from(1u128)], vec![Value::from(2u128), Value::from(3u128)]],
+        };
+        let result = executor.execute(&foreign_call).unwrap();
+        let expected = vec![
+            ForeignCallOutput::Array(vec![Value::from(1u128)]),
+            ForeignCallOutput::Array(vec![Value::from(2u128), Value::from(3u128)]),
+        ];
+        assert_eq!(result.values, expected);
+
+        let request = server.join().unwrap();
+        assert_eq!(request["method"], "my_oracle");
+        assert_eq!(request["params"][1].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn unknown_foreign_calls_fail_without_an_oracle_resolver() {
+        let mut executor = DefaultForeignCallExecutor::new(Vec::new(), None, false).unwrap();
+
+        let foreign_call =
+            ForeignCallWaitInfo { function: "my_oracle".to_owned(), inputs: Vec::new() };
+        let error = executor.execute(&foreign_call).unwrap_err();
+        assert!(matches!(error, ForeignCallError::NoHandler(name) if name == "my_oracle"));
+    }
+
+    #[test]
+    fn printing_writes_to_the_output() {
+        let foreign_call = ForeignCallWaitInfo {
+            function: "oracle_print_array_impl".to_owned(),
+            inputs: vec![vec![Value::from(1u128), Value::from(2u128)]],
+        };
+
+        let mut output = Vec::new();
+        {
+            let mut executor = DefaultForeignCallExecutor::new(&mut output, None, false).unwrap();
+            executor.execute(&foreign_call).unwrap();
+        }
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with('[') && output.ends_with("]\n"), "{output}");
+        assert_eq!(output.matches(", ").count(), 1);
+    }
+
+    fn call(function: &str, inputs: Vec<Vec<Value>>) -> ForeignCallWaitInfo {
+        ForeignCallWaitInfo { function: function.to_owned(), inputs }
+    }
+
+    fn field(value: u128) -> Vec<Value> {
+        vec![Value::from(value)]
+    }
+
+    #[test]
+    fn mocked_oracles_answer_matching_calls() {
+        let mut executor = DefaultForeignCallExecutor::new(Vec::new(), None, true).unwrap();
+
+        let name = "my_oracle".bytes().map(|byte| Value::from(byte as u128)).collect();
+        let id = executor.execute(&call("create_mock", vec![name])).unwrap();
+        let [ForeignCallOutput::Single(id)] = id.values.as_slice() else {
+            panic!("expected a mock id")
+        };
+        let id = vec![*id];
+        executor.execute(&call("set_mock_params", vec![id.clone(), field(1)])).unwrap();
+        executor
+            .execute(&call(
+                "set_mock_returns",
+                vec![
+                    id.clone(),
+                    field(10),
+                    vec![Value::from(11u128), Value::from(12u128)],
+                    field(0),
+                ],
+            ))
+            .unwrap();
+        executor.execute(&call("set_mock_times", vec![id.clone(), field(1)])).unwrap();
+
+        // Calls with other parameters aren't answered by the mock
+        let error = executor.execute(&call("my_oracle", vec![field(2)])).unwrap_err();
+        assert!(matches!(error, ForeignCallError::NoHandler(_)));
+
+        let result = executor.execute(&call("my_oracle", vec![field(1)])).unwrap();
+        let expected = vec![
+            ForeignCallOutput::Single(Value::from(10u128)),
+            ForeignCallOutput::Array(vec![Value::from(11u128), Value::from(12u128)]),
+        ];
+        assert_eq!(result.values, expected);
+
+        // The mock only answers a single call
+        let error = executor.execute(&call("my_oracle", vec![field(1)])).unwrap_err();
+        assert!(matches!(error, ForeignCallError::NoHandler(_)));
+
+        let times_called =
+            executor.execute(&call("get_mock_times_called", vec![id.clone()])).unwrap();
+        assert_eq!(times_called.values, vec![ForeignCallOutput::Single(Value::from(1u128))]);
+
+        executor.execute(&call("clear_mock", vec![id.clone()])).unwrap();
+        let error = executor.execute(&call("get_mock_times_called", vec![id])).unwrap_err();
+        assert!(matches!(error, ForeignCallError::UnknownMock(0)));
+    }
+
+    #[test]
+    fn mocked_arrays_of_a_single_element_are_returned_as_arrays() {
+        let mut executor = DefaultForeignCallExecutor::new(Vec::new(), None, true).unwrap();
+
+        let name = "my_oracle".bytes().map(|byte| Value::from(byte as u128)).collect();
+        let id = executor.execute(&call("create_mock", vec![name])).unwrap();
+        let [ForeignCallOutput::Single(id)] = id.values.as_slice() else {
+            panic!("expected a mock id")
+        };
+        let id = vec![*id];
+
+        executor.execute(&call("set_mock_returns", vec![id.clone(), field(10), field(0)])).unwrap();
+        let result = executor.execute(&call("my_oracle", Vec::new())).unwrap();
+        assert_eq!(result.values, vec![ForeignCallOutput::Single(Value::from(10u128))]);
+
+        executor.execute(&call("set_mock_returns", vec![id, field(10), field(1)])).unwrap();
+        let result = executor.execute(&call("my_oracle", Vec::new())).unwrap();
+        assert_eq!(result.values, vec![ForeignCallOutput::Array(vec![Value::from(10u128)])]);
+    }
+
+    #[test]
+    fn mocks_are_only_available_when_enabled() {
+        let mut executor = DefaultForeignCallExecutor::new(Vec::new(), None, false).unwrap();
+
+        let error = executor.execute(&call("create_mock", vec![Vec::new()])).unwrap_err();
+        assert!(matches!(error, ForeignCallError::NoHandler(name) if name == "create_mock"));
+    }
+}
@@ -1,11 +1,19 @@
 pub use self::codegen_verifier::codegen_verifier;
+pub use self::debug::DebugExecutor;
 pub use self::execute::execute_circuit;
+pub use self::foreign_calls::{DefaultForeignCallExecutor, ForeignCallExecutor};
 pub use self::preprocess::{preprocess_contract_function, preprocess_program};
 pub use self::prove::prove_execution;
+pub use self::trace::{execute_circuit_with_trace, OpcodeTrace};
 pub use self::verify::verify_proof;
 
 mod codegen_verifier;
+mod debug;
 mod execute;
+mod foreign_calls;
 mod preprocess;
 mod prove;
+#[cfg(test)]
+mod test_utils;
+mod trace;
 mod verify;
@@ -0,0 +1,36 @@
+//! Helpers shared by the tests of the `ops` module.
+
+use acvm::acir::BlackBoxFunc;
+use acvm::{BlackBoxFunctionSolver, BlackBoxResolutionError, FieldElement};
+
+/// A black box function solver for circuits which don't call any black box functions, which
+/// fails to solve any that are called.
+#[derive(Default)]
+pub(crate) struct DummyBlackBoxSolver;
+
+impl BlackBoxFunctionSolver for DummyBlackBoxSolver {
+    fn schnorr_verify(
+        &self,
+        _public_key_x: &FieldElement,
+        _public_key_y: &FieldElement,
+        _signature: &[u8],
+        _message: &[u8],
+    ) -> Result<bool, BlackBoxResolutionError> {
+        Err(BlackBoxResolutionError::Unsupported(BlackBoxFunc::SchnorrVerify))
+    }
+
+    fn pedersen(
+        &self,
+        _inputs: &[FieldElement],
+        _domain_separator: u32,
+    ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+        Err(BlackBoxResolutionError::Unsupported(BlackBoxFunc::Pedersen))
+    }
+
+    fn fixed_base_scalar_mul(
+        &self,
+        _input: &FieldElement,
+    ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+        Err(BlackBoxResolutionError::Unsupported(BlackBoxFunc::FixedBaseScalarMul))
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeSet;
+
+use acvm::acir::circuit::brillig::{BrilligInputs, BrilligOutputs};
+use acvm::acir::circuit::directives::{Directive, QuotientDirective};
+use acvm::acir::circuit::{Circuit, Opcode};
+use acvm::acir::native_types::{Expression, Witness, WitnessMap};
+use acvm::{BlackBoxFunctionSolver, FieldElement};
+
+use crate::NargoError;
+
+use super::debug::DebugExecutor;
+use super::foreign_calls::ForeignCallExecutor;
+
+/// The witnesses read and written by a single opcode during execution.
+#[derive(Debug, Clone)]
+pub struct OpcodeTrace {
+    pub index: usize,
+    pub opcode: Opcode,
+    /// The solved witnesses the opcode reads, in ascending order.
+    pub inputs: Vec<(Witness, FieldElement)>,
+    /// The witnesses solved by the opcode, in ascending order.
+    pub outputs: Vec<(Witness, FieldElement)>,
+}
+
+/// Executes `circuit` like [`execute_circuit`][super::execute_circuit], appending the inputs and
+/// outputs of each opcode to `trace` as it is solved.
+///
+/// The trace of the opcodes solved before a failure is kept, so it can be used to investigate it.
+pub fn execute_circuit_with_trace<B: BlackBoxFunctionSolver + Default>(
+    backend: &B,
+    circuit: &Circuit,
+    initial_witness: WitnessMap,
+    foreign_call_executor: impl ForeignCallExecutor,
+    trace: &mut Vec<OpcodeTrace>,
+) -> Result<WitnessMap, NargoError> {
+    let mut executor =
+        DebugExecutor::new(backend, &circuit.opcodes, initial_witness, foreign_call_executor);
+
+    while let Some(index) = executor.next_opcode() {
+        let opcode = &circuit.opcodes[index];
+        // Only the witnesses the opcode refers to are looked up, rather than comparing the whole
+        // witness map before and after each opcode.
+        let solved_before = executor.witness_map();
+        let inputs = opcode_inputs(opcode)
+            .into_iter()
+            .filter_map(|witness| solved_before.get(&witness).map(|value| (witness, *value)))
+            .collect();
+        let unsolved_outputs: Vec<_> = opcode_outputs(opcode)
+            .into_iter()
+            .filter(|witness| solved_before.get(witness).is_none())
+            .collect();
+
+        executor.step_opcode()?;
+
+        let solved_after = executor.witness_map();
+        let outputs = unsolved_outputs
+            .into_iter()
+            .filter_map(|witness| solved_after.get(&witness).map(|value| (witness, *value)))
+            .collect();
+        trace.push(OpcodeTrace { index, opcode: opcode.clone(), inputs, outputs });
+    }
+
+    Ok(executor.witness_map().clone())
+}
+
+/// Returns the witnesses which `opcode` may read from in order to be solved.
+fn opcode_inputs(opcode: &Opcode) -> BTreeSet<Witness> {
+    let mut expressions: Vec<&Expression> = Vec::new();
+    let mut witnesses = BTreeSet::new();
+    match opcode {
+        Opcode::Arithmetic(expression) => expressions.push(expression),
+        Opcode::BlackBoxFuncCall(call) => {
+            witnesses.extend(call.get_inputs_vec().into_iter().map(|input| input.witness));
+        }
+        Opcode::Directive(Directive::Quotient(QuotientDirective { a, b, predicate, .. })) => {
+            expressions.extend([a, b]);
+            expressions.extend(predicate);
+        }
+        Opcode::Directive(Directive::ToLeRadix { a, .. }) => expressions.push(a),
+        Opcode::Directive(Directive::PermutationSort { inputs, .. }) => {
+            expressions.extend(inputs.iter().flatten());
+        }
+        Opcode::Directive(_) => (),
+        Opcode::Block(block) | Opcode::ROM(block) | Opcode::RAM(block) => {
+            for op in &block.trace {
+                expressions.extend([&op.operation, &op.index, &op.value]);
+            }
+        }
+        Opcode::Brillig(brillig) => {
+            for input in &brillig.inputs {
+                match input {
+                    BrilligInputs::Single(expression) => expressions.push(expression),
+                    BrilligInputs::Array(array) => expressions.extend(array),
+                }
+            }
+            expressions.extend(&brillig.predicate);
+        }
+        _ => (),
+    }
+
+    for expression in expressions {
+        witnesses.extend(expression_witnesses(expression));
+    }
+    witnesses
+}
+
+/// Returns the witnesses which `opcode` may solve.
+fn opcode_outputs(opcode: &Opcode) -> BTreeSet<Witness> {
+    match opcode {
+        Opcode::Arithmetic(expression) => expression_witnesses(expression).collect(),
+        Opcode::BlackBoxFuncCall(call) => call.get_outputs_vec().into_iter().collect(),
+        Opcode::Directive(Directive::Quotient(QuotientDirective { q, r, .. })) => {
+            BTreeSet::from([*q, *r])
+        }
+        Opcode::Directive(Directive::ToLeRadix { b, .. }) => b.iter().copied().collect(),
+        Opcode::Directive(Directive::PermutationSort { bits, .. }) => {
+            bits.iter().copied().collect()
+        }
+        Opcode::Block(block) | Opcode::ROM(block) | Opcode::RAM(block) => {
+            block.trace.iter().flat_map(|op| expression_witnesses(&op.value)).collect()
+        }
+        Opcode::Brillig(brillig) => brillig
+            .outputs
+            .iter()
+            .flat_map(|output| match output {
+                BrilligOutputs::Simple(witness) => vec![*witness],
+                BrilligOutputs::Array(witnesses) => witnesses.clone(),
+            })
+            .collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+fn expression_witnesses(expression: &Expression) -> impl Iterator<Item = Witness> + '_ {
+    let mul_terms = expression.mul_terms.iter().flat_map(|(_, lhs, rhs)| [*lhs, *rhs]);
+    mul_terms.chain(expression.linear_combinations.iter().map(|(_, witness)| *witness))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use acvm::acir::circuit::{Circuit, Opcode, PublicInputs};
+    use acvm::acir::native_types::{Expression, Witness, WitnessMap};
+    use acvm::FieldElement;
+
+    use crate::ops::test_utils::DummyBlackBoxSolver;
+    use crate::ops::DefaultForeignCallExecutor;
+
+    use super::execute_circuit_with_trace;
+
+    #[test]
+    fn records_the_inputs_and_outputs_of_each_opcode() {
+        // w3 = w1 * w2
+        let product = Opcode::Arithmetic(Expression {
+            mul_terms: vec![(FieldElement::one(), Witness(1), Witness(2))],
+            linear_combinations: vec![(-FieldElement::one(), Witness(3))],
+            q_c: FieldElement::zero(),
+        });
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes: vec![product],
+            public_parameters: PublicInputs(BTreeSet::new()),
+            return_values: PublicInputs(BTreeSet::new()),
+        };
+        let initial_witness = WitnessMap::from(
+            [(Witness(1), FieldElement::from(3u128)), (Witness(2), FieldElement::from(4u128))]
+                .into(),
+        );
+        let executor = DefaultForeignCallExecutor::new(std::io::sink(), None, false).unwrap();
+
+        let mut trace = Vec::new();
+        let solved = execute_circuit_with_trace(
+            &DummyBlackBoxSolver,
+            &circuit,
+            initial_witness,
+            executor,
+            &mut trace,
+        )
+        .unwrap();
+
+        assert_eq!(solved.get(&Witness(3)), Some(&FieldElement::from(12u128)));
+        assert_eq!(trace.len(), 1);
+        assert_eq!(
+            trace[0].inputs,
+            [(Witness(1), FieldElement::from(3u128)), (Witness(2), FieldElement::from(4u128))]
+        );
+        assert_eq!(trace[0].outputs, [(Witness(3), FieldElement::from(12u128))]);
+    }
+}
@@ -35,36 +35,39 @@ fn main() {
 
     generate_tests(&mut test_file, false);
     generate_tests(&mut test_file, true);
-}
 
-fn load_conf(conf_path: &Path) -> BTreeMap<String, Vec<String>> {
-    let config_str = std::fs::read_to_string(conf_path).unwrap();
+    let destination = Path::new(&out_dir).join("ssa_parity.rs");
+    let mut test_file = File::create(destination).unwrap();
 
-    let mut conf_data = match toml::from_str(&config_str) {
-        Ok(t) => t,
-        Err(_) => {
-            BTreeMap::from([("exclude".to_string(), Vec::new()), ("fail".to_string(), Vec::new())])
-        }
-    };
-    if conf_data.get("exclude").is_none() {
-        conf_data.insert("exclude".to_string(), Vec::new());
-    }
-    if conf_data.get("fail").is_none() {
-        conf_data.insert("fail".to_string(), Vec::new());
-    }
-    conf_data
+    generate_ssa_parity_tests(&mut test_file);
 }
 
-fn generate_tests(test_file: &mut File, experimental_ssa: bool) {
+/// The directory holding the test programs of `nargo_cli`
+fn tests_dir() -> PathBuf {
     // Try to find the directory that Cargo sets when it is running; otherwise fallback to assuming the CWD
     // is the root of the repository and append the crate path
     let manifest_dir = match std::env::var("CARGO_MANIFEST_DIR") {
         Ok(dir) => PathBuf::from(dir),
         Err(_) => std::env::current_dir().unwrap().join("crates").join("nargo_cli"),
     };
+    manifest_dir.join("tests")
+}
+
+fn load_conf(conf_path: &Path) -> BTreeMap<String, Vec<String>> {
+    let config_str = std::fs::read_to_string(conf_path).unwrap();
+
+    let mut conf_data: BTreeMap<String, Vec<String>> =
+        toml::from_str(&config_str).unwrap_or_default();
+    for key in ["exclude", "fail", "ssa_parity_fail", "run_tests"] {
+        conf_data.entry(key.to_string()).or_default();
+    }
+    conf_data
+}
+
+fn generate_tests(test_file: &mut File, experimental_ssa: bool) {
     // Choose the test directory depending on whether we are in the SSA refactor module or not
     let test_sub_dir = if experimental_ssa { "test_data_ssa_refactor" } else { "test_data" };
-    let test_data_dir = manifest_dir.join("tests").join(test_sub_dir);
+    let test_data_dir = tests_dir().join(test_sub_dir);
     let config_path = test_data_dir.join("config.toml");
 
     // Load config.toml file from `test_data` directory
@@ -87,6 +90,7 @@ fn generate_tests(test_file: &mut File, experimental_ssa: bool) {
             if config_data["exclude"].contains(&test_name) { "#[ignore]" } else { "" };
 
         let should_fail = config_data["fail"].contains(&test_name);
+        let run_tests = config_data["run_tests"].contains(&test_name);
 
         write!(
             test_file,
@@ -97,7 +101,7 @@ fn prove_and_verify_{test_sub_dir}_{test_name}() {{
     let test_program_dir = PathBuf::from("{test_dir}");
 
     let mut cmd = Command::cargo_bin("nargo").unwrap();
-    cmd.arg("--program-dir").arg(test_program_dir);
+    cmd.arg("--program-dir").arg(&test_program_dir);
     cmd.arg("execute");
     if {experimental_ssa} {{
         cmd.arg("--experimental-ssa");
@@ -109,6 +113,60 @@ fn prove_and_verify_{test_sub_dir}_{test_name}() {{
     }} else {{
         cmd.assert().success();
     }}
+
+    if {run_tests} {{
+        let mut cmd = Command::cargo_bin("nargo").unwrap();
+        cmd.arg("--program-dir").arg(test_program_dir);
+        cmd.arg("test");
+        if {experimental_ssa} {{
+            cmd.arg("--experimental-ssa");
+        }};
+        cmd.assert().success();
+    }}
+}}
+            "#,
+            test_dir = test_dir.display(),
+        )
+        .expect("Could not write templated test file.");
+    }
+}
+
+/// Generates a test for each program in `test_data` which executes it through both the legacy
+/// and the experimental SSA pipelines, and compares their results.
+///
+/// The programs listed under `ssa_parity_fail` in `config.toml` are expected to give different
+/// results, so that the test fails once they agree and the program can be removed from the list.
+/// Each test is also given the opcode count recorded for its program in
+/// `ssa_opcode_baseline.toml`, if any.
+fn generate_ssa_parity_tests(test_file: &mut File) {
+    let test_data_dir = tests_dir().join("test_data");
+    let config_data = load_conf(&test_data_dir.join("config.toml"));
+    let baseline_str =
+        std::fs::read_to_string(test_data_dir.join("ssa_opcode_baseline.toml")).unwrap_or_default();
+    let baselines: BTreeMap<String, u64> =
+        toml::from_str(&baseline_str).expect("Could not parse ssa_opcode_baseline.toml");
+
+    let test_case_dirs =
+        fs::read_dir(&test_data_dir).unwrap().flatten().filter(|c| c.path().is_dir());
+
+    for test_dir in test_case_dirs {
+        let test_name =
+            test_dir.file_name().into_string().expect("Directory can't be converted to string");
+        let test_dir = &test_dir.path();
+
+        let exclude_macro =
+            if config_data["exclude"].contains(&test_name) { "#[ignore]" } else { "" };
+
+        let diverges = config_data["ssa_parity_fail"].contains(&test_name);
+        let baseline = baselines.get(&test_name);
+
+        write!(
+            test_file,
+            r#"
+{exclude_macro}
+#[test]
+fn ssa_parity_{test_name}() {{
+    compare_ssa_pipelines(Path::new("{test_dir}"), {diverges}, {baseline:?});
 }}
             "#,
             test_dir = test_dir.display(),
@@ -1,8 +1,12 @@
-use acvm::{acir::circuit::Circuit, Backend};
-use iter_extended::try_vecmap;
+use acvm::{
+    acir::circuit::{Circuit, OpcodeLabel},
+    Backend,
+};
+use iter_extended::{try_vecmap, vecmap};
 use nargo::{artifacts::contract::PreprocessedContract, NargoError};
 use noirc_driver::{
-    compile_contracts, compile_main, CompileOptions, CompiledProgram, ErrorsAndWarnings, Warnings,
+    compile_contracts, compile_main, CompileOptions, CompiledProgram, ContractFunction,
+    ErrorsAndWarnings, Warnings,
 };
 use noirc_errors::reporter::ReportedErrors;
 use noirc_frontend::hir::Context;
@@ -26,6 +30,9 @@ use super::NargoConfig;
 // TODO(#1388): pull this from backend.
 const BACKEND_IDENTIFIER: &str = "acvm-backend-barretenberg";
 
+/// The origin given to opcodes which the optimizer can't trace back to the unoptimized circuit.
+pub(super) const UNKNOWN_ORIGIN: &str = "<unknown>";
+
 /// Compile the program and its secret execution trace into ACIR format
 #[derive(Debug, Clone, Args)]
 pub(crate) struct CompileCommand {
@@ -66,25 +73,24 @@ pub(crate) fn run<B: Backend>(
         // This is due to EACH function needing it's own CRS, PKey, and VKey from the backend.
         let preprocessed_contracts: Result<Vec<PreprocessedContract>, CliError<B>> =
             try_vecmap(contracts, |contract| {
-                let preprocessed_contract_functions =
-                    try_vecmap(contract.functions, |mut func| {
-                        func.bytecode = optimize_circuit(backend, func.bytecode)?;
-
-                        common_reference_string = update_common_reference_string(
-                            backend,
-                            &common_reference_string,
-                            &func.bytecode,
-                        )
-                        .map_err(CliError::CommonReferenceStringError)?;
-
-                        preprocess_contract_function(
-                            backend,
-                            args.include_keys,
-                            &common_reference_string,
-                            func,
-                        )
-                        .map_err(CliError::ProofSystemCompilerError)
-                    })?;
+                let preprocessed_contract_functions = try_vecmap(contract.functions, |func| {
+                    let func = optimize_contract_function(backend, func)?;
+
+                    common_reference_string = update_common_reference_string(
+                        backend,
+                        &common_reference_string,
+                        &func.bytecode,
+                    )
+                    .map_err(CliError::CommonReferenceStringError)?;
+
+                    preprocess_contract_function(
+                        backend,
+                        args.include_keys,
+                        &common_reference_string,
+                        func,
+                    )
+                    .map_err(CliError::ProofSystemCompilerError)
+                })?;
 
                 Ok(PreprocessedContract {
                     name: contract.name,
@@ -124,24 +130,58 @@ pub(crate) fn compile_circuit<B: Backend>(
 ) -> Result<CompiledProgram, CliError<B>> {
     let mut context = resolve_root_manifest(program_dir)?;
     let result = compile_main(&mut context, compile_options);
-    let mut program = report_errors(result, &context, compile_options.deny_warnings)?;
+    let program = report_errors(result, &context, compile_options.deny_warnings)?;
 
     // Apply backend specific optimizations.
-    program.circuit = optimize_circuit(backend, program.circuit).unwrap();
+    Ok(optimize_program(backend, program).unwrap())
+}
+
+pub(super) fn optimize_program<B: Backend>(
+    backend: &B,
+    mut program: CompiledProgram,
+) -> Result<CompiledProgram, CliError<B>> {
+    let (optimized_circuit, opcode_labels) = optimize_circuit(backend, program.circuit)?;
+    program.circuit = optimized_circuit;
+    program.opcode_origins = remap_opcode_origins(&program.opcode_origins, &opcode_labels);
+    program.debug.update_acir(&opcode_labels);
     Ok(program)
 }
 
-pub(super) fn optimize_circuit<B: Backend>(
+pub(super) fn optimize_contract_function<B: Backend>(
+    backend: &B,
+    mut func: ContractFunction,
+) -> Result<ContractFunction, CliError<B>> {
+    let (optimized_bytecode, opcode_labels) = optimize_circuit(backend, func.bytecode)?;
+    func.bytecode = optimized_bytecode;
+    func.opcode_origins = remap_opcode_origins(&func.opcode_origins, &opcode_labels);
+    Ok(func)
+}
+
+fn optimize_circuit<B: Backend>(
     backend: &B,
     circuit: Circuit,
-) -> Result<Circuit, CliError<B>> {
-    let (optimized_circuit, _): (Circuit, Vec<acvm::acir::circuit::OpcodeLabel>) =
+) -> Result<(Circuit, Vec<OpcodeLabel>), CliError<B>> {
+    let (optimized_circuit, opcode_labels) =
         acvm::compiler::compile(circuit, backend.np_language(), |opcode| {
             backend.supports_opcode(opcode)
         })
         .map_err(|_| NargoError::CompilationError)?;
 
-    Ok(optimized_circuit)
+    Ok((optimized_circuit, opcode_labels))
+}
+
+/// Maps the origin of each opcode in an unoptimized circuit onto the opcodes of the optimized
+/// circuit, using the labels returned by the optimizer.
+fn remap_opcode_origins(origins: &[String], opcode_labels: &[OpcodeLabel]) -> Vec<String> {
+    if origins.is_empty() {
+        // Origins were not tracked for this circuit
+        return Vec::new();
+    }
+
+    vecmap(opcode_labels, |label| match label {
+        OpcodeLabel::Resolved(index) => origins[*index as usize].clone(),
+        OpcodeLabel::Unresolved => UNKNOWN_ORIGIN.to_owned(),
+    })
 }
 
 /// Helper function for reporting any errors in a Result<(T, Warnings), ErrorsAndWarnings>
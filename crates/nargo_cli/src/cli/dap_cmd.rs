@@ -0,0 +1,403 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use acvm::Backend;
+use clap::Args;
+use fm::FileId;
+use nargo::ops::{DebugExecutor, DefaultForeignCallExecutor};
+use nargo::NargoError;
+use noirc_abi::input_parser::Format;
+use noirc_driver::{CompileOptions, CompiledProgram};
+use serde_json::{json, Value};
+
+use super::debug_cmd::{compile_for_debugging, Debugger};
+use super::fs::inputs::read_inputs_from_file;
+use super::NargoConfig;
+use crate::{constants::PROVER_INPUT_FILE, errors::CliError, find_package_root};
+
+/// The only thread reported to the client, as a circuit is executed sequentially.
+const THREAD_ID: i64 = 1;
+/// The reference the client uses to request the variables in scope.
+const LOCALS_REFERENCE: i64 = 1;
+
+/// Starts a Debug Adapter Protocol server on stdin and stdout
+///
+/// Editors launch a debugging session with the `projectFolder` of the program to debug, which
+/// defaults to the current package, and optionally the `proverName` of its inputs, `stopOnEntry` and an `oracleResolver`.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct DapCommand {
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+pub(crate) fn run<B: Backend>(
+    backend: &B,
+    args: DapCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let mut connection = Connection::new(io::stdin().lock(), io::stdout().lock());
+    serve(backend, &mut connection, &args.compile_options, &config.program_dir)
+        .map_err(|error| CliError::Generic(format!("Debug adapter connection failed: {error}")))
+}
+
+/// Handles requests until a debugging session has been launched and runs it.
+fn serve<B: Backend>(
+    backend: &B,
+    connection: &mut Connection<impl BufRead, impl Write>,
+    compile_options: &CompileOptions,
+    default_program_dir: &Path,
+) -> io::Result<()> {
+    while let Some(request) = connection.read_message()? {
+        match command(&request) {
+            "initialize" => connection.respond(
+                &request,
+                json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsTerminateRequest": true,
+                }),
+            )?,
+            "launch" => {
+                let arguments = &request["arguments"];
+                let program_dir = arguments["projectFolder"]
+                    .as_str()
+                    .map_or_else(|| default_program_dir.to_path_buf(), PathBuf::from);
+                let prover_name = arguments["proverName"].as_str().unwrap_or(PROVER_INPUT_FILE);
+
+                let launched = find_package_root(&program_dir)
+                    .map_err(CliError::<B>::from)
+                    .and_then(|program_dir| {
+                        let (program, sources) =
+                            compile_for_debugging(&program_dir, compile_options)?;
+                        let (inputs_map, _) = read_inputs_from_file(
+                            &program_dir,
+                            prover_name,
+                            Format::Toml,
+                            &program.abi,
+                        )?;
+                        let initial_witness = program.abi.encode(&inputs_map, None)?;
+                        Ok((program, sources, initial_witness))
+                    });
+                let (program, sources, initial_witness) = match launched {
+                    Ok(launched) => launched,
+                    Err(error) => {
+                        connection.respond_error(&request, &error.to_string())?;
+                        continue;
+                    }
+                };
+
+                let output = SharedOutput::default();
+                let oracle_resolver = arguments["oracleResolver"].as_str();
+                let foreign_call_executor =
+                    match DefaultForeignCallExecutor::new(output.clone(), oracle_resolver, false) {
+                        Ok(executor) => executor,
+                        Err(error) => {
+                            connection.respond_error(&request, &error.to_string())?;
+                            continue;
+                        }
+                    };
+                connection.respond(&request, json!({}))?;
+
+                let executor = DebugExecutor::new(
+                    backend,
+                    &program.circuit.opcodes,
+                    initial_witness,
+                    foreign_call_executor,
+                );
+                let debugger =
+                    Debugger::new(executor, &program.circuit.opcodes, &program.debug, sources);
+                let stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+                return Session { connection, debugger, program: &program, output, stop_on_entry }
+                    .run();
+            }
+            "disconnect" => return connection.respond(&request, json!({})),
+            other => connection
+                .respond_error(&request, &format!("`{other}` requires a launched program"))?,
+        }
+    }
+    Ok(())
+}
+
+/// A debugging session of a launched program.
+struct Session<'a, B: Backend, R, W> {
+    connection: &'a mut Connection<R, W>,
+    debugger: Debugger<'a, B, DefaultForeignCallExecutor<SharedOutput>>,
+    program: &'a CompiledProgram,
+    /// Anything printed by the program, which is forwarded to the client as output events.
+    output: SharedOutput,
+    stop_on_entry: bool,
+}
+
+impl<'a, B: Backend, R: BufRead, W: Write> Session<'a, B, R, W> {
+    fn run(mut self) -> io::Result<()> {
+        let mut breakpoints = HashSet::new();
+        self.connection.send_event("initialized", json!({}))?;
+
+        while let Some(request) = self.connection.read_message()? {
+            match command(&request) {
+                "setBreakpoints" => {
+                    let body = self.set_breakpoints(&request["arguments"], &mut breakpoints);
+                    self.connection.respond(&request, body)?;
+                }
+                "configurationDone" => {
+                    self.connection.respond(&request, json!({}))?;
+                    if self.stop_on_entry {
+                        self.send_stopped("entry")?;
+                    } else {
+                        let result = self.debugger.continue_to(&breakpoints);
+                        self.report(result, "breakpoint")?;
+                    }
+                }
+                "threads" => self.connection.respond(
+                    &request,
+                    json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+                )?,
+                "stackTrace" => {
+                    let body = self.stack_trace();
+                    self.connection.respond(&request, body)?;
+                }
+                "scopes" => self.connection.respond(
+                    &request,
+                    json!({ "scopes": [{
+                        "name": "Locals",
+                        "variablesReference": LOCALS_REFERENCE,
+                        "expensive": false,
+                    }] }),
+                )?,
+                "variables" => {
+                    let variables = self.debugger.variables().into_iter().map(|(name, value)| {
+                        json!({ "name": name, "value": value, "variablesReference": 0 })
+                    });
+                    let body = json!({ "variables": variables.collect::<Vec<_>>() });
+                    self.connection.respond(&request, body)?;
+                }
+                "continue" => {
+                    self.connection.respond(&request, json!({ "allThreadsContinued": true }))?;
+                    let result = self.debugger.continue_to(&breakpoints);
+                    self.report(result, "breakpoint")?;
+                }
+                "next" => {
+                    self.connection.respond(&request, json!({}))?;
+                    let result = self.debugger.step_line();
+                    self.report(result, "step")?;
+                }
+                "stepIn" => {
+                    self.connection.respond(&request, json!({}))?;
+                    let result = self.debugger.executor.step_brillig_opcode();
+                    self.report(result, "step")?;
+                }
+                "disconnect" | "terminate" => return self.connection.respond(&request, json!({})),
+                other => self
+                    .connection
+                    .respond_error(&request, &format!("`{other}` is not supported"))?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the breakpoints of a source file, reporting which lines could be found.
+    fn set_breakpoints(
+        &self,
+        arguments: &Value,
+        breakpoints: &mut HashSet<(FileId, usize)>,
+    ) -> Value {
+        let file = arguments["source"]["path"].as_str().and_then(|path| {
+            let file = self.debugger.sources.find(Path::new(path))?;
+            breakpoints.retain(|(other, _)| *other != file);
+            Some(file)
+        });
+
+        let lines = arguments["breakpoints"].as_array().into_iter().flatten();
+        let lines = lines.filter_map(|breakpoint| breakpoint["line"].as_u64());
+        let results: Vec<_> = lines
+            .map(|line| {
+                let line = line as usize;
+                let file =
+                    file.filter(|file| self.debugger.sources.line_text(*file, line).is_some());
+                if let Some(file) = file {
+                    breakpoints.insert((file, line));
+                }
+                json!({ "verified": file.is_some(), "line": line })
+            })
+            .collect();
+        json!({ "breakpoints": results })
+    }
+
+    /// Reports the single frame of the circuit being executed.
+    fn stack_trace(&self) -> Value {
+        let Some(index) = self.debugger.executor.next_opcode() else {
+            return json!({ "stackFrames": [], "totalFrames": 0 });
+        };
+
+        let name = self.program.opcode_origins.get(index).map_or("main", String::as_str);
+        let mut frame = json!({ "id": index, "name": name, "line": 0, "column": 0 });
+        if let Some((file, line)) = self.debugger.current_line() {
+            let path = self.debugger.sources.path(file);
+            let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            frame["source"] = json!({
+                "name": path.file_name().map(|name| name.to_string_lossy()),
+                "path": path,
+            });
+            frame["line"] = json!(line);
+            frame["column"] = json!(1);
+        }
+        json!({ "stackFrames": [frame], "totalFrames": 1 })
+    }
+
+    /// Reports the outcome of resuming execution to the client.
+    fn report(&mut self, result: Result<(), NargoError>, reason: &str) -> io::Result<()> {
+        self.forward_output()?;
+        match result {
+            Err(error) => {
+                let message = format!("Execution failed: {error}\n");
+                self.connection
+                    .send_event("output", json!({ "category": "stderr", "output": message }))?;
+                self.connection.send_event(
+                    "stopped",
+                    json!({
+                        "reason": "exception",
+                        "description": error.to_string(),
+                        "threadId": THREAD_ID,
+                    }),
+                )
+            }
+            Ok(()) if self.debugger.executor.next_opcode().is_none() => {
+                self.connection.send_event("exited", json!({ "exitCode": 0 }))?;
+                self.connection.send_event("terminated", json!({}))
+            }
+            Ok(()) => self.send_stopped(reason),
+        }
+    }
+
+    fn send_stopped(&mut self, reason: &str) -> io::Result<()> {
+        self.connection.send_event("stopped", json!({ "reason": reason, "threadId": THREAD_ID }))
+    }
+
+    fn forward_output(&mut self) -> io::Result<()> {
+        let output = std::mem::take(&mut *self.output.0.borrow_mut());
+        if output.is_empty() {
+            return Ok(());
+        }
+        let output = String::from_utf8_lossy(&output);
+        self.connection.send_event("output", json!({ "category": "stdout", "output": output }))
+    }
+}
+
+fn command(request: &Value) -> &str {
+    request["command"].as_str().unwrap_or_default()
+}
+
+/// Collects the output of the program, as stdout is reserved for the protocol.
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads and writes Debug Adapter Protocol messages, each of which is a JSON object preceded by
+/// a `Content-Length` header.
+struct Connection<R, W> {
+    input: R,
+    output: W,
+    next_seq: i64,
+}
+
+impl<R: BufRead, W: Write> Connection<R, W> {
+    fn new(input: R, output: W) -> Self {
+        Connection { input, output, next_seq: 1 }
+    }
+
+    /// Reads the next message, or returns None once the client closes the connection.
+    fn read_message(&mut self) -> io::Result<Option<Value>> {
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            if self.input.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header.strip_prefix("Content-Length:") {
+                content_length = length.trim().parse::<usize>().ok();
+            }
+        }
+
+        let content_length = content_length.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "message is missing a Content-Length")
+        })?;
+        let mut content = vec![0; content_length];
+        self.input.read_exact(&mut content)?;
+        serde_json::from_slice(&content).map(Some).map_err(io::Error::from)
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        message["seq"] = json!(self.next_seq);
+        self.next_seq += 1;
+
+        let content = message.to_string();
+        write!(self.output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+        self.output.flush()
+    }
+
+    fn respond(&mut self, request: &Value, body: Value) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": true,
+            "command": request["command"],
+            "body": body,
+        }))
+    }
+
+    fn respond_error(&mut self, request: &Value, message: &str) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": false,
+            "command": request["command"],
+            "message": message,
+        }))
+    }
+
+    fn send_event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Connection;
+
+    #[test]
+    fn messages_are_framed_by_their_content_length() {
+        let input = "Content-Length: 9\r\n\r\n{\"seq\":1}Content-Length: 2\r\n\r\n{}";
+        let mut output = Vec::new();
+        let mut connection = Connection::new(input.as_bytes(), &mut output);
+
+        assert_eq!(connection.read_message().unwrap(), Some(json!({ "seq": 1 })));
+        assert_eq!(connection.read_message().unwrap(), Some(json!({})));
+        assert_eq!(connection.read_message().unwrap(), None);
+
+        connection.send_event("initialized", json!({})).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let (header, content) = output.split_once("\r\n\r\n").unwrap();
+        assert_eq!(header, format!("Content-Length: {}", content.len()));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(content).unwrap(),
+            json!({ "seq": 1, "type": "event", "event": "initialized", "body": {} })
+        );
+    }
+}
@@ -0,0 +1,525 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use acvm::acir::circuit::Opcode;
+use acvm::acir::native_types::Witness;
+use acvm::{Backend, FieldElement};
+use clap::Args;
+use fm::{FileId, FileManager};
+use iter_extended::vecmap;
+use nargo::ops::{DebugExecutor, DefaultForeignCallExecutor, ForeignCallExecutor};
+use nargo::NargoError;
+use noirc_abi::input_parser::Format;
+use noirc_driver::{compile_main, CompileOptions, CompiledProgram};
+use noirc_errors::debug_info::DebugInfo;
+use noirc_errors::Location;
+use noirc_frontend::graph::LOCAL_CRATE;
+
+use super::compile_cmd::report_errors;
+use super::fs::inputs::read_inputs_from_file;
+use super::NargoConfig;
+use crate::{constants::PROVER_INPUT_FILE, errors::CliError, resolver::resolve_root_manifest};
+
+/// Executes a circuit step by step in an interactive debugger
+///
+/// Breakpoints are set on lines of the Noir source, and the values of the variables in scope
+/// can be inspected at each step. Debug information is only available with the experimental
+/// SSA pass, which is always used by this command.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct DebugCommand {
+    /// The name of the toml file which contains the inputs for the prover
+    #[clap(long, short, default_value = PROVER_INPUT_FILE)]
+    prover_name: String,
+
+    /// JSON-RPC endpoint used to resolve calls to `#[oracle]` functions
+    #[arg(long)]
+    oracle_resolver: Option<String>,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+const HELP: &str = "\
+Commands:
+  step, s            execute the next opcode
+  next, n            execute until the next line of source is reached
+  into, i            execute the next instruction of a Brillig opcode
+  continue, c        execute until a breakpoint is reached or the circuit finishes
+  break, b <line>    set a breakpoint on a line of the main file, or on `<file>:<line>`
+  delete, d <line>   remove a breakpoint
+  breakpoints        list the breakpoints
+  vars, v            show the values of the variables in scope
+  witness, w [index] show the solved witnesses, or a single witness
+  opcodes            list the opcodes of the circuit
+  help, h            show this message
+  quit, q            stop debugging";
+
+pub(crate) fn run<B: Backend>(
+    backend: &B,
+    args: DebugCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let (CompiledProgram { circuit, abi, debug, .. }, sources) =
+        compile_for_debugging(&config.program_dir, &args.compile_options)?;
+
+    let (inputs_map, _) =
+        read_inputs_from_file(&config.program_dir, &args.prover_name, Format::Toml, &abi)?;
+    let initial_witness = abi.encode(&inputs_map, None)?;
+
+    let foreign_call_executor =
+        DefaultForeignCallExecutor::new(std::io::stdout(), args.oracle_resolver.as_deref(), false)
+            .map_err(NargoError::from)?;
+
+    let executor =
+        DebugExecutor::new(backend, &circuit.opcodes, initial_witness, foreign_call_executor);
+    let mut debugger = Debugger::new(executor, &circuit.opcodes, &debug, sources);
+
+    println!(
+        "Debugging circuit with {} opcodes, type `help` for a list of commands",
+        circuit.opcodes.len()
+    );
+    debugger.run(std::io::stdin().lock());
+
+    if debugger.executor.next_opcode().is_none() {
+        let solved_witness = debugger.executor.witness_map().clone();
+        let (_, return_value) = abi.public_abi().decode(&solved_witness)?;
+        println!("Circuit witness successfully solved");
+        if let Some(return_value) = return_value {
+            println!("Circuit output: {return_value:?}");
+        }
+    }
+    Ok(())
+}
+
+/// Compiles the program in `program_dir` for debugging, returning it alongside the source files
+/// its opcodes were generated from.
+pub(super) fn compile_for_debugging<B: Backend>(
+    program_dir: &Path,
+    compile_options: &CompileOptions,
+) -> Result<(CompiledProgram, SourceFiles), CliError<B>> {
+    // Only the experimental SSA pass relates opcodes back to the source.
+    let compile_options = CompileOptions { experimental_ssa: true, ..compile_options.clone() };
+
+    let mut context = resolve_root_manifest(program_dir)?;
+    let result = compile_main(&mut context, &compile_options);
+    // The circuit is not optimized by the backend, so that each opcode can be stepped through
+    // as it was generated from the source.
+    let program = report_errors(result, &context, compile_options.deny_warnings)?;
+
+    let root_file = context.crate_graph[LOCAL_CRATE].root_file_id;
+    let sources = SourceFiles::new(&mut context.file_manager, &program.debug, root_file);
+    Ok((program, sources))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Step,
+    Next,
+    Into,
+    Continue,
+    Break(LineSpec),
+    Delete(LineSpec),
+    Breakpoints,
+    Vars,
+    Witness(Option<u32>),
+    Opcodes,
+    Help,
+    Quit,
+}
+
+/// A line of a source file, which defaults to the main file of the crate.
+#[derive(Debug, PartialEq, Eq)]
+struct LineSpec {
+    file: Option<String>,
+    line: usize,
+}
+
+impl Command {
+    fn parse(input: &str) -> Result<Command, String> {
+        let mut words = input.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let argument = words.next();
+        if words.next().is_some() {
+            return Err(format!("too many arguments to `{command}`"));
+        }
+
+        let line_spec = || -> Result<LineSpec, String> {
+            let argument = argument.ok_or_else(|| format!("`{command}` expects a line"))?;
+            let (file, line) = match argument.rsplit_once(':') {
+                Some((file, line)) => (Some(file.to_owned()), line),
+                None => (None, argument),
+            };
+            let line = line.parse().map_err(|_| format!("`{line}` is not a line number"))?;
+            Ok(LineSpec { file, line })
+        };
+        let no_argument = |command| match argument {
+            Some(_) => Err(format!("`{input}` does not take an argument")),
+            None => Ok(command),
+        };
+
+        match command {
+            "step" | "s" => no_argument(Command::Step),
+            "next" | "n" => no_argument(Command::Next),
+            "into" | "i" => no_argument(Command::Into),
+            "continue" | "c" => no_argument(Command::Continue),
+            "break" | "b" => Ok(Command::Break(line_spec()?)),
+            "delete" | "d" => Ok(Command::Delete(line_spec()?)),
+            "breakpoints" => no_argument(Command::Breakpoints),
+            "vars" | "v" => no_argument(Command::Vars),
+            "witness" | "w" => match argument {
+                Some(index) => {
+                    let index = index.trim_start_matches('_');
+                    let index = index.parse().map_err(|_| format!("`{index}` is not a witness"))?;
+                    Ok(Command::Witness(Some(index)))
+                }
+                None => Ok(Command::Witness(None)),
+            },
+            "opcodes" => no_argument(Command::Opcodes),
+            "help" | "h" => no_argument(Command::Help),
+            "quit" | "q" => no_argument(Command::Quit),
+            _ => Err(format!("unknown command `{command}`, type `help` for a list of commands")),
+        }
+    }
+}
+
+struct SourceFile {
+    path: PathBuf,
+    source: String,
+}
+
+/// The source files of the opcodes being debugged, used to show and set breakpoints on lines.
+pub(super) struct SourceFiles {
+    files: HashMap<FileId, SourceFile>,
+    root_file: FileId,
+}
+
+impl SourceFiles {
+    fn new(file_manager: &mut FileManager, debug: &DebugInfo, root_file: FileId) -> Self {
+        let file_ids = debug.opcode_locations.iter().flatten().map(|location| location.file);
+        let files = file_ids
+            .chain(std::iter::once(root_file))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|file_id| {
+                let path = file_manager.path(file_id).to_path_buf();
+                let source = file_manager.fetch_file(file_id).source().to_owned();
+                (file_id, SourceFile { path, source })
+            })
+            .collect();
+        SourceFiles { files, root_file }
+    }
+
+    /// Returns the file and 1-based line number `location` starts on.
+    pub(super) fn line(&self, location: Location) -> Option<(FileId, usize)> {
+        let source = &self.files.get(&location.file)?.source;
+        let preceding = source.get(..location.span.start() as usize)?;
+        Some((location.file, preceding.matches('\n').count() + 1))
+    }
+
+    pub(super) fn line_text(&self, file: FileId, line: usize) -> Option<&str> {
+        self.files.get(&file)?.source.lines().nth(line.checked_sub(1)?)
+    }
+
+    pub(super) fn path(&self, file: FileId) -> &Path {
+        &self.files[&file].path
+    }
+
+    /// Returns the file at `path`, which may be relative to the current directory.
+    pub(super) fn find(&self, path: &Path) -> Option<FileId> {
+        let path = path.canonicalize().ok()?;
+        let (file_id, _) = self
+            .files
+            .iter()
+            .find(|(_, file)| file.path.canonicalize().ok() == Some(path.clone()))?;
+        Some(*file_id)
+    }
+
+    /// Resolves `spec` to a line of one of the files being debugged.
+    fn resolve(&self, spec: &LineSpec) -> Result<(FileId, usize), String> {
+        let file = match &spec.file {
+            None => self.root_file,
+            Some(name) => self
+                .files
+                .iter()
+                .find(|(_, file)| file.path.ends_with(name))
+                .map(|(file_id, _)| *file_id)
+                .ok_or_else(|| format!("no opcodes were generated from a file named `{name}`"))?,
+        };
+        if self.line_text(file, spec.line).is_none() {
+            return Err(format!("{} has no line {}", self.path(file).display(), spec.line));
+        }
+        Ok((file, spec.line))
+    }
+}
+
+/// Steps through the execution of a circuit in terms of the source it was generated from.
+pub(super) struct Debugger<'a, B: Backend, F: ForeignCallExecutor> {
+    pub(super) executor: DebugExecutor<'a, B, F>,
+    opcodes: &'a [Opcode],
+    debug: &'a DebugInfo,
+    pub(super) sources: SourceFiles,
+}
+
+impl<'a, B: Backend, F: ForeignCallExecutor> Debugger<'a, B, F> {
+    pub(super) fn new(
+        executor: DebugExecutor<'a, B, F>,
+        opcodes: &'a [Opcode],
+        debug: &'a DebugInfo,
+        sources: SourceFiles,
+    ) -> Self {
+        Debugger { executor, opcodes, debug, sources }
+    }
+
+    /// Returns the file and line of the next opcode, if it was generated from the source.
+    pub(super) fn current_line(&self) -> Option<(FileId, usize)> {
+        let location = self.debug.location(self.executor.next_opcode()?)?;
+        self.sources.line(location)
+    }
+
+    /// Executes opcodes until reaching the first opcode of a line with a breakpoint.
+    pub(super) fn continue_to(
+        &mut self,
+        breakpoints: &HashSet<(FileId, usize)>,
+    ) -> Result<(), NargoError> {
+        let mut current_line = self.current_line();
+        while self.executor.next_opcode().is_some() {
+            self.executor.step_opcode()?;
+
+            let line = self.current_line();
+            if line != current_line && line.map_or(false, |line| breakpoints.contains(&line)) {
+                break;
+            }
+            current_line = line;
+        }
+        Ok(())
+    }
+
+    /// Executes opcodes until reaching the first opcode of a different source line.
+    pub(super) fn step_line(&mut self) -> Result<(), NargoError> {
+        let current_line = self.current_line();
+        while self.executor.next_opcode().is_some() {
+            self.executor.step_opcode()?;
+
+            let line = self.current_line();
+            if line.is_some() && line != current_line {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the name and value of each variable in scope, where later definitions of a
+    /// variable shadow earlier ones. Values which cannot be evaluated yet are shown as `?`.
+    pub(super) fn variables(&self) -> Vec<(String, String)> {
+        let index = self.executor.next_opcode().unwrap_or(self.opcodes.len());
+
+        let mut variables: Vec<_> = Vec::new();
+        for variable in self.debug.variables_in_scope(index) {
+            let name = format!("{}::{}", variable.function, variable.name);
+            variables.retain(|(other, _)| *other != name);
+            variables.push((name, variable));
+        }
+
+        vecmap(variables, |(name, variable)| {
+            let values = vecmap(&variable.value, |expression| {
+                self.executor.evaluate(expression).map_or("?".to_owned(), format_field)
+            });
+            match values.as_slice() {
+                [value] => (name, value.clone()),
+                values => (name, format!("[{}]", values.join(", "))),
+            }
+        })
+    }
+
+    fn run(&mut self, input: impl BufRead) {
+        let mut breakpoints = HashSet::new();
+        self.show_position();
+
+        let mut lines = input.lines();
+        loop {
+            print!("> ");
+            std::io::stdout().flush().expect("failed to flush stdout");
+            let Some(Ok(line)) = lines.next() else { break };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let command = match Command::parse(&line) {
+                Ok(command) => command,
+                Err(error) => {
+                    println!("{error}");
+                    continue;
+                }
+            };
+
+            match command {
+                Command::Step => self.step(|debugger| debugger.executor.step_opcode()),
+                Command::Next => self.step(|debugger| debugger.step_line()),
+                Command::Into => self.step(|debugger| debugger.executor.step_brillig_opcode()),
+                Command::Continue => self.step(|debugger| debugger.continue_to(&breakpoints)),
+                Command::Break(spec) => match self.sources.resolve(&spec) {
+                    Ok(line) => {
+                        breakpoints.insert(line);
+                    }
+                    Err(error) => println!("{error}"),
+                },
+                Command::Delete(spec) => match self.sources.resolve(&spec) {
+                    Ok(line) if breakpoints.remove(&line) => (),
+                    Ok(_) => println!("there is no breakpoint on line {}", spec.line),
+                    Err(error) => println!("{error}"),
+                },
+                Command::Breakpoints => {
+                    let mut lines: Vec<_> = breakpoints
+                        .iter()
+                        .map(|(file, line)| (self.sources.path(*file), *line))
+                        .collect();
+                    lines.sort();
+                    for (path, line) in lines {
+                        println!("{}:{line}", path.display());
+                    }
+                }
+                Command::Vars => self.show_variables(),
+                Command::Witness(index) => self.show_witness(index),
+                Command::Opcodes => self.show_opcodes(),
+                Command::Help => println!("{HELP}"),
+                Command::Quit => break,
+            }
+        }
+    }
+
+    /// Resumes execution using `step`, showing where execution stopped.
+    fn step(&mut self, step: impl FnOnce(&mut Self) -> Result<(), NargoError>) {
+        if self.executor.next_opcode().is_none() {
+            println!("Execution has finished");
+            return;
+        }
+        match step(self) {
+            Ok(()) => self.show_position(),
+            Err(error) => println!("Execution failed: {error}"),
+        }
+    }
+
+    fn show_position(&self) {
+        let Some(index) = self.executor.next_opcode() else {
+            println!("Execution has finished");
+            return;
+        };
+
+        println!("Opcode {index}: {}", self.opcodes[index]);
+        if let (Some((pc, registers)), Opcode::Brillig(brillig)) =
+            (self.executor.brillig_state(), &self.opcodes[index])
+        {
+            println!("  Brillig instruction {pc}: {:?}", brillig.bytecode[pc]);
+            let registers = registers.iter().map(|register| format_field(register.to_field()));
+            println!("  Registers: [{}]", registers.collect::<Vec<_>>().join(", "));
+        }
+
+        if let Some((file, line)) = self.current_line() {
+            println!("  at {}:{line}", self.sources.path(file).display());
+            if let Some(text) = self.sources.line_text(file, line) {
+                println!("{line:>5} | {}", text.trim_end());
+            }
+        }
+    }
+
+    fn show_variables(&self) {
+        for (name, value) in self.variables() {
+            println!("{name} = {value}");
+        }
+    }
+
+    fn show_witness(&self, index: Option<u32>) {
+        let witness_map = self.executor.witness_map();
+        match index {
+            Some(index) => match witness_map.get(&Witness(index)) {
+                Some(value) => println!("_{index} = {}", format_field(*value)),
+                None => println!("_{index} has not been solved"),
+            },
+            None => {
+                for (witness, value) in witness_map.clone() {
+                    println!("_{} = {}", witness.witness_index(), format_field(value));
+                }
+            }
+        }
+    }
+
+    fn show_opcodes(&self) {
+        let next_opcode = self.executor.next_opcode();
+        for (index, opcode) in self.opcodes.iter().enumerate() {
+            let marker = if Some(index) == next_opcode { "->" } else { "  " };
+            println!("{marker} {index:>4}: {opcode}");
+        }
+    }
+}
+
+/// Formats small values in decimal and large values, such as negative field elements, in hex.
+pub(super) fn format_field(value: FieldElement) -> String {
+    if value.num_bits() <= 64 {
+        value.to_u128().to_string()
+    } else {
+        format!("0x{}", value.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use nargo::ops::{DebugExecutor, DefaultForeignCallExecutor};
+    use noirc_abi::input_parser::Format;
+    use noirc_driver::{CompileOptions, CompiledProgram};
+
+    use super::{compile_for_debugging, Command, Debugger, LineSpec};
+    use crate::backends::ConcreteBackend;
+    use crate::cli::fs::inputs::read_inputs_from_file;
+    use crate::constants::PROVER_INPUT_FILE;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Command::parse("s"), Ok(Command::Step));
+        assert_eq!(Command::parse("next"), Ok(Command::Next));
+        assert_eq!(Command::parse("  continue "), Ok(Command::Continue));
+        assert_eq!(Command::parse("b 12"), Ok(Command::Break(LineSpec { file: None, line: 12 })));
+        assert_eq!(
+            Command::parse("delete src/foo.nr:3"),
+            Ok(Command::Delete(LineSpec { file: Some("src/foo.nr".to_owned()), line: 3 }))
+        );
+        assert_eq!(Command::parse("witness _7"), Ok(Command::Witness(Some(7))));
+        assert_eq!(Command::parse("w"), Ok(Command::Witness(None)));
+
+        assert!(Command::parse("break").is_err());
+        assert!(Command::parse("break main.nr:x").is_err());
+        assert!(Command::parse("step 2").is_err());
+        assert!(Command::parse("jump").is_err());
+    }
+
+    #[test]
+    fn runs_scripted_session() {
+        let program_dir =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data_ssa_refactor/1_mul");
+        let Ok((CompiledProgram { circuit, abi, debug, .. }, sources)) =
+            compile_for_debugging::<ConcreteBackend>(&program_dir, &CompileOptions::default())
+        else {
+            panic!("failed to compile {}", program_dir.display());
+        };
+        let (inputs_map, _) =
+            read_inputs_from_file(&program_dir, PROVER_INPUT_FILE, Format::Toml, &abi).unwrap();
+        let initial_witness = abi.encode(&inputs_map, None).unwrap();
+
+        let backend = ConcreteBackend::default();
+        let foreign_call_executor =
+            DefaultForeignCallExecutor::new(std::io::sink(), None, false).unwrap();
+        let executor =
+            DebugExecutor::new(&backend, &circuit.opcodes, initial_witness, foreign_call_executor);
+        let mut debugger = Debugger::new(executor, &circuit.opcodes, &debug, sources);
+        let root_file = debugger.sources.root_file;
+
+        // Execution stops on the first opcode of the line with the breakpoint.
+        debugger.run("b 6\nc\n".as_bytes());
+        assert_eq!(debugger.current_line(), Some((root_file, 6)));
+
+        debugger.run("c\n".as_bytes());
+        assert_eq!(debugger.executor.next_opcode(), None);
+    }
+}
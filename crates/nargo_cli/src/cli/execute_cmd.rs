@@ -1,18 +1,31 @@
 use std::path::Path;
 
-use acvm::acir::{circuit::Circuit, native_types::WitnessMap};
-use acvm::Backend;
-use clap::Args;
-use noirc_abi::input_parser::{Format, InputValue};
+use acvm::acir::{
+    circuit::Circuit,
+    native_types::{Witness, WitnessMap},
+};
+use acvm::{Backend, FieldElement};
+use clap::{Args, ValueEnum};
+use nargo::ops::{
+    execute_circuit, execute_circuit_with_trace, DefaultForeignCallExecutor, OpcodeTrace,
+};
+use nargo::NargoError;
+use noirc_abi::input_parser::Format;
 use noirc_abi::{Abi, InputMap};
-use noirc_driver::{CompileOptions, CompiledProgram};
+use noirc_driver::{create_ssa_interpreter, CompileOptions, CompiledProgram};
+use noirc_frontend::graph::LOCAL_CRATE;
 
-use super::fs::{inputs::read_inputs_from_file, witness::save_witness_to_dir};
+use super::debug_cmd::format_field;
+use super::fs::{
+    inputs::read_inputs_from_file,
+    witness::{save_witness_json_to_dir, save_witness_to_dir},
+};
 use super::NargoConfig;
 use crate::{
-    cli::compile_cmd::compile_circuit,
+    cli::{check_cmd::check_crate_and_report_errors, compile_cmd::compile_circuit},
     constants::{PROVER_INPUT_FILE, TARGET_DIR},
     errors::CliError,
+    resolver::resolve_root_manifest,
 };
 
 /// Executes a circuit to calculate its return value
@@ -25,26 +38,74 @@ pub(crate) struct ExecuteCommand {
     #[clap(long, short, default_value = PROVER_INPUT_FILE)]
     prover_name: String,
 
+    /// JSON-RPC endpoint used to resolve calls to `#[oracle]` functions
+    #[arg(long)]
+    oracle_resolver: Option<String>,
+
+    /// Print each opcode as it is solved, along with the witnesses it reads and writes
+    #[arg(long)]
+    trace: bool,
+
+    /// Interpret the program's SSA instead of executing its circuit
+    #[arg(long, conflicts_with = "trace")]
+    ssa_interpret: bool,
+
+    /// The format the execution witness is written in
+    #[arg(long, value_enum, default_value_t = WitnessFormat::Binary)]
+    witness_format: WitnessFormat,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum WitnessFormat {
+    /// The serialized witness map, as read by the backend
+    Binary,
+    /// The values of the circuit's parameters and return value along with the witness map
+    Json,
+}
+
 pub(crate) fn run<B: Backend>(
     backend: &B,
     args: ExecuteCommand,
     config: NargoConfig,
 ) -> Result<(), CliError<B>> {
-    let (return_value, solved_witness) =
-        execute_with_path(backend, &config.program_dir, args.prover_name, &args.compile_options)?;
+    let (abi, solved_witness) = if args.ssa_interpret {
+        interpret_with_path(&config.program_dir, args.prover_name, &args.compile_options)?
+    } else {
+        execute_with_path(
+            backend,
+            &config.program_dir,
+            args.prover_name,
+            args.oracle_resolver.as_deref(),
+            args.trace,
+            &args.compile_options,
+        )?
+    };
+
+    let (input_map, return_value) = abi.decode(&solved_witness)?;
 
     println!("Circuit witness successfully solved");
-    if let Some(return_value) = return_value {
+    if let Some(return_value) = &return_value {
         println!("Circuit output: {return_value:?}");
     }
     if let Some(witness_name) = args.witness_name {
         let witness_dir = config.program_dir.join(TARGET_DIR);
 
-        let witness_path = save_witness_to_dir(solved_witness, &witness_name, witness_dir)?;
+        let witness_path = match args.witness_format {
+            WitnessFormat::Binary => {
+                save_witness_to_dir(solved_witness, &witness_name, witness_dir)?
+            }
+            WitnessFormat::Json => save_witness_json_to_dir(
+                &solved_witness,
+                &input_map,
+                &return_value,
+                &abi,
+                &witness_name,
+                witness_dir,
+            )?,
+        };
 
         println!("Witness saved to {}", witness_path.display());
     }
@@ -55,20 +116,48 @@ fn execute_with_path<B: Backend>(
     backend: &B,
     program_dir: &Path,
     prover_name: String,
+    oracle_resolver: Option<&str>,
+    trace: bool,
     compile_options: &CompileOptions,
-) -> Result<(Option<InputValue>, WitnessMap), CliError<B>> {
-    let CompiledProgram { abi, circuit } = compile_circuit(backend, program_dir, compile_options)?;
+) -> Result<(Abi, WitnessMap), CliError<B>> {
+    let CompiledProgram { abi, circuit, .. } =
+        compile_circuit(backend, program_dir, compile_options)?;
 
     // Parse the initial witness values from Prover.toml
     let (inputs_map, _) =
         read_inputs_from_file(program_dir, prover_name.as_str(), Format::Toml, &abi)?;
 
-    let solved_witness = execute_program(backend, circuit, &abi, &inputs_map)?;
+    let solved_witness = if trace {
+        trace_program(backend, &circuit, &abi, &inputs_map, oracle_resolver)?
+    } else {
+        execute_program(backend, circuit, &abi, &inputs_map, oracle_resolver)?
+    };
+
+    Ok((abi, solved_witness))
+}
+
+/// Runs the program's `main` function through the SSA interpreter, producing the witness of the
+/// parameters and return value which executing its circuit would have assigned.
+fn interpret_with_path<B: Backend>(
+    program_dir: &Path,
+    prover_name: String,
+    compile_options: &CompileOptions,
+) -> Result<(Abi, WitnessMap), CliError<B>> {
+    let mut context = resolve_root_manifest(program_dir)?;
+    check_crate_and_report_errors(&mut context, compile_options.deny_warnings, true)?;
+    let main_function = context.get_main_function(&LOCAL_CRATE).ok_or_else(|| {
+        CliError::Generic("cannot interpret a crate without a main function".to_owned())
+    })?;
 
-    let public_abi = abi.public_abi();
-    let (_, return_value) = public_abi.decode(&solved_witness)?;
+    let interpreter = create_ssa_interpreter(&context, main_function);
+    let abi = interpreter.abi().clone();
 
-    Ok((return_value, solved_witness))
+    let (inputs_map, _) =
+        read_inputs_from_file(program_dir, prover_name.as_str(), Format::Toml, &abi)?;
+    let initial_witness = abi.encode(&inputs_map, None)?;
+    let solved_witness = interpreter.execute(&initial_witness)?;
+
+    Ok((abi, solved_witness))
 }
 
 pub(crate) fn execute_program<B: Backend>(
@@ -76,10 +165,57 @@ pub(crate) fn execute_program<B: Backend>(
     circuit: Circuit,
     abi: &Abi,
     inputs_map: &InputMap,
+    oracle_resolver: Option<&str>,
 ) -> Result<WitnessMap, CliError<B>> {
     let initial_witness = abi.encode(inputs_map, None)?;
 
-    let solved_witness = nargo::ops::execute_circuit(backend, circuit, initial_witness)?;
+    let mut foreign_call_executor =
+        DefaultForeignCallExecutor::new(std::io::stdout(), oracle_resolver, false)
+            .map_err(NargoError::from)?;
+    let solved_witness =
+        execute_circuit(backend, circuit, initial_witness, &mut foreign_call_executor)?;
 
     Ok(solved_witness)
 }
+
+/// Executes the circuit like [`execute_program`], printing the trace of its execution up to the
+/// point where it succeeds or fails.
+fn trace_program<B: Backend>(
+    backend: &B,
+    circuit: &Circuit,
+    abi: &Abi,
+    inputs_map: &InputMap,
+    oracle_resolver: Option<&str>,
+) -> Result<WitnessMap, CliError<B>> {
+    let initial_witness = abi.encode(inputs_map, None)?;
+
+    let foreign_call_executor =
+        DefaultForeignCallExecutor::new(std::io::stdout(), oracle_resolver, false)
+            .map_err(NargoError::from)?;
+    let mut trace = Vec::new();
+    let solved_witness = execute_circuit_with_trace(
+        backend,
+        circuit,
+        initial_witness,
+        foreign_call_executor,
+        &mut trace,
+    );
+
+    for opcode_trace in &trace {
+        print_opcode_trace(opcode_trace);
+    }
+    Ok(solved_witness?)
+}
+
+fn print_opcode_trace(opcode_trace: &OpcodeTrace) {
+    let format_witnesses = |witnesses: &[(Witness, FieldElement)]| {
+        let witnesses = witnesses.iter().map(|(witness, value)| {
+            format!("_{} = {}", witness.witness_index(), format_field(*value))
+        });
+        witnesses.collect::<Vec<_>>().join(", ")
+    };
+
+    println!("[{}] {}", opcode_trace.index, opcode_trace.opcode);
+    println!("    inputs:  {}", format_witnesses(&opcode_trace.inputs));
+    println!("    outputs: {}", format_witnesses(&opcode_trace.outputs));
+}
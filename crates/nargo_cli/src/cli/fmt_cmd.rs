@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use acvm::Backend;
+use clap::Args;
+use fm::FileManager;
+use noirc_errors::reporter::report_all;
+
+use super::fs::write_to_file;
+use super::NargoConfig;
+use crate::{constants::SRC_DIR, errors::CliError, find_package_manifest, manifest};
+
+/// Formats the Noir source files of the package
+#[derive(Debug, Clone, Args)]
+pub(crate) struct FormatCommand {
+    /// Check whether the source files are formatted without changing them
+    #[arg(long)]
+    check: bool,
+}
+
+pub(crate) fn run<B: Backend>(
+    // Backend is currently unused, but we might want to use it to inform the formatter in the future
+    _backend: &B,
+    args: FormatCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let manifest_path = find_package_manifest(&config.program_dir)?;
+    let fmt_config = manifest::parse(manifest_path)?.fmt;
+
+    let mut file_manager = FileManager::default();
+    let mut unformatted_files = Vec::new();
+
+    for path in source_files(&config.program_dir.join(SRC_DIR)) {
+        let file_id = file_manager
+            .add_file(&path)
+            .unwrap_or_else(|| panic!("couldn't read {}", path.display()));
+        let source = file_manager.fetch_file(file_id).source().to_owned();
+
+        let formatted = nargo_fmt::format(&source, &fmt_config).map_err(|errors| {
+            let diagnostics: Vec<_> =
+                errors.into_iter().map(|error| error.in_file(file_id)).collect();
+            report_all(&file_manager, &diagnostics, false)
+        })?;
+
+        if formatted == source {
+            continue;
+        }
+
+        if args.check {
+            println!("{} is not formatted", path.display());
+            unformatted_files.push(path);
+        } else {
+            write_to_file(formatted.as_bytes(), &path);
+        }
+    }
+
+    if unformatted_files.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::Generic(format!(
+            "{} file(s) need formatting, run `nargo fmt` to format them",
+            unformatted_files.len()
+        )))
+    }
+}
+
+/// Returns the paths of all Noir source files under `dir`, in a stable order.
+fn source_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(source_files(&path));
+        } else if path.extension().map_or(false, |extension| extension == fm::FILE_EXTENSION) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
@@ -1,6 +1,14 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use acvm::acir::native_types::WitnessMap;
+use noirc_abi::{
+    input_parser::{Format, InputValue},
+    Abi, InputMap, MAIN_RETURN_NAME,
+};
+use serde_json::json;
 
 use super::{create_named_dir, write_to_file};
 use crate::{constants::WITNESS_EXT, errors::FilesystemError};
@@ -20,6 +28,39 @@ pub(crate) fn save_witness_to_dir<P: AsRef<Path>>(
     Ok(witness_path)
 }
 
+/// Writes the solved witness to a JSON file, both as the values of the circuit's parameters and
+/// return value, keyed by their names in the ABI, and as a map from witness indices to values.
+pub(crate) fn save_witness_json_to_dir<P: AsRef<Path>>(
+    witnesses: &WitnessMap,
+    input_map: &InputMap,
+    return_value: &Option<InputValue>,
+    abi: &Abi,
+    witness_name: &str,
+    witness_dir: P,
+) -> Result<PathBuf, FilesystemError> {
+    create_named_dir(witness_dir.as_ref(), "witness");
+    let witness_path = witness_dir.as_ref().join(witness_name).with_extension(Format::Json.ext());
+
+    let mut input_map = input_map.clone();
+    if let Some(return_value) = return_value {
+        input_map.insert(MAIN_RETURN_NAME.to_owned(), return_value.clone());
+    }
+    let inputs: serde_json::Value = serde_json::from_str(&Format::Json.serialize(&input_map, abi)?)
+        .expect("serialized inputs should be valid JSON");
+
+    let witnesses: BTreeMap<u32, String> = witnesses
+        .clone()
+        .into_iter()
+        .map(|(witness, value)| (witness.witness_index(), format!("0x{}", value.to_hex())))
+        .collect();
+
+    let json = json!({ "inputs": inputs, "witnesses": witnesses });
+    let json = serde_json::to_string_pretty(&json).expect("could not serialize witness");
+    write_to_file(json.as_bytes(), &witness_path);
+
+    Ok(witness_path)
+}
+
 #[cfg(not(feature = "flat_witness"))]
 fn serialize_witness_map(witnesses: WitnessMap) -> Result<Vec<u8>, FilesystemError> {
     let buf: Vec<u8> = witnesses.try_into()?;
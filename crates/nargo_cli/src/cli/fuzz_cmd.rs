@@ -0,0 +1,283 @@
+use acvm::{acir::circuit::Circuit, Backend, FieldElement};
+use clap::Args;
+use nargo::ops::{execute_circuit, DefaultForeignCallExecutor};
+use noirc_abi::{
+    input_parser::{Format, InputValue},
+    Abi, AbiType, InputMap,
+};
+use noirc_driver::{CompileOptions, CompiledProgram};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::fs::{create_named_dir, inputs::write_inputs_to_file};
+use super::NargoConfig;
+use crate::{
+    cli::compile_cmd::compile_circuit,
+    constants::{COUNTEREXAMPLE_FILE, TARGET_DIR},
+    errors::CliError,
+};
+
+/// The maximum number of candidate inputs executed while shrinking a counter-example
+const MAX_SHRINK_STEPS: usize = 1024;
+
+/// Executes the circuit with random inputs, looking for inputs which cause it to fail
+///
+/// The smallest failing inputs found are written to `target/Counterexample.toml`.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct FuzzCommand {
+    /// The number of random inputs to execute the circuit with
+    #[arg(long, default_value_t = 256)]
+    runs: usize,
+
+    /// The seed used to generate inputs, defaults to a random seed
+    #[arg(long)]
+    seed: Option<u64>,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+pub(crate) fn run<B: Backend>(
+    backend: &B,
+    args: FuzzCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let CompiledProgram { circuit, abi, .. } =
+        compile_circuit(backend, &config.program_dir, &args.compile_options)?;
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    println!("Fuzzing circuit with {} runs (seed: {seed})", args.runs);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for run in 1..=args.runs {
+        let inputs = random_inputs(&mut rng, &abi);
+        let Err(error) = execute(backend, &circuit, &abi, &inputs) else { continue };
+        println!("Run {run} failed: {error}");
+
+        // Only keep simplifications which fail in the same way, so that shrinking doesn't wander
+        // off to a different bug.
+        let inputs = shrink(&abi, inputs, |candidate| {
+            execute(backend, &circuit, &abi, candidate).err().as_ref() == Some(&error)
+        });
+
+        let target_dir = create_named_dir(&config.program_dir.join(TARGET_DIR), "target");
+        write_inputs_to_file(&inputs, &None, &abi, &target_dir, COUNTEREXAMPLE_FILE, Format::Toml)?;
+
+        return Err(CliError::Generic(format!(
+            "found a counter-example, saved to {}",
+            target_dir.join(COUNTEREXAMPLE_FILE).with_extension(Format::Toml.ext()).display()
+        )));
+    }
+
+    println!("All {} runs passed", args.runs);
+    Ok(())
+}
+
+/// Executes the circuit with the given inputs, returning the error message if it fails.
+fn execute<B: Backend>(
+    backend: &B,
+    circuit: &Circuit,
+    abi: &Abi,
+    inputs: &InputMap,
+) -> Result<(), String> {
+    let initial_witness = abi.encode(inputs, None).map_err(|error| error.to_string())?;
+
+    // Anything the circuit prints would drown out the fuzzer's own output.
+    let mut foreign_call_executor = DefaultForeignCallExecutor::new(std::io::sink(), None, false)
+        .expect("no oracle resolver is used");
+    execute_circuit(backend, circuit.clone(), initial_witness, &mut foreign_call_executor)
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
+
+fn random_inputs(rng: &mut impl Rng, abi: &Abi) -> InputMap {
+    abi.parameters.iter().map(|param| (param.name.clone(), random_value(rng, &param.typ))).collect()
+}
+
+fn random_value(rng: &mut impl Rng, typ: &AbiType) -> InputValue {
+    match typ {
+        AbiType::Field => InputValue::Field(random_field(rng, FieldElement::max_num_bits())),
+        // Signed integers are encoded in two's complement so any `width` bit value is valid.
+        AbiType::Integer { width, .. } => InputValue::Field(random_field(rng, *width)),
+        AbiType::Boolean => {
+            InputValue::Field(if rng.gen() { FieldElement::one() } else { FieldElement::zero() })
+        }
+        AbiType::Array { length, typ } => {
+            InputValue::Vec((0..*length).map(|_| random_value(rng, typ)).collect())
+        }
+        AbiType::Struct { fields } => InputValue::Struct(
+            fields.iter().map(|(name, typ)| (name.clone(), random_value(rng, typ))).collect(),
+        ),
+        AbiType::String { length } => {
+            InputValue::String((0..*length).map(|_| rng.gen_range(b' '..=b'~') as char).collect())
+        }
+    }
+}
+
+/// Returns a random value of at most `bits` bits, biased towards the edge cases zero, one and
+/// the largest value.
+fn random_field(rng: &mut impl Rng, bits: u32) -> FieldElement {
+    let mut bytes = vec![0u8; ((bits + 7) / 8) as usize];
+    match rng.gen_range(0..8) {
+        0 => (),
+        1 => bytes[bytes.len() - 1] = 1,
+        2 => bytes.fill(u8::MAX),
+        _ => rng.fill(&mut bytes[..]),
+    }
+    if bits % 8 != 0 {
+        bytes[0] &= (1 << (bits % 8)) - 1;
+    }
+    FieldElement::from_be_bytes_reduce(&bytes)
+}
+
+/// Greedily simplifies `inputs` for as long as the simplified inputs still `fail`.
+fn shrink(abi: &Abi, mut inputs: InputMap, mut fails: impl FnMut(&InputMap) -> bool) -> InputMap {
+    let mut steps = 0;
+    'simplify: while steps < MAX_SHRINK_STEPS {
+        for param in &abi.parameters {
+            for value in simpler_values(&inputs[&param.name], &param.typ) {
+                let mut candidate = inputs.clone();
+                candidate.insert(param.name.clone(), value);
+
+                steps += 1;
+                if fails(&candidate) {
+                    inputs = candidate;
+                    continue 'simplify;
+                }
+                if steps == MAX_SHRINK_STEPS {
+                    break 'simplify;
+                }
+            }
+        }
+        // No simplification of any parameter still fails.
+        break;
+    }
+    inputs
+}
+
+/// Returns the values which are one step simpler than `value`, each differing in a single element.
+fn simpler_values(value: &InputValue, typ: &AbiType) -> Vec<InputValue> {
+    match (value, typ) {
+        (InputValue::Field(field), _) => {
+            let mut simpler = Vec::new();
+            if !field.is_zero() {
+                simpler.push(FieldElement::zero());
+            }
+            if field.num_bits() <= 128 && field.to_u128() > 1 {
+                let field = field.to_u128();
+                simpler.push(FieldElement::from(field / 2));
+                simpler.push(FieldElement::from(field - 1));
+            }
+            simpler.into_iter().map(InputValue::Field).collect()
+        }
+        (InputValue::String(string), _) => (0..string.len())
+            .filter(|&index| string.as_bytes()[index] != b'a')
+            .map(|index| {
+                let mut bytes = string.clone().into_bytes();
+                bytes[index] = b'a';
+                InputValue::String(String::from_utf8(bytes).expect("generated strings are ASCII"))
+            })
+            .collect(),
+        (InputValue::Vec(elements), AbiType::Array { typ, .. }) => {
+            let mut simpler = Vec::new();
+            for (index, element) in elements.iter().enumerate() {
+                for element in simpler_values(element, typ) {
+                    let mut elements = elements.clone();
+                    elements[index] = element;
+                    simpler.push(InputValue::Vec(elements));
+                }
+            }
+            simpler
+        }
+        (InputValue::Struct(members), AbiType::Struct { fields }) => {
+            let mut simpler = Vec::new();
+            for (name, typ) in fields {
+                for member in simpler_values(&members[name], typ) {
+                    let mut members = members.clone();
+                    members.insert(name.clone(), member);
+                    simpler.push(InputValue::Struct(members));
+                }
+            }
+            simpler
+        }
+        _ => unreachable!("value should have been generated from its abi type"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::FieldElement;
+    use noirc_abi::{input_parser::InputValue, Abi, AbiParameter, AbiType, AbiVisibility, Sign};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{random_inputs, shrink};
+
+    fn abi(parameters: Vec<(&str, AbiType)>) -> Abi {
+        Abi {
+            parameters: parameters
+                .into_iter()
+                .map(|(name, typ)| AbiParameter {
+                    name: name.to_owned(),
+                    typ,
+                    visibility: AbiVisibility::Private,
+                })
+                .collect(),
+            param_witnesses: BTreeMap::new(),
+            return_type: None,
+            return_witnesses: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn random_inputs_match_the_abi() {
+        let abi = abi(vec![
+            ("x", AbiType::Field),
+            ("y", AbiType::Integer { sign: Sign::Unsigned, width: 3 }),
+            ("z", AbiType::Integer { sign: Sign::Signed, width: 32 }),
+            ("flags", AbiType::Array { length: 4, typ: Box::new(AbiType::Boolean) }),
+            ("name", AbiType::String { length: 5 }),
+            (
+                "point",
+                AbiType::Struct {
+                    fields: vec![
+                        ("a".to_owned(), AbiType::Integer { sign: Sign::Unsigned, width: 8 }),
+                        ("b".to_owned(), AbiType::Field),
+                    ],
+                },
+            ),
+        ]);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let inputs = random_inputs(&mut rng, &abi);
+            for param in &abi.parameters {
+                assert!(inputs[&param.name].matches_abi(&param.typ), "{:?}", inputs[&param.name]);
+            }
+        }
+    }
+
+    #[test]
+    fn shrinking_finds_the_smallest_failing_input() {
+        let element_type = AbiType::Integer { sign: Sign::Unsigned, width: 32 };
+        let abi = abi(vec![("xs", AbiType::Array { length: 2, typ: Box::new(element_type) })]);
+        let array = |a: u128, b: u128| {
+            InputValue::Vec(vec![
+                InputValue::Field(FieldElement::from(a)),
+                InputValue::Field(FieldElement::from(b)),
+            ])
+        };
+
+        // The circuit fails whenever the second element is at least 10.
+        let fails = |inputs: &BTreeMap<String, InputValue>| match &inputs["xs"] {
+            InputValue::Vec(elements) => {
+                matches!(&elements[1], InputValue::Field(x) if x.to_u128() >= 10)
+            }
+            _ => unreachable!(),
+        };
+
+        let inputs = BTreeMap::from([("xs".to_owned(), array(1234, 5678))]);
+        let shrunk = shrink(&abi, inputs, fails);
+        assert_eq!(shrunk["xs"], array(0, 10));
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+
+use acvm::{
+    acir::circuit::{Circuit, Opcode},
+    Backend,
+};
+use clap::Args;
+use noirc_driver::{compile_contracts, CompileOptions};
+use serde::Serialize;
+
+use crate::{errors::CliError, resolver::resolve_root_manifest};
+
+use super::compile_cmd::{
+    compile_circuit, optimize_contract_function, report_errors, UNKNOWN_ORIGIN,
+};
+use super::NargoConfig;
+
+/// Reports the cost of the circuit, broken down by source function and opcode kind
+///
+/// Opcodes are attributed to the function they were written in before inlining.
+/// This is only tracked when compiling with `--experimental-ssa`.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct InfoCommand {
+    /// Output the report as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Report on each contract function rather than on `main`
+    #[arg(short, long)]
+    contracts: bool,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct CircuitInfo {
+    name: String,
+    /// The total number of opcodes in the optimized circuit
+    acir_opcodes: usize,
+    /// The size of the circuit as reported by the backend
+    circuit_size: u32,
+    /// The cost of each source function, most expensive first
+    functions: Vec<FunctionInfo>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct FunctionInfo {
+    name: String,
+    acir_opcodes: usize,
+    /// The number of opcodes of each kind generated for this function
+    opcode_kinds: BTreeMap<String, usize>,
+}
+
+pub(crate) fn run<B: Backend>(
+    backend: &B,
+    args: InfoCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let circuits = if args.contracts {
+        let mut context = resolve_root_manifest(&config.program_dir)?;
+        let result = compile_contracts(&mut context, &args.compile_options);
+        let contracts = report_errors(result, &context, args.compile_options.deny_warnings)?;
+
+        let mut circuits = Vec::new();
+        for contract in contracts {
+            for func in contract.functions {
+                let func = optimize_contract_function(backend, func)?;
+                let name = format!("{}::{}", contract.name, func.name);
+                circuits.push(circuit_info(backend, name, &func.bytecode, &func.opcode_origins)?);
+            }
+        }
+        circuits
+    } else {
+        let program = compile_circuit(backend, &config.program_dir, &args.compile_options)?;
+        vec![circuit_info(backend, "main".to_owned(), &program.circuit, &program.opcode_origins)?]
+    };
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&circuits).expect("could not serialize report");
+        println!("{json}");
+    } else {
+        circuits.iter().for_each(print_circuit_info);
+    }
+
+    Ok(())
+}
+
+fn circuit_info<B: Backend>(
+    backend: &B,
+    name: String,
+    circuit: &Circuit,
+    opcode_origins: &[String],
+) -> Result<CircuitInfo, CliError<B>> {
+    let circuit_size =
+        backend.get_exact_circuit_size(circuit).map_err(CliError::ProofSystemCompilerError)?;
+
+    Ok(CircuitInfo {
+        name,
+        acir_opcodes: circuit.opcodes.len(),
+        circuit_size,
+        functions: function_costs(circuit, opcode_origins),
+    })
+}
+
+/// Groups the opcodes of `circuit` by the source function they were generated for.
+fn function_costs(circuit: &Circuit, opcode_origins: &[String]) -> Vec<FunctionInfo> {
+    let mut functions: BTreeMap<&str, BTreeMap<String, usize>> = BTreeMap::new();
+
+    for (index, opcode) in circuit.opcodes.iter().enumerate() {
+        let origin = opcode_origins.get(index).map_or(UNKNOWN_ORIGIN, String::as_str);
+        let kinds = functions.entry(origin).or_default();
+        *kinds.entry(opcode_kind(opcode)).or_default() += 1;
+    }
+
+    let mut functions: Vec<_> = functions
+        .into_iter()
+        .map(|(name, opcode_kinds)| FunctionInfo {
+            name: name.to_owned(),
+            acir_opcodes: opcode_kinds.values().sum(),
+            opcode_kinds,
+        })
+        .collect();
+
+    // Most expensive functions first, ties are kept in alphabetical order
+    functions.sort_by(|a, b| b.acir_opcodes.cmp(&a.acir_opcodes));
+    functions
+}
+
+fn opcode_kind(opcode: &Opcode) -> String {
+    match opcode {
+        Opcode::Arithmetic(_) => "arithmetic".to_owned(),
+        Opcode::BlackBoxFuncCall(call) => {
+            format!("black_box::{}", call.get_black_box_func().name())
+        }
+        Opcode::Brillig(_) => "brillig".to_owned(),
+        Opcode::Directive(_) => "directive".to_owned(),
+        Opcode::Block(_) | Opcode::ROM(_) | Opcode::RAM(_) => "memory".to_owned(),
+        other => other.name().to_owned(),
+    }
+}
+
+fn print_circuit_info(info: &CircuitInfo) {
+    println!(
+        "Circuit `{}`: {} ACIR opcodes, backend circuit size {}",
+        info.name, info.acir_opcodes, info.circuit_size
+    );
+
+    let name_width = info.functions.iter().map(|function| function.name.len()).max().unwrap_or(0);
+    for function in &info.functions {
+        let kinds: Vec<_> =
+            function.opcode_kinds.iter().map(|(kind, count)| format!("{kind}: {count}")).collect();
+        println!(
+            "    {:<name_width$}  {:>8}  ({})",
+            function.name,
+            function.acir_opcodes,
+            kinds.join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use acvm::acir::{
+        circuit::{
+            opcodes::BlackBoxFuncCall, opcodes::FunctionInput, Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness},
+    };
+
+    use super::{function_costs, FunctionInfo, UNKNOWN_ORIGIN};
+
+    #[test]
+    fn opcodes_are_grouped_by_origin_and_kind() {
+        let range = Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness: Witness(1), num_bits: 8 },
+        });
+        let arithmetic = Opcode::Arithmetic(Expression::from(Witness(1)));
+        let circuit = Circuit {
+            current_witness_index: 1,
+            opcodes: vec![arithmetic.clone(), range, arithmetic.clone(), arithmetic],
+            public_parameters: PublicInputs(BTreeSet::new()),
+            return_values: PublicInputs(BTreeSet::new()),
+        };
+        let origins = vec!["main".to_owned(), "foo".to_owned(), "foo".to_owned()];
+
+        let expected = vec![
+            FunctionInfo {
+                name: "foo".to_owned(),
+                acir_opcodes: 2,
+                opcode_kinds: BTreeMap::from([
+                    ("arithmetic".to_owned(), 1),
+                    ("black_box::range".to_owned(), 1),
+                ]),
+            },
+            FunctionInfo {
+                name: UNKNOWN_ORIGIN.to_owned(),
+                acir_opcodes: 1,
+                opcode_kinds: BTreeMap::from([("arithmetic".to_owned(), 1)]),
+            },
+            FunctionInfo {
+                name: "main".to_owned(),
+                acir_opcodes: 1,
+                opcode_kinds: BTreeMap::from([("arithmetic".to_owned(), 1)]),
+            },
+        ];
+        assert_eq!(function_costs(&circuit, &origins), expected);
+    }
+}
@@ -11,8 +11,13 @@ mod fs;
 mod check_cmd;
 mod codegen_verifier_cmd;
 mod compile_cmd;
+mod dap_cmd;
+mod debug_cmd;
 mod execute_cmd;
+mod fmt_cmd;
+mod fuzz_cmd;
 mod gates_cmd;
+mod info_cmd;
 mod lsp_cmd;
 mod new_cmd;
 mod prove_cmd;
@@ -49,12 +54,17 @@ enum NargoCommand {
     Check(check_cmd::CheckCommand),
     CodegenVerifier(codegen_verifier_cmd::CodegenVerifierCommand),
     Compile(compile_cmd::CompileCommand),
+    Dap(dap_cmd::DapCommand),
+    Debug(debug_cmd::DebugCommand),
     New(new_cmd::NewCommand),
     Execute(execute_cmd::ExecuteCommand),
+    Fmt(fmt_cmd::FormatCommand),
+    Fuzz(fuzz_cmd::FuzzCommand),
     Prove(prove_cmd::ProveCommand),
     Verify(verify_cmd::VerifyCommand),
     Test(test_cmd::TestCommand),
     Gates(gates_cmd::GatesCommand),
+    Info(info_cmd::InfoCommand),
     Lsp(lsp_cmd::LspCommand),
 }
 
@@ -62,7 +72,8 @@ pub fn start_cli() -> eyre::Result<()> {
     let NargoCli { command, mut config } = NargoCli::parse();
 
     // Search through parent directories to find package root if necessary.
-    if !matches!(command, NargoCommand::New(_) | NargoCommand::Lsp(_)) {
+    // The debug adapter finds the package of each program it launches instead.
+    if !matches!(command, NargoCommand::New(_) | NargoCommand::Lsp(_) | NargoCommand::Dap(_)) {
         config.program_dir = find_package_root(&config.program_dir)?;
     }
 
@@ -72,11 +83,16 @@ pub fn start_cli() -> eyre::Result<()> {
         NargoCommand::New(args) => new_cmd::run(&backend, args, config),
         NargoCommand::Check(args) => check_cmd::run(&backend, args, config),
         NargoCommand::Compile(args) => compile_cmd::run(&backend, args, config),
+        NargoCommand::Dap(args) => dap_cmd::run(&backend, args, config),
+        NargoCommand::Debug(args) => debug_cmd::run(&backend, args, config),
         NargoCommand::Execute(args) => execute_cmd::run(&backend, args, config),
+        NargoCommand::Fmt(args) => fmt_cmd::run(&backend, args, config),
+        NargoCommand::Fuzz(args) => fuzz_cmd::run(&backend, args, config),
         NargoCommand::Prove(args) => prove_cmd::run(&backend, args, config),
         NargoCommand::Verify(args) => verify_cmd::run(&backend, args, config),
         NargoCommand::Test(args) => test_cmd::run(&backend, args, config),
         NargoCommand::Gates(args) => gates_cmd::run(&backend, args, config),
+        NargoCommand::Info(args) => info_cmd::run(&backend, args, config),
         NargoCommand::CodegenVerifier(args) => codegen_verifier_cmd::run(&backend, args, config),
         NargoCommand::Lsp(args) => lsp_cmd::run(&backend, args, config),
     }?;
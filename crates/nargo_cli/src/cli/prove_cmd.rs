@@ -47,6 +47,10 @@ pub(crate) struct ProveCommand {
     #[arg(long)]
     verify: bool,
 
+    /// JSON-RPC endpoint used to resolve calls to `#[oracle]` functions
+    #[arg(long)]
+    oracle_resolver: Option<String>,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -71,6 +75,7 @@ pub(crate) fn run<B: Backend>(
         proof_dir,
         circuit_build_path,
         args.verify,
+        args.oracle_resolver.as_deref(),
         &args.compile_options,
     )?;
 
@@ -87,6 +92,7 @@ pub(crate) fn prove_with_path<B: Backend, P: AsRef<Path>>(
     proof_dir: P,
     circuit_build_path: Option<PathBuf>,
     check_proof: bool,
+    oracle_resolver: Option<&str>,
     compile_options: &CompileOptions,
 ) -> Result<Option<PathBuf>, CliError<B>> {
     let common_reference_string = read_cached_common_reference_string();
@@ -122,7 +128,8 @@ pub(crate) fn prove_with_path<B: Backend, P: AsRef<Path>>(
     let (inputs_map, _) =
         read_inputs_from_file(&program_dir, prover_name.as_str(), Format::Toml, &abi)?;
 
-    let solved_witness = execute_program(backend, bytecode.clone(), &abi, &inputs_map)?;
+    let solved_witness =
+        execute_program(backend, bytecode.clone(), &abi, &inputs_map, oracle_resolver)?;
 
     // Write public inputs into Verifier.toml
     let public_abi = abi.public_abi();
@@ -1,10 +1,26 @@
-use std::{io::Write, path::Path};
+use std::{
+    io::Write,
+    num::NonZeroUsize,
+    path::Path,
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
 
-use acvm::{acir::native_types::WitnessMap, Backend};
-use clap::Args;
-use nargo::ops::execute_circuit;
+use acvm::{
+    acir::{circuit::Circuit, native_types::WitnessMap},
+    Backend,
+};
+use clap::{Args, ValueEnum};
+use nargo::ops::{execute_circuit, DefaultForeignCallExecutor};
 use noirc_driver::{compile_no_check, CompileOptions};
-use noirc_frontend::{graph::LOCAL_CRATE, hir::Context, node_interner::FuncId};
+use noirc_errors::{reporter, FileDiagnostic};
+use noirc_frontend::{
+    graph::LOCAL_CRATE,
+    hir::{Context, FunctionNameMatch},
+    node_interner::FuncId,
+    token::{Attribute, TestScope},
+};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 use crate::{
@@ -12,7 +28,7 @@ use crate::{
     resolver::resolve_root_manifest,
 };
 
-use super::{compile_cmd::optimize_circuit, NargoConfig};
+use super::{compile_cmd::optimize_program, NargoConfig};
 
 /// Run the tests for this program
 #[derive(Debug, Clone, Args)]
@@ -20,91 +36,475 @@ pub(crate) struct TestCommand {
     /// If given, only tests with names containing this string will be run
     test_name: Option<String>,
 
+    /// Only run the test whose name is exactly `test_name`
+    #[arg(long, requires = "test_name")]
+    exact: bool,
+
+    /// List the names of the matching tests without running them
+    #[arg(long)]
+    list: bool,
+
+    /// The number of threads used to run tests, defaults to the number of available cores
+    #[arg(long)]
+    test_threads: Option<NonZeroUsize>,
+
+    /// Print a machine-readable report of the test results to stdout
+    #[arg(long, value_enum)]
+    report: Option<ReportFormat>,
+
+    /// JSON-RPC endpoint used to resolve calls to `#[oracle]` functions
+    #[arg(long)]
+    oracle_resolver: Option<String>,
+
+    /// Display the output of `println` statements of tests which pass, as well as those which fail
+    #[arg(long)]
+    show_test_output: bool,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Junit,
+}
+
 pub(crate) fn run<B: Backend>(
     backend: &B,
     args: TestCommand,
     config: NargoConfig,
 ) -> Result<(), CliError<B>> {
-    let test_name: String = args.test_name.unwrap_or_else(|| "".to_owned());
+    let pattern = match &args.test_name {
+        Some(test_name) if args.exact => FunctionNameMatch::Exact(test_name),
+        Some(test_name) => FunctionNameMatch::Contains(test_name),
+        None => FunctionNameMatch::Anything,
+    };
+
+    let mut context = resolve_root_manifest(&config.program_dir)?;
+    check_crate_and_report_errors(
+        &mut context,
+        args.compile_options.deny_warnings,
+        args.compile_options.experimental_ssa,
+    )?;
+
+    let test_functions = context.get_all_test_functions_in_crate_matching(&LOCAL_CRATE, pattern);
+
+    if args.list {
+        for test_function in test_functions {
+            println!("{}", context.function_name(&test_function));
+        }
+        return Ok(());
+    }
+
+    let threads = args
+        .test_threads
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get);
+
+    if args.report.is_none() {
+        println!("Running {} test functions...", test_functions.len());
+    }
+
+    // Tests are always compiled with their `println` statements, whose output is shown if the
+    // test fails, or if `--show-test-output` is given.
+    let compile_options = CompileOptions { show_output: true, ..args.compile_options };
+
+    let start = Instant::now();
+    let mut reports = run_tests(
+        backend,
+        &context,
+        test_functions,
+        &compile_options,
+        args.oracle_resolver.as_deref(),
+        threads,
+        args.show_test_output,
+    );
+    reports.sort_by(|a, b| a.name.cmp(&b.name));
+
+    match args.report {
+        Some(ReportFormat::Json) => println!("{}", json_report(&reports)),
+        Some(ReportFormat::Junit) => {
+            println!("{}", junit_report(&package_name(&config.program_dir), &reports));
+        }
+        None => (),
+    }
+
+    print_summary(&reports, start.elapsed())
+}
+
+/// A test which compiled successfully and is waiting to be executed.
+struct TestJob {
+    name: String,
+    scope: TestScope,
+    circuit: Circuit,
+    compile_time: Duration,
+}
+
+struct TestReport {
+    name: String,
+    status: TestStatus,
+    /// The time taken to compile and execute the test
+    duration: Duration,
+    /// Everything the test printed while executing
+    output: String,
+}
+
+enum TestStatus {
+    Passed,
+    Failed { message: String },
+}
 
-    run_tests(backend, &config.program_dir, &test_name, &args.compile_options)
+impl TestReport {
+    fn passed(&self) -> bool {
+        matches!(self.status, TestStatus::Passed)
+    }
 }
 
+/// Compiles each test on the current thread, as the compiler's context can't be shared between
+/// threads, while the compiled tests are executed by a pool of `threads` worker threads.
 fn run_tests<B: Backend>(
     backend: &B,
-    program_dir: &Path,
-    test_name: &str,
+    context: &Context,
+    test_functions: Vec<FuncId>,
     compile_options: &CompileOptions,
-) -> Result<(), CliError<B>> {
-    let mut context = resolve_root_manifest(program_dir)?;
-    check_crate_and_report_errors(
-        &mut context,
-        compile_options.deny_warnings,
-        compile_options.experimental_ssa,
-    )?;
+    oracle_resolver: Option<&str>,
+    threads: usize,
+    show_output: bool,
+) -> Vec<TestReport> {
+    let (job_sender, job_receiver) = mpsc::channel::<TestJob>();
+    let job_receiver = Mutex::new(job_receiver);
+    let reports = Mutex::new(Vec::new());
 
-    let test_functions = context.get_all_test_functions_in_crate_matching(&LOCAL_CRATE, test_name);
-    println!("Running {} test functions...", test_functions.len());
-    let mut failing = 0;
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                // Each worker thread executes tests with its own instance of the backend
+                let backend = B::default();
+                loop {
+                    let job = job_receiver.lock().expect("a test thread panicked").recv();
+                    let Ok(job) = job else { break };
 
-    let writer = StandardStream::stderr(ColorChoice::Always);
-    let mut writer = writer.lock();
+                    let report = execute_test(&backend, job, oracle_resolver);
+                    print_test_result(&report, show_output);
+                    reports.lock().expect("a test thread panicked").push(report);
+                }
+            });
+        }
 
-    for test_function in test_functions {
-        let test_name = context.function_name(&test_function);
-        writeln!(writer, "Testing {test_name}...").expect("Failed to write to stdout");
-        writer.flush().ok();
+        for test_function in test_functions {
+            let name = context.function_name(&test_function).to_owned();
+            let scope = test_scope(context, test_function);
+            let start = Instant::now();
 
-        match run_test(backend, test_name, test_function, &context, compile_options) {
-            Ok(_) => {
-                writer.set_color(ColorSpec::new().set_fg(Some(Color::Green))).ok();
-                writeln!(writer, "ok").ok();
+            match compile_test(backend, context, test_function, compile_options) {
+                Ok(circuit) => {
+                    let job = TestJob { name, scope, circuit, compile_time: start.elapsed() };
+                    job_sender.send(job).expect("all test threads have stopped");
+                }
+                Err(diagnostic) => {
+                    // A test which is expected to fail may do so by failing to compile, e.g. if
+                    // one of its constraints can never be satisfied.
+                    let status = test_status(&scope, Err(diagnostic.diagnostic.message.clone()));
+                    if let TestStatus::Failed { .. } = status {
+                        reporter::report(
+                            &context.file_manager,
+                            &diagnostic.diagnostic,
+                            Some(diagnostic.file_id),
+                            false,
+                        );
+                    }
+                    let report = TestReport {
+                        name,
+                        status,
+                        duration: start.elapsed(),
+                        output: String::new(),
+                    };
+                    print_test_result(&report, show_output);
+                    reports.lock().expect("a test thread panicked").push(report);
+                }
             }
-            // Assume an error was already printed to stdout
-            Err(_) => failing += 1,
         }
-        writer.reset().ok();
-    }
 
-    if failing == 0 {
-        writer.set_color(ColorSpec::new().set_fg(Some(Color::Green))).unwrap();
-        writeln!(writer, "All tests passed").ok();
-    } else {
-        let plural = if failing == 1 { "" } else { "s" };
-        return Err(CliError::Generic(format!("{failing} test{plural} failed")));
-    }
+        // Closing the channel lets the worker threads finish once all tests have been executed
+        drop(job_sender);
+    });
 
-    writer.reset().ok();
-    Ok(())
+    reports.into_inner().expect("a test thread panicked")
 }
 
-fn run_test<B: Backend>(
+fn test_scope(context: &Context, test_function: FuncId) -> TestScope {
+    match context.function_meta(&test_function).attributes {
+        Some(Attribute::Test(scope)) => scope,
+        _ => unreachable!("test functions are marked with a test attribute"),
+    }
+}
+
+fn compile_test<B: Backend>(
     backend: &B,
-    test_name: &str,
-    main: FuncId,
     context: &Context,
-    config: &CompileOptions,
-) -> Result<(), CliError<B>> {
-    let mut program = compile_no_check(context, config, main)
-        .map_err(|_| CliError::Generic(format!("Test '{test_name}' failed to compile")))?;
+    test_function: FuncId,
+    compile_options: &CompileOptions,
+) -> Result<Circuit, FileDiagnostic> {
+    let program = compile_no_check(context, compile_options, test_function)?;
     // Note: We could perform this test using the unoptimized ACIR as generated by `compile_no_check`.
-    program.circuit = optimize_circuit(backend, program.circuit).unwrap();
+    let program = optimize_program(backend, program).unwrap();
+    Ok(program.circuit)
+}
+
+fn execute_test<B: Backend>(
+    backend: &B,
+    job: TestJob,
+    oracle_resolver: Option<&str>,
+) -> TestReport {
+    let start = Instant::now();
+    let mut output = Vec::new();
 
     // Run the backend to ensure the PWG evaluates functions like std::hash::pedersen,
     // otherwise constraints involving these expressions will not error.
-    match execute_circuit(backend, program.circuit, WitnessMap::new()) {
-        Ok(_) => Ok(()),
-        Err(error) => {
-            let writer = StandardStream::stderr(ColorChoice::Always);
-            let mut writer = writer.lock();
+    // Each test gets its own executor so that the oracles mocked by one test don't leak into others.
+    let result = DefaultForeignCallExecutor::new(&mut output, oracle_resolver, true)
+        .map_err(|error| error.to_string())
+        .and_then(|mut foreign_call_executor| {
+            execute_circuit(backend, job.circuit, WitnessMap::new(), &mut foreign_call_executor)
+                .map(|_| ())
+                .map_err(|error| error.to_string())
+        });
+    let status = test_status(&job.scope, result);
+
+    TestReport {
+        name: job.name,
+        status,
+        duration: job.compile_time + start.elapsed(),
+        output: String::from_utf8_lossy(&output).into_owned(),
+    }
+}
+
+/// Decides whether a test passed given the outcome of compiling and executing it, where an error
+/// holds the message the test failed with.
+fn test_status(scope: &TestScope, result: Result<(), String>) -> TestStatus {
+    match (scope, result) {
+        (TestScope::None, Ok(())) => TestStatus::Passed,
+        (TestScope::None, Err(message)) => TestStatus::Failed { message },
+        (TestScope::ShouldFailWith { reason: None }, Ok(())) => {
+            TestStatus::Failed { message: "test should have failed".to_owned() }
+        }
+        (TestScope::ShouldFailWith { reason: Some(reason) }, Ok(())) => TestStatus::Failed {
+            message: format!("test should have failed with message containing {reason:?}"),
+        },
+        (TestScope::ShouldFailWith { reason: None }, Err(_)) => TestStatus::Passed,
+        (TestScope::ShouldFailWith { reason: Some(reason) }, Err(message)) => {
+            if message.contains(reason.as_str()) {
+                TestStatus::Passed
+            } else {
+                TestStatus::Failed {
+                    message: format!(
+                        "test failed with {message:?} rather than a message containing {reason:?}"
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Prints the result of a single test along with its output, if the test failed or `show_output`
+/// is set.
+fn print_test_result(report: &TestReport, show_output: bool) {
+    let writer = StandardStream::stderr(ColorChoice::Always);
+    let mut writer = writer.lock();
+
+    write!(writer, "Testing {}... ", report.name).ok();
+    match &report.status {
+        TestStatus::Passed => {
+            writer.set_color(ColorSpec::new().set_fg(Some(Color::Green))).ok();
+            write!(writer, "ok").ok();
+        }
+        TestStatus::Failed { .. } => {
             writer.set_color(ColorSpec::new().set_fg(Some(Color::Red))).ok();
-            writeln!(writer, "failed").ok();
-            writer.reset().ok();
-            Err(error.into())
+            write!(writer, "failed").ok();
+        }
+    }
+    writer.reset().ok();
+    writeln!(writer, " ({})", format_duration(report.duration)).ok();
+
+    if !report.passed() || show_output {
+        writer.write_all(report.output.as_bytes()).ok();
+    }
+    if let TestStatus::Failed { message } = &report.status {
+        writeln!(writer, "{message}").ok();
+    }
+}
+
+fn print_summary<B: Backend>(reports: &[TestReport], elapsed: Duration) -> Result<(), CliError<B>> {
+    let writer = StandardStream::stderr(ColorChoice::Always);
+    let mut writer = writer.lock();
+
+    let failed: Vec<_> = reports.iter().filter(|report| !report.passed()).collect();
+    let passed = reports.len() - failed.len();
+
+    if failed.is_empty() {
+        writer.set_color(ColorSpec::new().set_fg(Some(Color::Green))).ok();
+        writeln!(writer, "All tests passed").ok();
+        writer.reset().ok();
+    } else {
+        writeln!(writer, "\nFailed tests:").ok();
+        for report in &failed {
+            writeln!(writer, "    {}", report.name).ok();
         }
     }
+    writeln!(
+        writer,
+        "{passed} passed; {} failed; finished in {}",
+        failed.len(),
+        format_duration(elapsed)
+    )
+    .ok();
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        let failing = failed.len();
+        let plural = if failing == 1 { "" } else { "s" };
+        Err(CliError::Generic(format!("{failing} test{plural} failed")))
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}s", duration.as_secs_f64())
+}
+
+fn package_name(program_dir: &Path) -> String {
+    program_dir.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+fn json_report(reports: &[TestReport]) -> String {
+    let tests: Vec<_> = reports
+        .iter()
+        .map(|report| {
+            let (status, message) = match &report.status {
+                TestStatus::Passed => ("passed", None),
+                TestStatus::Failed { message } => ("failed", Some(message)),
+            };
+            serde_json::json!({
+                "name": report.name,
+                "status": status,
+                "message": message,
+                "duration_ms": report.duration.as_millis() as u64,
+                "output": report.output,
+            })
+        })
+        .collect();
+
+    let failed = reports.iter().filter(|report| !report.passed()).count();
+    let report = serde_json::json!({
+        "passed": reports.len() - failed,
+        "failed": failed,
+        "tests": tests,
+    });
+    serde_json::to_string_pretty(&report).expect("could not serialize test report")
+}
+
+fn junit_report(suite_name: &str, reports: &[TestReport]) -> String {
+    let failures = reports.iter().filter(|report| !report.passed()).count();
+    let time: f64 = reports.iter().map(|report| report.duration.as_secs_f64()).sum();
+    let suite_name = xml_escape(suite_name);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites tests=\"{}\" failures=\"{failures}\" time=\"{time:.3}\">\n",
+        reports.len()
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"{suite_name}\" tests=\"{}\" failures=\"{failures}\" time=\"{time:.3}\">\n",
+        reports.len()
+    ));
+
+    for report in reports {
+        xml.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"{suite_name}\" time=\"{:.3}\">\n",
+            xml_escape(&report.name),
+            report.duration.as_secs_f64()
+        ));
+        if let TestStatus::Failed { message } = &report.status {
+            xml.push_str(&format!("      <failure message=\"{}\"/>\n", xml_escape(message)));
+        }
+        if !report.output.is_empty() {
+            xml.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                xml_escape(&report.output)
+            ));
+        }
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>");
+    xml
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use noirc_frontend::token::TestScope;
+
+    use super::{junit_report, test_status, TestReport, TestStatus};
+
+    #[test]
+    fn should_fail_tests_pass_only_when_failing_with_the_expected_reason() {
+        let should_fail = TestScope::ShouldFailWith { reason: None };
+        let should_fail_with =
+            TestScope::ShouldFailWith { reason: Some("index out of bounds".to_owned()) };
+        let error = || Err("index out of bounds: the len is 3".to_owned());
+
+        assert!(matches!(test_status(&TestScope::None, Ok(())), TestStatus::Passed));
+        assert!(matches!(test_status(&TestScope::None, error()), TestStatus::Failed { .. }));
+        assert!(matches!(test_status(&should_fail, Ok(())), TestStatus::Failed { .. }));
+        assert!(matches!(test_status(&should_fail, error()), TestStatus::Passed));
+        assert!(matches!(test_status(&should_fail_with, error()), TestStatus::Passed));
+        assert!(matches!(test_status(&should_fail_with, Ok(())), TestStatus::Failed { .. }));
+        assert!(matches!(
+            test_status(&should_fail_with, Err("unsatisfied constraint".to_owned())),
+            TestStatus::Failed { .. }
+        ));
+    }
+
+    #[test]
+    fn junit_report_escapes_names_and_messages() {
+        let reports = vec![
+            TestReport {
+                name: "passes".to_owned(),
+                status: TestStatus::Passed,
+                duration: Duration::from_millis(1500),
+                output: "0x01\n".to_owned(),
+            },
+            TestReport {
+                name: "fails".to_owned(),
+                status: TestStatus::Failed { message: "expected <1> & \"2\"".to_owned() },
+                duration: Duration::from_millis(500),
+                output: String::new(),
+            },
+        ];
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="2" failures="1" time="2.000">
+  <testsuite name="my_package" tests="2" failures="1" time="2.000">
+    <testcase name="passes" classname="my_package" time="1.500">
+      <system-out>0x01
+</system-out>
+    </testcase>
+    <testcase name="fails" classname="my_package" time="0.500">
+      <failure message="expected &lt;1&gt; &amp; &quot;2&quot;"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+        assert_eq!(junit_report("my_package", &reports), expected);
+    }
 }
@@ -13,6 +13,8 @@ pub(crate) const TARGET_DIR: &str = "target";
 pub(crate) const PROVER_INPUT_FILE: &str = "Prover";
 /// The file from which Nargo pulls verifier inputs
 pub(crate) const VERIFIER_INPUT_FILE: &str = "Verifier";
+/// The file to which `nargo fuzz` writes the inputs of a failing execution
+pub(crate) const COUNTEREXAMPLE_FILE: &str = "Counterexample";
 /// The package definition file for a Noir project.
 pub(crate) const PKG_FILE: &str = "Nargo.toml";
 
@@ -3,8 +3,9 @@ use acvm::{
     SmartContract,
 };
 use hex::FromHexError;
-use nargo::NargoError;
+use nargo::{manifest::InvalidPackageError, NargoError};
 use noirc_abi::errors::{AbiError, InputParserError};
+use noirc_driver::InterpreterError;
 use noirc_errors::reporter::ReportedErrors;
 use std::path::PathBuf;
 use thiserror::Error;
@@ -44,6 +45,10 @@ pub(crate) enum CliError<B: Backend> {
     #[error(transparent)]
     ResolutionError(#[from] DependencyResolutionError),
 
+    /// The package's manifest or directory layout is invalid
+    #[error(transparent)]
+    InvalidPackage(#[from] InvalidPackageError),
+
     /// Errors encountered while compiling the noir program.
     /// These errors are already written to stderr.
     #[error("Aborting due to {} previous error{}", .0.error_count, if .0.error_count == 1 { "" } else { "s" })]
@@ -64,6 +69,10 @@ pub(crate) enum CliError<B: Backend> {
     #[error(transparent)]
     NargoError(#[from] NargoError),
 
+    /// Error from interpreting a program's SSA
+    #[error(transparent)]
+    InterpreterError(#[from] InterpreterError),
+
     /// Backend error caused by a function on the SmartContract trait
     #[error(transparent)]
     SmartContractError(<B as SmartContract>::Error), // Unfortunately, Rust won't let us `impl From` over an Associated Type on a generic
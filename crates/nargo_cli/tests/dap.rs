@@ -0,0 +1,95 @@
+//! Drives `nargo dap` through a debugging session the way an editor would, by writing
+//! Debug Adapter Protocol requests to its stdin and reading the messages it writes to stdout.
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use serde_json::{json, Value};
+use std::process::Command;
+
+const MAIN: &str = "fn main(x: Field, y: pub Field) {
+    let sum = x + y;
+    let product = sum * x;
+    assert(product != 0);
+}
+";
+
+fn encode(requests: &[Value]) -> Vec<u8> {
+    let mut input = Vec::new();
+    for (seq, request) in requests.iter().enumerate() {
+        let mut request = request.clone();
+        request["seq"] = json!(seq + 1);
+        request["type"] = json!("request");
+        let content = request.to_string();
+        input.extend(format!("Content-Length: {}\r\n\r\n{content}", content.len()).into_bytes());
+    }
+    input
+}
+
+fn decode(mut output: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    while let Some(header_end) = output.find("\r\n\r\n") {
+        let length: usize = output[..header_end]
+            .strip_prefix("Content-Length: ")
+            .and_then(|length| length.parse().ok())
+            .expect("messages should start with a Content-Length header");
+        let content = &output[header_end + 4..header_end + 4 + length];
+        messages.push(serde_json::from_str(content).unwrap());
+        output = &output[header_end + 4 + length..];
+    }
+    messages
+}
+
+#[test]
+fn stops_at_breakpoints_and_shows_variables() {
+    let project_dir = assert_fs::TempDir::new().unwrap();
+    project_dir
+        .child("Nargo.toml")
+        .write_str("[package]\nauthors = [\"\"]\ncompiler_version = \"0.1\"\n\n[dependencies]")
+        .unwrap();
+    project_dir.child("src").child("main.nr").write_str(MAIN).unwrap();
+    project_dir.child("Prover.toml").write_str("x = \"3\"\ny = \"4\"\n").unwrap();
+    let main_path = project_dir.child("src").child("main.nr");
+
+    let input = encode(&[
+        json!({ "command": "initialize", "arguments": { "adapterID": "noir" } }),
+        json!({ "command": "launch", "arguments": { "projectFolder": project_dir.path() } }),
+        json!({
+            "command": "setBreakpoints",
+            "arguments": { "source": { "path": main_path.path() }, "breakpoints": [{ "line": 4 }] },
+        }),
+        json!({ "command": "configurationDone" }),
+        json!({ "command": "variables", "arguments": { "variablesReference": 1 } }),
+        json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        json!({ "command": "disconnect" }),
+    ]);
+
+    let output = Command::cargo_bin("nargo").unwrap().arg("dap").write_stdin(input).unwrap();
+    assert!(output.status.success());
+    let messages = decode(&String::from_utf8(output.stdout).unwrap());
+
+    for response in messages.iter().filter(|message| message["type"] == "response") {
+        assert_eq!(response["success"], true, "request failed: {response}");
+    }
+
+    let events: Vec<_> = messages
+        .iter()
+        .filter(|message| message["type"] == "event" && message["event"] != "output")
+        .map(|event| event["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(events, ["initialized", "stopped", "exited", "terminated"]);
+
+    let stopped = messages.iter().find(|message| message["event"] == "stopped").unwrap();
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+
+    let breakpoints = messages.iter().find(|message| message["command"] == "setBreakpoints");
+    assert_eq!(breakpoints.unwrap()["body"]["breakpoints"][0]["verified"], true);
+
+    let variables = messages.iter().find(|message| message["command"] == "variables").unwrap();
+    let variables = variables["body"]["variables"].as_array().unwrap();
+    let value_of = |name: &str| {
+        let variable = variables.iter().find(|variable| variable["name"] == name);
+        variable.map(|variable| variable["value"].clone())
+    };
+    assert_eq!(value_of("main::sum"), Some(json!("7")));
+    assert_eq!(value_of("main::product"), Some(json!("21")));
+}
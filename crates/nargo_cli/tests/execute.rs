@@ -0,0 +1,84 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use std::process::Command;
+
+const MAIN: &str = "fn main(x: Field, y: pub Field) -> pub Field {
+    let product = x * y;
+    assert(product != 0);
+    product
+}
+";
+
+fn create_project() -> assert_fs::TempDir {
+    let project_dir = assert_fs::TempDir::new().unwrap();
+    project_dir
+        .child("Nargo.toml")
+        .write_str("[package]\nauthors = [\"\"]\ncompiler_version = \"0.1\"\n\n[dependencies]")
+        .unwrap();
+    project_dir.child("src").child("main.nr").write_str(MAIN).unwrap();
+    project_dir.child("Prover.toml").write_str("x = \"3\"\ny = \"4\"\n").unwrap();
+    project_dir
+}
+
+#[test]
+fn traces_the_inputs_and_outputs_of_each_opcode() {
+    let project_dir = create_project();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.arg("--program-dir").arg(project_dir.path()).arg("execute").arg("--trace");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[0] "))
+        .stdout(predicate::str::contains("_1 = 3, _2 = 4"))
+        .stdout(predicate::str::contains("= 12"));
+}
+
+#[test]
+fn writes_the_witness_as_json() {
+    let project_dir = create_project();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.arg("--program-dir").arg(project_dir.path()).args([
+        "execute",
+        "witness",
+        "--witness-format",
+        "json",
+    ]);
+    cmd.assert().success();
+
+    let witness = std::fs::read_to_string(project_dir.child("target").child("witness.json"))
+        .expect("the witness should be written to the target directory");
+    let witness: Value = serde_json::from_str(&witness).unwrap();
+
+    assert_eq!(witness["inputs"], json!({ "x": field(3), "y": field(4), "return": field(12) }));
+    assert_eq!(witness["witnesses"]["1"], field(3));
+    assert_eq!(witness["witnesses"]["2"], field(4));
+}
+
+#[test]
+fn interprets_the_ssa_of_the_program() {
+    let project_dir = create_project();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.arg("--program-dir").arg(project_dir.path()).args([
+        "execute",
+        "witness",
+        "--ssa-interpret",
+        "--witness-format",
+        "json",
+    ]);
+    cmd.assert().success();
+
+    let witness = std::fs::read_to_string(project_dir.child("target").child("witness.json"))
+        .expect("the witness should be written to the target directory");
+    let witness: Value = serde_json::from_str(&witness).unwrap();
+
+    assert_eq!(witness["inputs"]["return"], field(12));
+}
+
+/// Returns the hex encoding of a field element, as written to JSON files.
+fn field(value: u64) -> String {
+    format!("0x{value:064x}")
+}
@@ -0,0 +1,94 @@
+//! Differential tests which execute each program in `test_data` through both the legacy and the
+//! experimental SSA pipelines with the same `Prover.toml`, to track the experimental pipeline's
+//! progress towards replacing the legacy one.
+//!
+//! A program reaches parity when both pipelines either fail, or solve its circuit with the same
+//! return value. The experimental circuit must also have no more opcodes than its baseline, which
+//! is the opcode count recorded for the program in `test_data/ssa_opcode_baseline.toml`, or else
+//! the opcode count of its legacy circuit. Run with `cargo test --test ssa_parity -- --nocapture`
+//! to see the opcode counts of both circuits.
+#[cfg(test)]
+mod tests {
+    use assert_cmd::prelude::*;
+    use serde_json::Value;
+
+    use std::path::Path;
+    use std::process::Command;
+
+    /// The result of executing a program through one of the pipelines
+    #[derive(Debug, PartialEq, Eq)]
+    enum Outcome {
+        Solved { return_value: Option<String> },
+        Failed,
+    }
+
+    fn nargo(program_dir: &Path, args: &[&str], experimental_ssa: bool) -> Command {
+        let mut cmd = Command::cargo_bin("nargo").unwrap();
+        cmd.arg("--program-dir").arg(program_dir).args(args);
+        if experimental_ssa {
+            cmd.arg("--experimental-ssa");
+        }
+        cmd
+    }
+
+    fn execute(program_dir: &Path, experimental_ssa: bool) -> Outcome {
+        let output = nargo(program_dir, &["execute"], experimental_ssa).output().unwrap();
+        if !output.status.success() {
+            return Outcome::Failed;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let return_value = stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("Circuit output: "))
+            .map(ToOwned::to_owned);
+        Outcome::Solved { return_value }
+    }
+
+    /// Returns the number of opcodes in the program's circuit, or None if it fails to compile.
+    fn count_opcodes(program_dir: &Path, experimental_ssa: bool) -> Option<u64> {
+        let output = nargo(program_dir, &["info", "--json"], experimental_ssa).output().unwrap();
+        if !output.status.success() {
+            return None;
+        }
+        let circuits: Value = serde_json::from_slice(&output.stdout).ok()?;
+        circuits[0]["acir_opcodes"].as_u64()
+    }
+
+    fn compare_ssa_pipelines(program_dir: &Path, diverges: bool, baseline: Option<u64>) {
+        let name = program_dir.file_name().unwrap().to_string_lossy();
+
+        if let (Some(legacy), Some(experimental)) =
+            (count_opcodes(program_dir, false), count_opcodes(program_dir, true))
+        {
+            let change = experimental as i64 - legacy as i64;
+            println!("{name}: {legacy} legacy opcodes, {experimental} experimental ({change:+})");
+
+            let baseline = baseline.unwrap_or(legacy);
+            assert!(
+                experimental <= baseline,
+                "{name} compiles to {experimental} opcodes through the experimental pipeline, \
+                 up from its baseline of {baseline}"
+            );
+            if experimental < baseline && baseline > legacy {
+                println!("{name}: lower its count in ssa_opcode_baseline.toml to {experimental}");
+            }
+        }
+
+        let legacy = execute(program_dir, false);
+        let experimental = execute(program_dir, true);
+        if diverges {
+            assert_ne!(
+                legacy, experimental,
+                "{name} has reached parity, remove it from `ssa_parity_fail` in config.toml"
+            );
+        } else {
+            assert_eq!(
+                legacy, experimental,
+                "{name} gives different results through the legacy and experimental pipelines"
+            );
+        }
+    }
+
+    // include tests generated by `build.rs`
+    include!(concat!(env!("OUT_DIR"), "/ssa_parity.rs"));
+}
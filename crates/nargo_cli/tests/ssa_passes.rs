@@ -0,0 +1,77 @@
+//! Tests which compile programs in `test_data_ssa_refactor` through the experimental SSA pipeline
+//! with and without an optimization pass, to check that the pass shrinks their circuits. Run with
+//! `cargo test --test ssa_passes -- --nocapture` to see the opcode counts of both circuits.
+#[cfg(test)]
+mod tests {
+    use assert_cmd::prelude::*;
+    use serde_json::Value;
+
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// The passes ACIR generation requires, along with constant folding so that the passes under
+    /// test are compared against an otherwise optimized circuit.
+    const BASE_PIPELINE: &str = "inline,unroll,simplify-cfg,flatten,mem2reg,fold-constants,die";
+
+    /// The base pipeline with global value numbering run after constant folding.
+    const GVN_PIPELINE: &str = "inline,unroll,simplify-cfg,flatten,mem2reg,fold-constants,gvn,die";
+
+    /// The base pipeline with loop invariants hoisted and induction variables strength reduced
+    /// before unrolling.
+    const LOOP_PIPELINE: &str =
+        "inline,licm,strength-reduce,unroll,simplify-cfg,flatten,mem2reg,fold-constants,die";
+
+    fn program_dir(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data_ssa_refactor").join(name)
+    }
+
+    /// Returns the number of opcodes in the program's circuit when compiled with `passes`.
+    fn count_opcodes(program_dir: &Path, passes: &str) -> u64 {
+        let mut cmd = Command::cargo_bin("nargo").unwrap();
+        cmd.arg("--program-dir").arg(program_dir);
+        cmd.args(["info", "--json", "--experimental-ssa", "--passes", passes]);
+
+        let output = cmd.output().unwrap();
+        assert!(
+            output.status.success(),
+            "{} fails to compile with `--passes {passes}`:\n{}",
+            program_dir.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let circuits: Value = serde_json::from_slice(&output.stdout).unwrap();
+        circuits[0]["acir_opcodes"].as_u64().unwrap()
+    }
+
+    /// Asserts that compiling `program` with `optimized` gives fewer opcodes than with `baseline`.
+    fn assert_pipeline_shrinks_circuit(program: &str, baseline: &str, optimized: &str) {
+        let program_dir = program_dir(program);
+        let before = count_opcodes(&program_dir, baseline);
+        let after = count_opcodes(&program_dir, optimized);
+        println!("{program}: {before} opcodes with `{baseline}`, {after} with `{optimized}`");
+        assert!(
+            after < before,
+            "{program} compiles to {after} opcodes with `{optimized}`, not fewer than the \
+             {before} opcodes it compiles to with `{baseline}`"
+        );
+    }
+
+    #[test]
+    fn gvn_shrinks_sha256() {
+        assert_pipeline_shrinks_circuit("sha256", BASE_PIPELINE, GVN_PIPELINE);
+    }
+
+    #[test]
+    fn gvn_shrinks_merkle_insert() {
+        assert_pipeline_shrinks_circuit("merkle_insert", BASE_PIPELINE, GVN_PIPELINE);
+    }
+
+    #[test]
+    fn loop_passes_shrink_sha256() {
+        assert_pipeline_shrinks_circuit("sha256", BASE_PIPELINE, LOOP_PIPELINE);
+    }
+
+    #[test]
+    fn loop_passes_shrink_poseidon() {
+        assert_pipeline_shrinks_circuit("poseidon_bn254_hash", BASE_PIPELINE, LOOP_PIPELINE);
+    }
+}
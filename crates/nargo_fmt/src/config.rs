@@ -0,0 +1,23 @@
+use serde::Deserialize;
+
+/// Formatting options, read from the `[fmt]` section of a package's `Nargo.toml`.
+///
+/// ```toml
+/// [fmt]
+/// max_width = 100
+/// tab_spaces = 4
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Lines wider than this are broken up at their outermost bracketed list, where possible.
+    pub max_width: usize,
+    /// Number of spaces used for each level of indentation.
+    pub tab_spaces: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { max_width: 100, tab_spaces: 4 }
+    }
+}
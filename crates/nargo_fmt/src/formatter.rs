@@ -0,0 +1,402 @@
+//! The formatter works on the token stream produced by the lexer (with comments retained) rather
+//! than on the AST, as the AST keeps no record of comments or of how the user laid out their code.
+//!
+//! Line breaks chosen by the user are kept, while indentation, the spacing between tokens on a
+//! line and blank lines are normalized. Lines which are still wider than `Config::max_width` are
+//! broken up at the commas of their outermost bracketed list.
+//!
+//! Every decision made here only depends on the tokens and on whether they were separated by
+//! whitespace or line breaks, so formatting already formatted code leaves it unchanged.
+
+use std::collections::VecDeque;
+
+use noirc_frontend::lexer::Lexer;
+use noirc_frontend::token::{Keyword, Token};
+
+use crate::Config;
+
+/// A token along with the layout information from the source which the formatter preserves.
+#[derive(Debug, Clone)]
+struct SourceToken {
+    token: Token,
+    /// The token exactly as it was written in the source.
+    text: String,
+    /// Number of line breaks between this token and the previous one.
+    newlines_before: usize,
+    /// Whether the token was separated from the previous one by whitespace.
+    space_before: bool,
+    /// Whether the token is an operator applied to the operand following it, e.g. the `-` in `-x`.
+    is_prefix: bool,
+}
+
+/// A line of output before it is indented.
+#[derive(Debug)]
+struct Line {
+    tokens: Vec<SourceToken>,
+    /// Whether the line was separated from the previous one by at least one blank line.
+    blank_line_before: bool,
+}
+
+pub(crate) fn format_source(source: &str, config: &Config) -> String {
+    let lines = split_lines(source_tokens(source));
+
+    let mut printer = Printer::new(config);
+    printer.print_lines(lines);
+    printer.output
+}
+
+fn source_tokens(source: &str) -> Vec<SourceToken> {
+    // Token spans are character offsets rather than byte offsets into the source.
+    let chars: Vec<char> = source.chars().collect();
+    let (tokens, _) = Lexer::new(source).skip_comments(false).tokenize();
+
+    let mut source_tokens: Vec<SourceToken> = Vec::new();
+    let mut previous_end = 0;
+    for spanned_token in tokens.0 {
+        let start = spanned_token.to_span().start() as usize;
+        let token = spanned_token.into_token();
+        if token == Token::EOF {
+            break;
+        }
+
+        let gap = &chars[previous_end.min(start)..start];
+        let length = source_length(&token, &chars[start..]);
+        previous_end = start + length;
+
+        let text: String = chars[start..previous_end].iter().collect();
+        let text = match token {
+            Token::LineComment(_) => text.trim_end().to_owned(),
+            _ => text,
+        };
+
+        let previous_token = source_tokens.iter().rev().find(|token| !is_comment(&token.token));
+        let is_prefix = is_prefix_operator(&token)
+            && !previous_token.map_or(false, |previous| ends_operand(&previous.token));
+
+        source_tokens.push(SourceToken {
+            token,
+            text,
+            newlines_before: gap.iter().filter(|ch| **ch == '\n').count(),
+            space_before: !gap.is_empty(),
+            is_prefix,
+        });
+    }
+    source_tokens
+}
+
+/// Returns the number of characters which `token` takes up at the start of `source`.
+fn source_length(token: &Token, source: &[char]) -> usize {
+    let count_while =
+        |predicate: fn(&char) -> bool| source.iter().take_while(|ch| predicate(ch)).count();
+
+    match token {
+        // The display form of integers is normalized, so we look at what was actually written.
+        Token::Int(_) => count_while(|ch| ch.is_ascii_hexdigit() || *ch == 'x'),
+        Token::Str(string) => string.chars().count() + 2,
+        Token::Attribute(_) => attribute_length(source),
+        Token::LineComment(_) => count_while(|ch| *ch != '\n'),
+        _ => token.to_string().chars().count(),
+    }
+}
+
+/// Returns the number of characters which the attribute at the start of `source` takes up.
+/// String arguments, such as the reason of `#[test(should_fail_with = "...")]`, may contain `]`.
+fn attribute_length(source: &[char]) -> usize {
+    let mut in_string = false;
+    for (index, ch) in source.iter().enumerate() {
+        match ch {
+            '"' => in_string = !in_string,
+            ']' if !in_string => return index + 1,
+            _ => (),
+        }
+    }
+    source.len()
+}
+
+fn split_lines(tokens: Vec<SourceToken>) -> Vec<Line> {
+    let mut lines: Vec<Line> = Vec::new();
+    for token in tokens {
+        match lines.last_mut() {
+            Some(line) if token.newlines_before == 0 => line.tokens.push(token),
+            _ => lines
+                .push(Line { blank_line_before: token.newlines_before > 1, tokens: vec![token] }),
+        }
+    }
+    lines
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Indent {
+    level: usize,
+    /// Whether the line continues an expression from the previous line, which indents it further.
+    continues_expression: bool,
+}
+
+impl Indent {
+    fn columns(self, config: &Config) -> usize {
+        (self.level + self.continues_expression as usize) * config.tab_spaces
+    }
+}
+
+struct Printer<'config> {
+    config: &'config Config,
+    output: String,
+    /// The indentation level of the line which opened each of the currently unclosed brackets.
+    open_brackets: Vec<usize>,
+    /// The last token printed, ignoring comments.
+    last_code_token: Option<Token>,
+}
+
+impl<'config> Printer<'config> {
+    fn new(config: &'config Config) -> Self {
+        Printer {
+            config,
+            output: String::new(),
+            open_brackets: Vec::new(),
+            last_code_token: None,
+        }
+    }
+
+    fn print_lines(&mut self, lines: Vec<Line>) {
+        let mut pending = VecDeque::from(lines);
+        while let Some(line) = pending.pop_front() {
+            let indent = self.indent_of(&line);
+            let width = indent.columns(self.config) + code_width(&line.tokens);
+
+            if width > self.config.max_width {
+                if let Some(pieces) = break_line(&line.tokens) {
+                    // The pieces may themselves be too wide so they go back into the queue.
+                    let mut blank_line_before = line.blank_line_before;
+                    let pieces = pieces.into_iter().map(|tokens| {
+                        let blank_line_before = std::mem::take(&mut blank_line_before);
+                        Line { tokens, blank_line_before }
+                    });
+                    for (index, piece) in pieces.enumerate() {
+                        pending.insert(index, piece);
+                    }
+                    continue;
+                }
+            }
+
+            self.print_line(&line, indent);
+        }
+    }
+
+    /// Lines are indented one level deeper than the line which opened the innermost unclosed
+    /// bracket, unless they start by closing that bracket. Lines continuing an expression from
+    /// the previous line are indented by one extra level.
+    fn indent_of(&self, line: &Line) -> Indent {
+        let first_token = &line.tokens[0].token;
+        let starts_with_closing_bracket = is_closing_bracket(first_token);
+
+        let level = match self.open_brackets.last() {
+            Some(level) if starts_with_closing_bracket => *level,
+            Some(level) => level + 1,
+            None => 0,
+        };
+
+        let continues_expression = !starts_with_closing_bracket
+            && *first_token != Token::LeftBrace
+            && self.last_code_token.as_ref().map_or(false, |token| !ends_line_naturally(token));
+
+        Indent { level, continues_expression }
+    }
+
+    fn print_line(&mut self, line: &Line, indent: Indent) {
+        let starts_with_closing_bracket =
+            line.tokens.first().map_or(false, |token| is_closing_bracket(&token.token));
+
+        // At most one blank line is kept, and never at the start of the file or of a block.
+        let follows_opening_bracket =
+            self.last_code_token.as_ref().map_or(false, is_opening_bracket);
+        if line.blank_line_before
+            && !self.output.is_empty()
+            && !follows_opening_bracket
+            && !starts_with_closing_bracket
+        {
+            self.output.push('\n');
+        }
+
+        self.output.push_str(&" ".repeat(indent.columns(self.config)));
+        self.output.push_str(&render(&line.tokens));
+        self.output.push('\n');
+
+        for token in &line.tokens {
+            if token.token == Token::LeftBrace {
+                // Blocks are indented relative to the start of the statement which opened them.
+                self.open_brackets.push(indent.level);
+            } else if is_opening_bracket(&token.token) {
+                self.open_brackets.push(indent.level + indent.continues_expression as usize);
+            } else if is_closing_bracket(&token.token) {
+                self.open_brackets.pop();
+            }
+        }
+
+        if let Some(token) = line.tokens.iter().rev().find(|token| !is_comment(&token.token)) {
+            self.last_code_token = Some(token.token.clone());
+        }
+    }
+}
+
+fn render(tokens: &[SourceToken]) -> String {
+    let mut line = String::new();
+    let mut previous = None;
+    for token in tokens {
+        if previous.map_or(false, |previous| needs_space(previous, token)) {
+            line.push(' ');
+        }
+        line.push_str(&token.text);
+        previous = Some(token);
+    }
+    line
+}
+
+/// The width of a line, ignoring any trailing comment as comments can't be broken up.
+fn code_width(tokens: &[SourceToken]) -> usize {
+    let code = match tokens.split_last() {
+        Some((last, code)) if is_comment(&last.token) => code,
+        _ => tokens,
+    };
+    render(code).chars().count()
+}
+
+/// Breaks a line after the opening bracket of its outermost bracketed list, after each of that
+/// list's commas and before its closing bracket.
+///
+/// Returns `None` if the line contains no bracketed list which could be broken up.
+fn break_line(tokens: &[SourceToken]) -> Option<Vec<Vec<SourceToken>>> {
+    let (open, close) = outermost_bracket_pair(tokens)?;
+
+    let mut pieces = vec![tokens[..=open].to_vec()];
+    let mut item = Vec::new();
+    let mut depth = 0;
+    for token in &tokens[open + 1..close] {
+        if is_opening_bracket(&token.token) {
+            depth += 1;
+        } else if is_closing_bracket(&token.token) {
+            depth -= 1;
+        }
+
+        item.push(token.clone());
+        if depth == 0 && token.token == Token::Comma {
+            pieces.push(std::mem::take(&mut item));
+        }
+    }
+    if !item.is_empty() {
+        pieces.push(item);
+    }
+    pieces.push(tokens[close..].to_vec());
+
+    Some(pieces)
+}
+
+/// Finds the first non-empty bracket pair opened and closed on this line which isn't nested in
+/// another such pair, preferring pairs which contain a comma separated list.
+fn outermost_bracket_pair(tokens: &[SourceToken]) -> Option<(usize, usize)> {
+    let mut open_brackets = Vec::new();
+    // (nesting depth, opening bracket index, closing bracket index)
+    let mut pairs = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if is_opening_bracket(&token.token) {
+            open_brackets.push(index);
+        } else if is_closing_bracket(&token.token) {
+            // Closing brackets which were opened on a previous line don't form a pair.
+            if let Some(open) = open_brackets.pop() {
+                if index > open + 1 {
+                    pairs.push((open_brackets.len(), open, index));
+                }
+            }
+        }
+    }
+
+    let outermost_depth = pairs.iter().map(|(depth, _, _)| *depth).min()?;
+    let outermost_pairs: Vec<_> = pairs
+        .into_iter()
+        .filter(|(depth, _, _)| *depth == outermost_depth)
+        .map(|(_, open, close)| (open, close))
+        .collect();
+
+    let contains_list = |(open, close): &(usize, usize)| {
+        let mut depth = 0;
+        tokens[open + 1..*close].iter().any(|token| {
+            if is_opening_bracket(&token.token) {
+                depth += 1;
+            } else if is_closing_bracket(&token.token) {
+                depth -= 1;
+            }
+            depth == 0 && token.token == Token::Comma
+        })
+    };
+
+    outermost_pairs.iter().find(|pair| contains_list(pair)).or(outermost_pairs.first()).copied()
+}
+
+fn needs_space(previous: &SourceToken, next: &SourceToken) -> bool {
+    use Token::*;
+
+    if is_comment(&next.token) {
+        return true;
+    }
+    if previous.is_prefix {
+        return false;
+    }
+
+    match (&previous.token, &next.token) {
+        (LeftParen | LeftBracket, _) => false,
+        (_, RightParen | RightBracket | Comma | Semicolon) => false,
+        (LeftBrace, RightBrace) => false,
+        (LeftBrace, _) | (_, RightBrace | LeftBrace) => true,
+        (Dot | DoubleDot | DoubleColon, _) | (_, Dot | DoubleDot | DoubleColon | Colon) => false,
+        (Comma | Colon | Semicolon, _) => true,
+        // The lexer splits compound assignments such as `x += 1` into an operator and `=`.
+        (operator, Assign) if Token::assign_shorthand_operators().contains(operator) => {
+            next.space_before
+        }
+        // `<` and `>` may delimit generics rather than compare values, and `|` may delimit
+        // closure parameters, so we keep whatever spacing the user chose around them.
+        (Less | Greater | Pipe, _) | (_, Less | Greater | Pipe) => next.space_before,
+        // Calls and indexing, e.g. `foo(x)`, `array[i]` and `foo(x)[i]`.
+        (Ident(_) | RightParen | RightBracket, LeftParen | LeftBracket) => false,
+        (Keyword(_), LeftParen | LeftBracket) => next.space_before,
+        _ => true,
+    }
+}
+
+fn is_comment(token: &Token) -> bool {
+    matches!(token, Token::LineComment(_))
+}
+
+fn is_opening_bracket(token: &Token) -> bool {
+    matches!(token, Token::LeftParen | Token::LeftBracket | Token::LeftBrace)
+}
+
+fn is_closing_bracket(token: &Token) -> bool {
+    matches!(token, Token::RightParen | Token::RightBracket | Token::RightBrace)
+}
+
+/// Whether a line ending in `token` can be followed by a new statement or item, rather than
+/// by the rest of an expression.
+fn ends_line_naturally(token: &Token) -> bool {
+    matches!(token, Token::Semicolon | Token::Comma | Token::RightBrace | Token::Attribute(_))
+        || is_opening_bracket(token)
+}
+
+/// Operators which are prefix operators when they don't follow an operand.
+fn is_prefix_operator(token: &Token) -> bool {
+    matches!(token, Token::Minus | Token::Bang | Token::Ampersand | Token::Star)
+}
+
+/// Whether `token` can be the last token of an operand, making an operator following it binary.
+fn ends_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Ident(_)
+            | Token::Int(_)
+            | Token::Bool(_)
+            | Token::Str(_)
+            | Token::IntType(_)
+            | Token::RightParen
+            | Token::RightBracket
+            | Token::Keyword(Keyword::Field | Keyword::Bool | Keyword::Char)
+    )
+}
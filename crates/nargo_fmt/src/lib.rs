@@ -0,0 +1,145 @@
+#![forbid(unsafe_code)]
+#![warn(unused_crate_dependencies, unused_extern_crates)]
+#![warn(unreachable_pub)]
+#![warn(clippy::semicolon_if_nothing_returned)]
+
+//! Formatter for Noir source code, used by `nargo fmt`.
+//!
+//! The formatter preserves comments and the line breaks chosen by the user while normalizing
+//! indentation, spacing and blank lines. See the `formatter` module for the details.
+
+mod config;
+mod formatter;
+
+pub use config::Config;
+
+use noirc_errors::CustomDiagnostic;
+
+/// Formats the Noir source code in `source` according to `config`.
+///
+/// Source code which doesn't parse is not formatted, the parser's errors are returned instead.
+pub fn format(source: &str, config: &Config) -> Result<String, Vec<CustomDiagnostic>> {
+    let (_, diagnostics) = noirc_frontend::parse_program(source);
+    let errors: Vec<_> = diagnostics.into_iter().filter(CustomDiagnostic::is_error).collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(formatter::format_source(source, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use noirc_frontend::lexer::Lexer;
+    use noirc_frontend::token::Token;
+
+    use super::{format, Config};
+
+    fn format_default(source: &str) -> String {
+        format(source, &Config::default()).expect("source should parse")
+    }
+
+    /// The tokens of `source` other than comments, which formatting must not change.
+    fn code_tokens(source: &str) -> Vec<Token> {
+        let (tokens, _) = Lexer::lex(source);
+        tokens.0.into_iter().map(|token| token.into_token()).collect()
+    }
+
+    #[test]
+    fn normalizes_spacing_and_indentation() {
+        let source = "fn main(x : Field,y:pub Field)->pub Field{
+let z=x+ -y;// trailing comment
+      // own line comment
+    if z==0{ assert(x!=y); }
+
+
+  foo::bar(x)[0] as u8
+}
+";
+        let expected = "fn main(x: Field, y: pub Field) -> pub Field {
+    let z = x + -y; // trailing comment
+    // own line comment
+    if z == 0 { assert(x != y); }
+
+    foo::bar(x)[0] as u8
+}
+";
+        assert_eq!(format_default(source), expected);
+    }
+
+    #[test]
+    fn breaks_long_lines() {
+        let source = "fn main() {
+    let result = some_function(first_argument, second_argument, [1, 2, 3], fourth_argument);
+}
+";
+        let expected = "fn main() {
+    let result = some_function(
+        first_argument,
+        second_argument,
+        [1, 2, 3],
+        fourth_argument
+    );
+}
+";
+        let config = Config { max_width: 60, ..Config::default() };
+        assert_eq!(format(source, &config).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_brackets_in_attribute_strings() {
+        let source = "#[test(should_fail_with = \"index [3] out of bounds\")]
+fn test_index() {
+    foo([1, 2, 3]);
+}
+";
+        assert_eq!(format_default(source), source);
+    }
+
+    #[test]
+    fn refuses_to_format_invalid_code() {
+        assert!(format("fn main( {", &Config::default()).is_err());
+    }
+
+    #[test]
+    fn formatting_test_programs_is_idempotent() {
+        let tests_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../nargo_cli/tests");
+        let test_data_dirs = ["test_data", "test_data_ssa_refactor"].map(|dir| tests_dir.join(dir));
+        let entries =
+            test_data_dirs.iter().flat_map(|dir| std::fs::read_dir(dir).unwrap().flatten());
+
+        for entry in entries {
+            let src_dir = entry.path().join("src");
+            let Ok(files) = std::fs::read_dir(&src_dir) else { continue };
+
+            for file in files.flatten() {
+                let path = file.path();
+                let source = std::fs::read_to_string(&path).unwrap();
+                // Every test program is valid, so failing to format one is a parser bug which
+                // would otherwise hide it from the checks below.
+                let formatted = match format(&source, &Config::default()) {
+                    Ok(formatted) => formatted,
+                    Err(errors) => {
+                        let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+                        panic!("{} does not parse:\n{}", path.display(), errors.join("\n"))
+                    }
+                };
+
+                assert_eq!(
+                    code_tokens(&source),
+                    code_tokens(&formatted),
+                    "formatting changed the meaning of {}",
+                    path.display()
+                );
+                assert_eq!(
+                    format_default(&formatted),
+                    formatted,
+                    "formatting is not idempotent for {}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
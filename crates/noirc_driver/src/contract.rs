@@ -47,6 +47,12 @@ pub struct ContractFunction {
 
     #[serde(serialize_with = "serialize_circuit", deserialize_with = "deserialize_circuit")]
     pub bytecode: Circuit,
+
+    /// The name of the source function each opcode in `bytecode` was generated for.
+    ///
+    /// This is only tracked by the experimental SSA pass and is empty otherwise.
+    #[serde(skip)]
+    pub opcode_origins: Vec<String>,
 }
 
 impl ContractFunctionType {
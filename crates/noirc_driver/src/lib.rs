@@ -6,8 +6,11 @@
 use clap::Args;
 use fm::FileId;
 use noirc_abi::FunctionSignature;
-use noirc_errors::{CustomDiagnostic, FileDiagnostic};
-use noirc_evaluator::{create_circuit, ssa_refactor::experimental_create_circuit};
+use noirc_errors::{debug_info::DebugInfo, CustomDiagnostic, FileDiagnostic};
+use noirc_evaluator::{
+    create_circuit,
+    ssa_refactor::{experimental_create_circuit, SsaInterpreter, SsaOptions, SsaPass, SsaPipeline},
+};
 use noirc_frontend::graph::{CrateId, CrateName, CrateType, LOCAL_CRATE};
 use noirc_frontend::hir::def_map::{Contract, CrateDefMap};
 use noirc_frontend::hir::Context;
@@ -20,6 +23,7 @@ mod contract;
 mod program;
 
 pub use contract::{CompiledContract, ContractFunction, ContractFunctionType};
+pub use noirc_evaluator::ssa_refactor::InterpreterError;
 pub use program::CompiledProgram;
 
 #[derive(Args, Clone, Debug, Serialize, Deserialize)]
@@ -43,6 +47,34 @@ pub struct CompileOptions {
     /// Compile and optimize using the new experimental SSA pass
     #[arg(long)]
     pub experimental_ssa: bool,
+
+    /// Check that the experimental SSA is well formed after each optimization pass
+    #[arg(long)]
+    pub verify_ssa: bool,
+
+    /// Fail on overflow of integer `+`, `-` and `*` in the experimental SSA, rather than wrapping
+    #[arg(long)]
+    pub check_overflow: bool,
+
+    /// The optimization level of the experimental SSA: 0 only runs the passes needed to generate
+    /// ACIR, 1 runs each optimization once and 2 repeats them until the program stops shrinking
+    #[arg(
+        short = 'O',
+        value_name = "LEVEL",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u8).range(0..=2)
+    )]
+    pub optimization_level: u8,
+
+    /// Run these experimental SSA passes instead of those of the optimization level,
+    /// e.g. `inline,licm,unroll,simplify-cfg,flatten,repeat(mem2reg,fold-constants,gvn,die)`.
+    /// The pipeline must run `inline`, `unroll`, `flatten`, `mem2reg` and `die`, in that order.
+    #[arg(long, value_name = "PIPELINE", value_parser = parse_ssa_pipeline)]
+    pub passes: Option<String>,
+
+    /// Emit the experimental SSA after each run of the given pass
+    #[arg(long, value_name = "PASS", value_parser = parse_ssa_pass)]
+    pub show_ssa_after: Vec<String>,
 }
 
 impl Default for CompileOptions {
@@ -53,10 +85,46 @@ impl Default for CompileOptions {
             deny_warnings: false,
             show_output: true,
             experimental_ssa: false,
+            verify_ssa: false,
+            check_overflow: false,
+            optimization_level: 1,
+            passes: None,
+            show_ssa_after: Vec::new(),
+        }
+    }
+}
+
+impl CompileOptions {
+    /// The options the experimental SSA is compiled with.
+    fn ssa_options(&self) -> SsaOptions {
+        let pipeline = match &self.passes {
+            Some(passes) => passes.parse().expect("--passes is validated when it is parsed"),
+            None => SsaPipeline::for_optimization_level(self.optimization_level),
+        };
+        let show_ssa_after = self
+            .show_ssa_after
+            .iter()
+            .map(|pass| pass.parse().expect("--show-ssa-after is validated when it is parsed"));
+        SsaOptions {
+            pipeline,
+            print_ssa_passes: self.show_ssa,
+            show_ssa_after: show_ssa_after.collect(),
+            verify_ssa: self.verify_ssa,
+            check_overflow: self.check_overflow,
         }
     }
 }
 
+fn parse_ssa_pipeline(pipeline: &str) -> Result<String, String> {
+    pipeline.parse::<SsaPipeline>()?;
+    Ok(pipeline.to_owned())
+}
+
+fn parse_ssa_pass(pass: &str) -> Result<String, String> {
+    pass.parse::<SsaPass>()?;
+    Ok(pass.to_owned())
+}
+
 /// Helper type used to signify where only warnings are expected in file diagnostics
 pub type Warnings = Vec<FileDiagnostic>;
 
@@ -196,10 +264,8 @@ pub fn compile_main(
     let main = match context.get_main_function(&LOCAL_CRATE) {
         Some(m) => m,
         None => {
-            let err = FileDiagnostic {
-                    file_id: FileId::default(),
-                    diagnostic: CustomDiagnostic::from_message("cannot compile crate into a program as the local crate is not a binary. For libraries, please use the check command")
-                };
+            let err = CustomDiagnostic::from_message("cannot compile crate into a program as the local crate is not a binary. For libraries, please use the check command")
+                .in_file(FileId::default());
             return Err(vec![err]);
         }
     };
@@ -290,6 +356,7 @@ fn compile_contract(
             is_internal: func_meta.is_internal.unwrap_or(false),
             abi: function.abi,
             bytecode: function.circuit,
+            opcode_origins: function.opcode_origins,
         });
     }
 
@@ -312,11 +379,32 @@ pub fn compile_no_check(
 ) -> Result<CompiledProgram, FileDiagnostic> {
     let program = monomorphize(main_function, &context.def_interner);
 
-    let (circuit, abi) = if options.experimental_ssa {
-        experimental_create_circuit(program, options.show_ssa, options.show_output)?
+    let (circuit, abi, opcode_origins, debug) = if options.experimental_ssa {
+        experimental_create_circuit(program, &options.ssa_options(), options.show_output)?
     } else {
-        create_circuit(program, options.show_ssa, options.show_output)?
+        // The legacy SSA pass always wraps on overflow, so overflow checks can't be honoured.
+        let checked_function = program.functions.iter().find(|function| function.check_overflow);
+        let unsupported = match checked_function {
+            _ if options.check_overflow => Some("--check-overflow".to_owned()),
+            Some(function) => Some(format!("#[check_overflow] on `{}`", function.name)),
+            None => None,
+        };
+        if let Some(unsupported) = unsupported {
+            let message = format!("{unsupported} requires --experimental-ssa");
+            return Err(CustomDiagnostic::from_message(&message).in_file(FileId::default()));
+        }
+
+        let (circuit, abi) = create_circuit(program, options.show_ssa, options.show_output)?;
+        // The legacy SSA pass doesn't track where opcodes come from.
+        (circuit, abi, Vec::new(), DebugInfo::default())
     };
 
-    Ok(CompiledProgram { circuit, abi })
+    Ok(CompiledProgram { circuit, abi, opcode_origins, debug })
+}
+
+/// Prepares the SSA of the main function to be interpreted rather than compiled into a circuit.
+/// Assumes self.check_crate is called beforehand!
+pub fn create_ssa_interpreter(context: &Context, main_function: FuncId) -> SsaInterpreter {
+    let program = monomorphize(main_function, &context.def_interner);
+    SsaInterpreter::new(program)
 }
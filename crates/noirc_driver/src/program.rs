@@ -1,4 +1,5 @@
 use acvm::acir::circuit::Circuit;
+use noirc_errors::debug_info::DebugInfo;
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -7,6 +8,16 @@ pub struct CompiledProgram {
     #[serde(serialize_with = "serialize_circuit", deserialize_with = "deserialize_circuit")]
     pub circuit: Circuit,
     pub abi: noirc_abi::Abi,
+    /// The name of the source function each opcode in `circuit` was generated for.
+    ///
+    /// This is only tracked by the experimental SSA pass and is empty otherwise.
+    #[serde(skip)]
+    pub opcode_origins: Vec<String>,
+    /// Relates the opcodes of `circuit` back to the source for debugging.
+    ///
+    /// This is only tracked by the experimental SSA pass and is empty otherwise.
+    #[serde(skip)]
+    pub debug: DebugInfo,
 }
 
 pub(crate) fn serialize_circuit<S>(circuit: &Circuit, s: S) -> Result<S::Ok, S::Error>
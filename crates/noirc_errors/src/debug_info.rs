@@ -0,0 +1,77 @@
+use acvm::acir::{circuit::OpcodeLabel, native_types::Expression};
+
+use crate::Location;
+
+/// Relates the opcodes of a compiled circuit back to the Noir source they were generated from.
+#[derive(Debug, Clone, Default)]
+pub struct DebugInfo {
+    /// The source location of each opcode, indexed by opcode.
+    pub opcode_locations: Vec<Option<Location>>,
+    /// The named variables of `main`, in the order they are defined.
+    pub variables: Vec<DebugVariable>,
+}
+
+/// A variable in the Noir source whose value can be read from the witness map.
+#[derive(Debug, Clone)]
+pub struct DebugVariable {
+    pub name: String,
+    /// The name of the function the variable was declared in, which differs from `main` for
+    /// variables of inlined functions.
+    pub function: String,
+    /// The value of each field element making up the variable.
+    pub value: Vec<Expression>,
+    /// The index of the first opcode executed once the variable has been assigned.
+    pub defined_at: usize,
+    /// The index of the first opcode executed once the variable has gone out of scope, by
+    /// returning from the inlined function call it was declared in. Variables of `main` never go
+    /// out of scope.
+    pub dropped_at: Option<usize>,
+}
+
+impl DebugInfo {
+    pub fn location(&self, opcode_index: usize) -> Option<Location> {
+        self.opcode_locations.get(opcode_index).copied().flatten()
+    }
+
+    /// Returns the variables which have been assigned before the opcode at `opcode_index` runs,
+    /// and have not gone out of scope yet.
+    pub fn variables_in_scope(&self, opcode_index: usize) -> impl Iterator<Item = &DebugVariable> {
+        self.variables.iter().filter(move |variable| {
+            variable.defined_at <= opcode_index
+                && variable.dropped_at.map_or(true, |dropped_at| opcode_index < dropped_at)
+        })
+    }
+
+    /// Maps the debug info of an unoptimized circuit onto the opcodes of the optimized circuit,
+    /// using the labels returned by the optimizer.
+    pub fn update_acir(&mut self, opcode_labels: &[OpcodeLabel]) {
+        if self.opcode_locations.is_empty() {
+            // Debug info was not tracked for this circuit
+            return;
+        }
+
+        let original_index = |label: &OpcodeLabel| match label {
+            OpcodeLabel::Resolved(index) => Some(*index as usize),
+            OpcodeLabel::Unresolved => None,
+        };
+
+        self.opcode_locations = opcode_labels
+            .iter()
+            .map(|label| original_index(label).and_then(|index| self.opcode_locations[index]))
+            .collect();
+
+        // The first optimized opcode which was generated at or after the original `opcode_index`
+        let updated_index = |opcode_index: usize| {
+            opcode_labels
+                .iter()
+                .position(|label| {
+                    original_index(label).map_or(false, |index| index >= opcode_index)
+                })
+                .unwrap_or(opcode_labels.len())
+        };
+        for variable in &mut self.variables {
+            variable.defined_at = updated_index(variable.defined_at);
+            variable.dropped_at = variable.dropped_at.map(updated_index);
+        }
+    }
+}
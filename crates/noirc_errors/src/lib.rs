@@ -3,6 +3,7 @@
 #![warn(unreachable_pub)]
 #![warn(clippy::semicolon_if_nothing_returned)]
 
+pub mod debug_info;
 mod position;
 pub mod reporter;
 pub use position::{Location, Position, Span, Spanned};
@@ -12,6 +13,16 @@ pub use reporter::{CustomDiagnostic, DiagnosticKind};
 pub struct FileDiagnostic {
     pub file_id: fm::FileId,
     pub diagnostic: CustomDiagnostic,
+
+    /// The locations of the function calls leading to the error, outermost first.
+    /// These are reported alongside the diagnostic, and may be in other files.
+    pub call_stack: Vec<Location>,
+}
+
+impl FileDiagnostic {
+    pub fn with_call_stack(self, call_stack: Vec<Location>) -> FileDiagnostic {
+        FileDiagnostic { call_stack, ..self }
+    }
 }
 
 impl From<FileDiagnostic> for Vec<FileDiagnostic> {
@@ -1,4 +1,4 @@
-use crate::{FileDiagnostic, Span};
+use crate::{FileDiagnostic, Location, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
@@ -60,7 +60,7 @@ impl CustomDiagnostic {
     }
 
     pub fn in_file(self, file_id: fm::FileId) -> FileDiagnostic {
-        FileDiagnostic { file_id, diagnostic: self }
+        FileDiagnostic { file_id, diagnostic: self, call_stack: Vec::new() }
     }
 
     pub fn add_note(&mut self, message: String) {
@@ -113,7 +113,11 @@ pub fn report_all(
 ) -> ReportedErrors {
     let error_count = diagnostics
         .iter()
-        .map(|error| report(files, &error.diagnostic, Some(error.file_id), deny_warnings) as u32)
+        .map(|error| {
+            let file = Some(error.file_id);
+            report_with_call_stack(files, &error.diagnostic, file, &error.call_stack, deny_warnings)
+                as u32
+        })
         .sum();
 
     ReportedErrors { error_count }
@@ -125,11 +129,28 @@ pub fn report(
     custom_diagnostic: &CustomDiagnostic,
     file: Option<fm::FileId>,
     deny_warnings: bool,
+) -> bool {
+    report_with_call_stack(files, custom_diagnostic, file, &[], deny_warnings)
+}
+
+/// Report the given diagnostic along with the calls leading to it, which are labelled
+/// in the files they were made in, and return true if it was an error
+fn report_with_call_stack(
+    files: &fm::FileManager,
+    custom_diagnostic: &CustomDiagnostic,
+    file: Option<fm::FileId>,
+    call_stack: &[Location],
+    deny_warnings: bool,
 ) -> bool {
     let writer = StandardStream::stderr(ColorChoice::Always);
     let config = codespan_reporting::term::Config::default();
 
-    let diagnostic = convert_diagnostic(custom_diagnostic, file, deny_warnings);
+    let call_stack_labels = call_stack.iter().map(|call| {
+        let range = call.span.start() as usize..call.span.end() as usize + 1;
+        Label::secondary(call.file.as_usize(), range).with_message("in this call")
+    });
+    let diagnostic = convert_diagnostic(custom_diagnostic, file, deny_warnings)
+        .with_labels(call_stack_labels.collect());
     term::emit(&mut writer.lock(), &config, files.as_simple_files(), &diagnostic).unwrap();
 
     deny_warnings || custom_diagnostic.is_error()
@@ -2,12 +2,15 @@ pub(crate) mod brillig_black_box;
 pub(crate) mod brillig_block;
 pub(crate) mod brillig_directive;
 pub(crate) mod brillig_fn;
+pub(crate) mod variable_liveness;
 
 use crate::ssa_refactor::ir::{function::Function, post_order::PostOrder};
 
 use std::collections::HashMap;
 
-use self::{brillig_block::BrilligBlock, brillig_fn::FunctionContext};
+use self::{
+    brillig_block::BrilligBlock, brillig_fn::FunctionContext, variable_liveness::VariableLiveness,
+};
 
 use super::brillig_ir::{artifact::BrilligArtifact, BrilligContext};
 
@@ -21,8 +24,11 @@ pub(crate) fn convert_ssa_function(func: &Function) -> BrilligArtifact {
     reverse_post_order.extend_from_slice(PostOrder::with_function(func).as_slice());
     reverse_post_order.reverse();
 
-    let mut function_context =
-        FunctionContext { function_id: func.id(), ssa_value_to_register: HashMap::new() };
+    let mut function_context = FunctionContext {
+        function_id: func.id(),
+        ssa_value_to_register: HashMap::new(),
+        liveness: VariableLiveness::from_function(func, &reverse_post_order),
+    };
 
     let mut brillig_context = BrilligContext::new(
         FunctionContext::parameters(func),
@@ -36,3 +42,91 @@ pub(crate) fn convert_ssa_function(func: &Function) -> BrilligArtifact {
 
     brillig_context.artifact()
 }
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::Opcode as BrilligOpcode;
+
+    use crate::ssa_refactor::ssa_gen::Ssa;
+
+    use super::convert_ssa_function;
+
+    /// Returns the number of registers the Brillig bytecode of the program's main function needs.
+    fn count_registers(src: &str) -> usize {
+        let ssa: Ssa = src.parse().unwrap();
+        let artifact = convert_ssa_function(ssa.main());
+
+        let mut registers = Vec::new();
+        for opcode in &artifact.byte_code {
+            match opcode {
+                BrilligOpcode::BinaryFieldOp { destination, lhs, rhs, .. }
+                | BrilligOpcode::BinaryIntOp { destination, lhs, rhs, .. } => {
+                    registers.extend([destination, lhs, rhs]);
+                }
+                BrilligOpcode::Mov { destination, source } => {
+                    registers.extend([destination, source]);
+                }
+                BrilligOpcode::Load { destination, source_pointer } => {
+                    registers.extend([destination, source_pointer]);
+                }
+                BrilligOpcode::Store { destination_pointer, source } => {
+                    registers.extend([destination_pointer, source]);
+                }
+                BrilligOpcode::Const { destination, .. } => registers.push(destination),
+                BrilligOpcode::JumpIf { condition, .. } => registers.push(condition),
+                _ => (),
+            }
+        }
+        registers.into_iter().map(|register| register.to_usize() + 1).max().unwrap_or_default()
+    }
+
+    /// A function multiplying its parameter by itself `length` times
+    fn multiplication_chain(length: usize) -> String {
+        let mut src = String::from("brillig fn main f0 {\n  b0(v0: u32):\n");
+        for i in 1..=length {
+            src += &format!("    v{i} = mul v{}, v0\n", i - 1);
+        }
+        src + &format!("    return v{length}\n}}")
+    }
+
+    /// A loop summing `i * y^length` for each `i` below `x`
+    fn loop_with_multiplication_chain(length: usize) -> String {
+        let mut src = String::from(
+            "brillig fn main f0 {
+              b0(v0: u32, v1: u32):
+                jmp b1(u32 0, u32 0)
+              b1(v2: u32, v3: u32):
+                v4 = lt v2, v0
+                jmpif v4 then: b2, else: b3
+              b2():
+                v10 = mul v2, v1\n",
+        );
+        for i in 11..10 + length {
+            src += &format!("    v{i} = mul v{}, v1\n", i - 1);
+        }
+        src + &format!(
+            "    v5 = add v3, v{}
+                v6 = add v2, u32 1
+                jmp b1(v6, v5)
+              b3():
+                return v3
+            }}",
+            9 + length
+        )
+    }
+
+    #[test]
+    fn registers_of_dead_values_are_reused() {
+        // Only the parameter and the last two products of the chain are held at once, after the
+        // two reserved registers. The result is returned through the register of a dead product.
+        assert_eq!(count_registers(&multiplication_chain(4)), 5);
+        assert_eq!(count_registers(&multiplication_chain(40)), 5);
+    }
+
+    #[test]
+    fn registers_are_reused_across_loop_iterations() {
+        let short_loop = count_registers(&loop_with_multiplication_chain(2));
+        let long_loop = count_registers(&loop_with_multiplication_chain(20));
+        assert_eq!(short_loop, long_loop);
+    }
+}
@@ -18,6 +18,7 @@ use iter_extended::vecmap;
 
 use super::brillig_black_box::convert_black_box_call;
 use super::brillig_fn::FunctionContext;
+use super::variable_liveness::ProgramPoint;
 
 /// Generate the compilation artifacts for compiling a function into brillig bytecode.
 pub(crate) struct BrilligBlock<'block> {
@@ -26,6 +27,8 @@ pub(crate) struct BrilligBlock<'block> {
     block_id: BasicBlockId,
     /// Context for creating brillig opcodes
     brillig_context: &'block mut BrilligContext,
+    /// Registers holding constants and array literals for the instruction being converted
+    temporary_registers: Vec<RegisterIndex>,
 }
 
 impl<'block> BrilligBlock<'block> {
@@ -36,7 +39,12 @@ impl<'block> BrilligBlock<'block> {
         block_id: BasicBlockId,
         dfg: &DataFlowGraph,
     ) {
-        let mut brillig_block = BrilligBlock { function_context, block_id, brillig_context };
+        let mut brillig_block = BrilligBlock {
+            function_context,
+            block_id,
+            brillig_context,
+            temporary_registers: Vec::new(),
+        };
 
         brillig_block.convert_block(dfg);
     }
@@ -49,16 +57,28 @@ impl<'block> BrilligBlock<'block> {
         // Convert the block parameters
         let block = &dfg[self.block_id];
         self.convert_block_params(block, dfg);
+        self.deallocate_registers(ProgramPoint::BlockStart(self.block_id));
 
         // Convert all of the instructions into the block
         for instruction_id in block.instructions() {
             self.convert_ssa_instruction(*instruction_id, dfg);
+            self.deallocate_registers(ProgramPoint::Instruction(*instruction_id));
         }
 
         // Process the block's terminator instruction
         let terminator_instruction =
             block.terminator().expect("block is expected to be constructed");
         self.convert_ssa_terminator(terminator_instruction, dfg);
+        self.deallocate_registers(ProgramPoint::Terminator(self.block_id));
+    }
+
+    /// Frees the temporary registers used by the program point which has just been converted,
+    /// along with the registers of the values which are no longer needed after it.
+    fn deallocate_registers(&mut self, point: ProgramPoint) {
+        for register in self.temporary_registers.drain(..) {
+            self.brillig_context.deallocate_register(register);
+        }
+        self.function_context.deallocate_dead_registers(self.brillig_context, point);
     }
 
     fn get_bit_size_from_ssa_type(typ: Type) -> u32 {
@@ -315,6 +335,7 @@ impl<'block> BrilligBlock<'block> {
                     destination,
                     size_register,
                 );
+                self.brillig_context.deallocate_register(size_register);
 
                 // Then set the value in the newly created array
                 let index_register = self.convert_ssa_value(*index, dfg);
@@ -336,10 +357,15 @@ impl<'block> BrilligBlock<'block> {
     ) {
         let value = &dfg[value_id];
         match value {
-            Value::Param { .. } | Value::Instruction { .. } | Value::NumericConstant { .. } => {
+            Value::Param { .. } | Value::Instruction { .. } => {
                 let value_register = self.convert_ssa_value(value_id, dfg);
                 self.brillig_context.store_instruction(address_register, value_register);
             }
+            Value::NumericConstant { constant, .. } => {
+                let value_register = self.brillig_context.make_constant((*constant).into());
+                self.brillig_context.store_instruction(address_register, value_register);
+                self.brillig_context.deallocate_register(value_register);
+            }
             Value::Array { array, element_type } => {
                 // Allocate a register for the iterator
                 let iterator_register = self.brillig_context.allocate_register();
@@ -364,6 +390,8 @@ impl<'block> BrilligBlock<'block> {
                         },
                     );
                 }
+                self.brillig_context.deallocate_register(iterator_register);
+                self.brillig_context.deallocate_register(size_of_item_register);
             }
             _ => unimplemented!("ICE: Value {:?} not storeable in memory", value),
         }
@@ -440,10 +468,8 @@ impl<'block> BrilligBlock<'block> {
                 self.function_context.get_or_create_register(self.brillig_context, value_id)
             }
             Value::NumericConstant { constant, .. } => {
-                let register_index =
-                    self.function_context.get_or_create_register(self.brillig_context, value_id);
-
-                self.brillig_context.const_instruction(register_index, (*constant).into());
+                let register_index = self.brillig_context.make_constant((*constant).into());
+                self.temporary_registers.push(register_index);
                 register_index
             }
             Value::Array { .. } => {
@@ -453,6 +479,7 @@ impl<'block> BrilligBlock<'block> {
                     compute_size_of_type(&dfg.type_of_value(value_id)),
                 );
                 self.store_in_memory(address_register, value_id, dfg);
+                self.temporary_registers.push(address_register);
                 address_register
             }
             _ => {
@@ -14,12 +14,17 @@ use crate::{
     },
 };
 
-use super::brillig_block::compute_size_of_type;
+use super::{
+    brillig_block::compute_size_of_type,
+    variable_liveness::{ProgramPoint, VariableLiveness},
+};
 
 pub(crate) struct FunctionContext {
     pub(crate) function_id: FunctionId,
     /// Map from SSA values to Register Indices.
     pub(crate) ssa_value_to_register: HashMap<ValueId, RegisterIndex>,
+    /// Where the registers of SSA values can be freed.
+    pub(crate) liveness: VariableLiveness,
 }
 
 impl FunctionContext {
@@ -48,6 +53,20 @@ impl FunctionContext {
         register
     }
 
+    /// Frees the registers of the values which are no longer needed once `point` has been
+    /// lowered, so that values defined later on can reuse them.
+    pub(crate) fn deallocate_dead_registers(
+        &mut self,
+        brillig_context: &mut BrilligContext,
+        point: ProgramPoint,
+    ) {
+        for value in self.liveness.last_uses(point) {
+            if let Some(register) = self.ssa_value_to_register.remove(value) {
+                brillig_context.deallocate_register(register);
+            }
+        }
+    }
+
     /// Creates a function label from a given SSA function id.
     pub(crate) fn function_id_to_function_label(function_id: FunctionId) -> Label {
         function_id.to_string()
@@ -0,0 +1,224 @@
+//! Liveness analysis of the values of an SSA function being lowered into Brillig, so that the
+//! register of a value can be reused once the value is no longer needed.
+//!
+//! The blocks of the function are laid out in the order they are lowered in, and each value is
+//! given a live interval over this order: from the first program point writing it to the last
+//! program point reading it, spanning every block the value is live across. Registers are then
+//! allocated by a linear scan over the same order. A value takes a free register when it is first
+//! written, and its register is freed once the last program point of its interval is lowered, so
+//! values only share a register when their intervals do not overlap.
+//!
+//! Block parameters are written by the `jmp` terminating each predecessor of their block, so the
+//! intervals of block parameters start at those terminators. Constants and array literals are
+//! written into temporary registers at each of their uses, so they are not tracked here.
+use std::collections::{HashMap, HashSet};
+
+use crate::ssa_refactor::ir::{
+    basic_block::BasicBlockId,
+    dfg::DataFlowGraph,
+    function::Function,
+    instruction::{InstructionId, TerminatorInstruction},
+    value::{Value, ValueId},
+};
+
+/// A point of a function at which registers are read or written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ProgramPoint {
+    /// The start of a block, where its parameters are received
+    BlockStart(BasicBlockId),
+    Instruction(InstructionId),
+    Terminator(BasicBlockId),
+}
+
+/// The program points at which the live interval of each value of a function ends.
+#[derive(Debug)]
+pub(crate) struct VariableLiveness {
+    last_uses: HashMap<ProgramPoint, Vec<ValueId>>,
+}
+
+impl VariableLiveness {
+    /// Computes the live intervals of the values of `func`, whose reachable blocks are lowered
+    /// in the order of `blocks`.
+    pub(crate) fn from_function(func: &Function, blocks: &[BasicBlockId]) -> Self {
+        let dfg = &func.dfg;
+        let live_in = compute_live_in(dfg, blocks);
+
+        // Program points are visited in the order they are lowered, so the last point recorded
+        // for a value ends its interval.
+        let mut interval_ends: HashMap<ValueId, ProgramPoint> = HashMap::new();
+        let mut record = |value: ValueId, point: ProgramPoint| {
+            if is_tracked(dfg, value) {
+                interval_ends.insert(value, point);
+            }
+        };
+
+        for block in blocks {
+            let start = ProgramPoint::BlockStart(*block);
+            for value in dfg.block_parameters(*block).iter().chain(&live_in[block]) {
+                record(*value, start);
+            }
+
+            for instruction in dfg[*block].instructions() {
+                let point = ProgramPoint::Instruction(*instruction);
+                dfg[*instruction].for_each_value(|value| record(value, point));
+                for result in dfg.instruction_results(*instruction) {
+                    record(*result, point);
+                }
+            }
+
+            let point = ProgramPoint::Terminator(*block);
+            let terminator = dfg[*block].unwrap_terminator();
+            terminator.for_each_value(|value| record(value, point));
+            if let TerminatorInstruction::Jmp { destination, .. } = terminator {
+                for parameter in dfg.block_parameters(*destination) {
+                    record(*parameter, point);
+                }
+            }
+            for successor in dfg[*block].successors() {
+                for value in &live_in[&successor] {
+                    record(*value, point);
+                }
+            }
+        }
+
+        let mut last_uses: HashMap<ProgramPoint, Vec<ValueId>> = HashMap::new();
+        for (value, point) in interval_ends {
+            last_uses.entry(point).or_default().push(value);
+        }
+        VariableLiveness { last_uses }
+    }
+
+    /// Returns the values which are no longer needed once `point` has been lowered.
+    pub(crate) fn last_uses(&self, point: ProgramPoint) -> &[ValueId] {
+        self.last_uses.get(&point).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Only block parameters and instruction results are held in registers across instructions.
+fn is_tracked(dfg: &DataFlowGraph, value: ValueId) -> bool {
+    matches!(&dfg[value], Value::Param { .. } | Value::Instruction { .. })
+}
+
+/// Computes the values which are live on entry to each block, other than its own parameters.
+fn compute_live_in(
+    dfg: &DataFlowGraph,
+    blocks: &[BasicBlockId],
+) -> HashMap<BasicBlockId, HashSet<ValueId>> {
+    let mut live_in: HashMap<BasicBlockId, HashSet<ValueId>> =
+        blocks.iter().map(|block| (*block, HashSet::new())).collect();
+
+    // Visiting the blocks in post order visits most successors before their predecessors, so
+    // only loops need more than one iteration to reach the fixed point.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for block in blocks.iter().rev() {
+            let mut live: HashSet<ValueId> = HashSet::new();
+            for successor in dfg[*block].successors() {
+                live.extend(&live_in[&successor]);
+            }
+
+            dfg[*block].unwrap_terminator().for_each_value(|value| {
+                if is_tracked(dfg, value) {
+                    live.insert(value);
+                }
+            });
+            for instruction in dfg[*block].instructions().iter().rev() {
+                for result in dfg.instruction_results(*instruction) {
+                    live.remove(result);
+                }
+                dfg[*instruction].for_each_value(|value| {
+                    if is_tracked(dfg, value) {
+                        live.insert(value);
+                    }
+                });
+            }
+            for parameter in dfg.block_parameters(*block) {
+                live.remove(parameter);
+            }
+
+            if live != live_in[block] {
+                live_in.insert(*block, live);
+                changed = true;
+            }
+        }
+    }
+    live_in
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::ssa_refactor::{
+        ir::{
+            basic_block::BasicBlockId, instruction::TerminatorInstruction, post_order::PostOrder,
+        },
+        ssa_gen::Ssa,
+    };
+
+    use super::{ProgramPoint, VariableLiveness};
+
+    #[test]
+    fn values_live_in_a_loop_are_held_until_its_back_edge() {
+        // let mut sum = 0; for i in 0..x { sum += i * y } sum + y
+        let src = "
+            brillig fn main f0 {
+              b0(v0: u32, v1: u32):
+                jmp b1(u32 0, u32 0)
+              b1(v2: u32, v3: u32):
+                v4 = lt v2, v0
+                jmpif v4 then: b2, else: b3
+              b2():
+                v5 = mul v2, v1
+                v6 = add v3, v5
+                v7 = add v2, u32 1
+                jmp b1(v7, v6)
+              b3():
+                v8 = add v3, v1
+                return v8
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        let function = ssa.main();
+        let dfg = &function.dfg;
+
+        let b0 = function.entry_block();
+        let b1 = dfg[b0].successors().next().unwrap();
+        let Some(TerminatorInstruction::JmpIf {
+            then_destination: b2, else_destination: b3, ..
+        }) = dfg[b1].terminator()
+        else {
+            panic!("Expected the loop header to end in a jmpif");
+        };
+        let (b2, b3) = (*b2, *b3);
+
+        // The exit of the loop is laid out before its body
+        let mut blocks = PostOrder::with_function(function).as_slice().to_vec();
+        blocks.reverse();
+        assert_eq!(blocks, vec![b0, b1, b3, b2]);
+        let liveness = VariableLiveness::from_function(function, &blocks);
+
+        let last_uses = |point| liveness.last_uses(point).iter().copied().collect::<HashSet<_>>();
+        let results = |block: BasicBlockId, index: usize| {
+            dfg.instruction_results(dfg[block].instructions()[index])[0]
+        };
+        let instruction = |block: BasicBlockId, index: usize| {
+            ProgramPoint::Instruction(dfg[block].instructions()[index])
+        };
+
+        let params = [dfg.block_parameters(b0), dfg.block_parameters(b1)].concat();
+        let (v5, v6, v7, v8) = (results(b2, 0), results(b2, 1), results(b2, 2), results(b3, 0));
+
+        // The values used on each iteration of the loop, including those also used after it,
+        // are held until the back edge, which is the last block laid out.
+        let mut expected: HashSet<_> = params.into_iter().collect();
+        expected.extend([v6, v7]);
+        assert_eq!(last_uses(ProgramPoint::Terminator(b2)), expected);
+
+        assert_eq!(last_uses(ProgramPoint::Terminator(b1)), HashSet::from([results(b1, 0)]));
+        assert_eq!(last_uses(instruction(b2, 1)), HashSet::from([v5]));
+        assert!(last_uses(instruction(b3, 0)).is_empty());
+        assert_eq!(last_uses(ProgramPoint::Terminator(b3)), HashSet::from([v8]));
+    }
+}
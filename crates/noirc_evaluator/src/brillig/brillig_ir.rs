@@ -119,6 +119,7 @@ impl BrilligContext {
         // debug_show handled by allocate_array_instruction
         let size_register = self.make_constant(size.into());
         self.allocate_array_instruction(pointer_register, size_register);
+        self.deallocate_register(size_register);
     }
 
     /// Allocates an array of size contained in size_register and stores the
@@ -176,6 +177,7 @@ impl BrilligContext {
             lhs: ReservedRegisters::stack_pointer(),
             rhs: size_register,
         });
+        self.deallocate_register(size_register);
     }
 
     /// Gets the value in the array at index `index` and stores it in `result`
@@ -351,8 +353,6 @@ impl BrilligContext {
     }
 
     /// Push a register to the deallocation list, ready for reuse.
-    /// TODO(AD): currently, register deallocation is only done with immediate values.
-    /// TODO(AD): See https://github.com/noir-lang/noir/issues/1720
     pub(crate) fn deallocate_register(&mut self, register_index: RegisterIndex) {
         self.registers.deallocate_register(register_index);
     }
@@ -386,9 +386,7 @@ impl BrilligContext {
         let mut destinations = Vec::with_capacity(return_registers.len());
 
         for (destination_index, return_register) in return_registers.iter().enumerate() {
-            // In case we have fewer return registers than indices to write to, ensure we've allocated this register
             let destination_register = ReservedRegisters::user_register_index(destination_index);
-            self.registers.ensure_register_is_allocated(destination_register);
             sources.push(*return_register);
             destinations.push(destination_register);
         }
@@ -403,6 +401,16 @@ impl BrilligContext {
         sources: Vec<RegisterIndex>,
         destinations: Vec<RegisterIndex>,
     ) {
+        // Sources and destinations may be free registers, such as the registers arguments are
+        // passed in, so they are reserved while the temporary registers are allocated.
+        let mut reserved_registers = Vec::new();
+        for register in sources.iter().chain(&destinations) {
+            if self.registers.is_free(*register) {
+                self.registers.ensure_register_is_allocated(*register);
+                reserved_registers.push(*register);
+            }
+        }
+
         let new_sources: Vec<_> = sources
             .iter()
             .map(|source| {
@@ -414,6 +422,10 @@ impl BrilligContext {
         for (new_source, destination) in new_sources.iter().zip(destinations.iter()) {
             self.mov_instruction(*destination, *new_source);
         }
+
+        for register in new_sources.into_iter().chain(reserved_registers) {
+            self.deallocate_register(register);
+        }
     }
 
     /// Emits a `mov` instruction.
@@ -692,6 +704,7 @@ impl BrilligContext {
             self.usize_op(iterator_register, BinaryIntOp::Sub, 1);
             self.load_instruction(*register, iterator_register);
         }
+        self.deallocate_register(iterator_register);
     }
 
     /// Utility method to perform a binary instruction with a constant value
@@ -740,11 +753,10 @@ impl BrilligContext {
         result_registers: &[RegisterIndex],
         saved_registers: &[RegisterIndex],
     ) {
-        // Allocate our result registers and write into them
-        // We assume the return values of our call are held in 0..num results register indices
-        for (i, result_register) in result_registers.iter().enumerate() {
-            self.mov_instruction(*result_register, self.register(i));
-        }
+        // Move the return values, held in 0..num results register indices, into our result
+        // registers. These may have been reused from the registers the values are returned in.
+        let sources = (0..result_registers.len()).map(|i| self.register(i)).collect();
+        self.mov_registers_to_registers_instruction(sources, result_registers.to_vec());
 
         // Restore all the same registers we have, in exact reverse order.
         // Note that we have allocated some registers above, which we will not be handling here,
@@ -778,7 +790,7 @@ pub(crate) enum BrilligBinaryOp {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::vec;
 
     use acvm::acir::brillig::{
@@ -792,7 +804,7 @@ mod tests {
 
     use super::{BrilligBinaryOp, BrilligOpcode, ReservedRegisters};
 
-    struct DummyBlackBoxSolver;
+    pub(crate) struct DummyBlackBoxSolver;
 
     impl BlackBoxFunctionSolver for DummyBlackBoxSolver {
         fn schnorr_verify(
@@ -0,0 +1,414 @@
+//! A peephole optimizer for the bytecode of a linked Brillig artifact.
+//!
+//! The bytecode emitted for each SSA instruction is correct in isolation, but leaves behind
+//! opcodes which are redundant in context:
+//! - moves between registers, such as those through the temporary registers of parallel moves,
+//!   whose destination is never read before being overwritten
+//! - jumps to the opcode which follows them, such as the jump to the next block laid out
+//! - constants loaded into registers which already hold them, such as the constant temporaries
+//!   of each iteration of a loop
+//!
+//! The optimizer runs before jumps are resolved, so removing opcodes only shifts the positions
+//! of labels and unresolved jumps. Calls and returns are treated as reading every register.
+use std::collections::{HashMap, HashSet};
+
+use acvm::acir::brillig::{Opcode as BrilligOpcode, RegisterIndex, Value};
+
+use super::{BrilligArtifact, OpcodeLocation};
+
+impl BrilligArtifact {
+    /// Removes redundant opcodes from the bytecode of a linked artifact.
+    ///
+    /// Artifacts with calls to functions which have not been linked are left as they are, as
+    /// the effects of those calls are unknown.
+    pub(crate) fn optimize(&mut self) {
+        if !self.unresolved_external_call_labels.is_empty() {
+            return;
+        }
+        loop {
+            let Some(jump_targets) = self.jump_targets() else {
+                return;
+            };
+            let labelled: HashSet<OpcodeLocation> = self.labels.values().copied().collect();
+
+            let propagated_copies = self.propagate_copies(&labelled);
+            let redundant_opcodes = self.find_redundant_opcodes(&jump_targets, &labelled);
+            if !propagated_copies && redundant_opcodes.is_empty() {
+                return;
+            }
+            self.remove_opcodes(&redundant_opcodes);
+        }
+    }
+
+    /// Returns the position jumped to by each jump or call, or None if any jump or call is not
+    /// resolved through a label.
+    fn jump_targets(&self) -> Option<HashMap<OpcodeLocation, OpcodeLocation>> {
+        let mut jump_targets = HashMap::new();
+        for (position, label) in &self.unresolved_jumps {
+            jump_targets.insert(*position, *self.labels.get(label)?);
+        }
+
+        let all_resolved = self.byte_code.iter().enumerate().all(|(position, opcode)| {
+            !matches!(
+                opcode,
+                BrilligOpcode::Jump { .. }
+                    | BrilligOpcode::JumpIf { .. }
+                    | BrilligOpcode::JumpIfNot { .. }
+                    | BrilligOpcode::Call { .. }
+            ) || jump_targets.contains_key(&position)
+        });
+        all_resolved.then_some(jump_targets)
+    }
+
+    /// Replaces the source of each move from a register holding a copy of another register
+    /// with the register copied, so that the move into the copy may later be removed. Returns
+    /// true if any move was changed.
+    fn propagate_copies(&mut self, labelled: &HashSet<OpcodeLocation>) -> bool {
+        // The register each register is a copy of, within the current straight line of code
+        let mut copies: HashMap<usize, RegisterIndex> = HashMap::new();
+        let mut changed = false;
+
+        for (position, opcode) in self.byte_code.iter_mut().enumerate() {
+            if labelled.contains(&position) {
+                copies.clear();
+            }
+            match opcode {
+                BrilligOpcode::Mov { destination, source } => {
+                    if let Some(original) = copies.get(&source.to_usize()) {
+                        *source = *original;
+                        changed = true;
+                    }
+                    let destination = destination.to_usize();
+                    copies.remove(&destination);
+                    copies.retain(|_, original| original.to_usize() != destination);
+                    if destination != source.to_usize() {
+                        copies.insert(destination, *source);
+                    }
+                }
+                _ => match register_written(opcode) {
+                    Some(register) => {
+                        copies.remove(&register);
+                        copies.retain(|_, original| original.to_usize() != register);
+                    }
+                    None if registers_read(opcode).is_none() => copies.clear(),
+                    None => (),
+                },
+            }
+        }
+        changed
+    }
+
+    /// Returns the positions of the opcodes which can be removed without changing the behavior
+    /// of the bytecode.
+    fn find_redundant_opcodes(
+        &self,
+        jump_targets: &HashMap<OpcodeLocation, OpcodeLocation>,
+        labelled: &HashSet<OpcodeLocation>,
+    ) -> HashSet<OpcodeLocation> {
+        let dead_out = self.dead_registers_out(jump_targets);
+
+        // The constant each register holds, within the current straight line of code
+        let mut constants: HashMap<usize, Value> = HashMap::new();
+        let mut redundant_opcodes = HashSet::new();
+
+        for (position, opcode) in self.byte_code.iter().enumerate() {
+            if labelled.contains(&position) {
+                constants.clear();
+            }
+
+            let written = register_written(opcode);
+            let is_dead_write =
+                matches!(opcode, BrilligOpcode::Mov { .. } | BrilligOpcode::Const { .. })
+                    && written.map_or(false, |register| dead_out[position].contains(&register));
+            if is_dead_write {
+                // The register keeps whatever it held before
+                constants.remove(&written.unwrap());
+                redundant_opcodes.insert(position);
+                continue;
+            }
+
+            let is_redundant = match opcode {
+                BrilligOpcode::Mov { destination, source } => destination == source,
+                BrilligOpcode::Const { destination, value } => {
+                    constants.get(&destination.to_usize()) == Some(value)
+                }
+                BrilligOpcode::Jump { .. }
+                | BrilligOpcode::JumpIf { .. }
+                | BrilligOpcode::JumpIfNot { .. } => jump_targets[&position] == position + 1,
+                _ => false,
+            };
+            if is_redundant {
+                redundant_opcodes.insert(position);
+                continue;
+            }
+
+            match opcode {
+                BrilligOpcode::Const { destination, value } => {
+                    constants.insert(destination.to_usize(), *value);
+                }
+                BrilligOpcode::Mov { destination, source } => {
+                    match constants.get(&source.to_usize()).copied() {
+                        Some(value) => constants.insert(destination.to_usize(), value),
+                        None => constants.remove(&destination.to_usize()),
+                    };
+                }
+                _ => match written {
+                    Some(register) => {
+                        constants.remove(&register);
+                    }
+                    None if registers_read(opcode).is_none() => constants.clear(),
+                    None => (),
+                },
+            }
+        }
+        redundant_opcodes
+    }
+
+    /// Computes the registers which are overwritten before being read on every path from each
+    /// opcode, so that writing to them there has no effect.
+    fn dead_registers_out(
+        &self,
+        jump_targets: &HashMap<OpcodeLocation, OpcodeLocation>,
+    ) -> Vec<HashSet<usize>> {
+        // Every register named by the bytecode, including those moves and constants write to
+        let registers: HashSet<usize> = self
+            .byte_code
+            .iter()
+            .flat_map(|opcode| {
+                let read = registers_read(opcode).unwrap_or_default();
+                read.into_iter().chain(register_written(opcode))
+            })
+            .collect();
+
+        let successors = |position: OpcodeLocation| match &self.byte_code[position] {
+            BrilligOpcode::Jump { .. } => vec![jump_targets[&position]],
+            BrilligOpcode::JumpIf { .. } | BrilligOpcode::JumpIfNot { .. } => {
+                vec![jump_targets[&position], position + 1]
+            }
+            BrilligOpcode::Return | BrilligOpcode::Stop | BrilligOpcode::Trap => Vec::new(),
+            _ => vec![position + 1],
+        };
+        let dead_out = |dead_in: &[HashSet<usize>], position| {
+            let mut dead = registers.clone();
+            for successor in successors(position) {
+                match dead_in.get(successor) {
+                    Some(successor_dead) => {
+                        dead.retain(|register| successor_dead.contains(register))
+                    }
+                    // Falling off the end of the bytecode is treated as reading every register
+                    None => dead.clear(),
+                }
+            }
+            dead
+        };
+
+        // Starting from every register being dead, the registers found to be read are removed
+        // until the sets no longer change.
+        let mut dead_in = vec![registers.clone(); self.byte_code.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for position in (0..self.byte_code.len()).rev() {
+                let dead =
+                    dead_registers_in(&self.byte_code[position], dead_out(&dead_in, position));
+                if dead != dead_in[position] {
+                    dead_in[position] = dead;
+                    changed = true;
+                }
+            }
+        }
+        (0..self.byte_code.len()).map(|position| dead_out(&dead_in, position)).collect()
+    }
+
+    /// Removes the opcodes at the given positions, moving each label and jump to the position
+    /// its opcode ends up at. Labels of removed opcodes move to the opcode which followed them.
+    fn remove_opcodes(&mut self, positions: &HashSet<OpcodeLocation>) {
+        // The position of the first opcode kept at or after each position
+        let mut new_positions = Vec::with_capacity(self.byte_code.len() + 1);
+        let mut kept_opcodes = 0;
+        for position in 0..=self.byte_code.len() {
+            new_positions.push(kept_opcodes);
+            if !positions.contains(&position) {
+                kept_opcodes += 1;
+            }
+        }
+
+        for position in self.labels.values_mut() {
+            *position = new_positions[*position];
+        }
+        for jumps in [&mut self.unresolved_jumps, &mut self.unresolved_external_call_labels] {
+            jumps.retain(|(position, _)| !positions.contains(position));
+            for (position, _) in jumps.iter_mut() {
+                *position = new_positions[*position];
+            }
+        }
+
+        let byte_code = std::mem::take(&mut self.byte_code);
+        self.byte_code = byte_code
+            .into_iter()
+            .enumerate()
+            .filter_map(|(position, opcode)| (!positions.contains(&position)).then_some(opcode))
+            .collect();
+    }
+}
+
+/// Returns the registers read by an opcode, or None if it may read any register.
+fn registers_read(opcode: &BrilligOpcode) -> Option<Vec<usize>> {
+    let registers = match opcode {
+        BrilligOpcode::BinaryFieldOp { lhs, rhs, .. }
+        | BrilligOpcode::BinaryIntOp { lhs, rhs, .. } => vec![*lhs, *rhs],
+        BrilligOpcode::JumpIf { condition, .. } | BrilligOpcode::JumpIfNot { condition, .. } => {
+            vec![*condition]
+        }
+        BrilligOpcode::Mov { source, .. } => vec![*source],
+        BrilligOpcode::Load { source_pointer, .. } => vec![*source_pointer],
+        BrilligOpcode::Store { destination_pointer, source } => vec![*destination_pointer, *source],
+        BrilligOpcode::Jump { .. } | BrilligOpcode::Const { .. } | BrilligOpcode::Trap => vec![],
+        // Calls, returns and the opcodes handled by the caller of the VM are assumed to read
+        // every register.
+        _ => return None,
+    };
+    Some(registers.into_iter().map(|register| register.to_usize()).collect())
+}
+
+/// Returns the register written by an opcode, if it only writes to a single known register.
+fn register_written(opcode: &BrilligOpcode) -> Option<usize> {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { destination, .. }
+        | BrilligOpcode::BinaryIntOp { destination, .. }
+        | BrilligOpcode::Mov { destination, .. }
+        | BrilligOpcode::Const { destination, .. }
+        | BrilligOpcode::Load { destination, .. } => Some(destination.to_usize()),
+        _ => None,
+    }
+}
+
+/// Returns the registers which are dead before an opcode, given those which are dead after it.
+fn dead_registers_in(opcode: &BrilligOpcode, mut dead_out: HashSet<usize>) -> HashSet<usize> {
+    let Some(read) = registers_read(opcode) else {
+        return HashSet::new();
+    };
+    // The registers read by an opcode are read before it writes to its destination
+    dead_out.extend(register_written(opcode));
+    for register in read {
+        dead_out.remove(&register);
+    }
+    dead_out
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::{BinaryIntOp, Opcode as BrilligOpcode, RegisterIndex, Value};
+    use acvm::brillig_vm::{Registers, VMStatus, VM};
+
+    use crate::brillig::brillig_ir::{artifact::BrilligArtifact, tests::DummyBlackBoxSolver};
+    use crate::ssa_refactor::ssa_gen::Ssa;
+
+    /// Runs the bytecode on the given inputs and returns the values of its first `returns`
+    /// registers.
+    fn execute(bytecode: Vec<BrilligOpcode>, inputs: &[usize], returns: usize) -> Vec<Value> {
+        let registers = Registers { inner: inputs.iter().copied().map(Value::from).collect() };
+        let mut vm = VM::new(registers, vec![], bytecode, vec![], &DummyBlackBoxSolver);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        vm.get_registers().inner[..returns].to_vec()
+    }
+
+    /// Checks that optimizing the bytecode of the program's main function removes opcodes
+    /// without changing the results of running it on each of the given inputs.
+    fn assert_optimized_equivalently(src: &str, inputs: &[[usize; 2]]) {
+        let ssa: Ssa = src.parse().unwrap();
+        let artifact = ssa.to_brillig().link_entry_point(ssa.main());
+        let mut optimized = artifact.clone();
+        optimized.optimize();
+
+        let (bytecode, optimized_bytecode) = (artifact.finish(), optimized.finish());
+        assert!(optimized_bytecode.len() < bytecode.len());
+        for inputs in inputs {
+            assert_eq!(
+                execute(optimized_bytecode.clone(), inputs, 1),
+                execute(bytecode.clone(), inputs, 1)
+            );
+        }
+    }
+
+    #[test]
+    fn removes_redundant_opcodes_and_moves_labels() {
+        let r2 = RegisterIndex::from(2);
+        let r3 = RegisterIndex::from(3);
+        let add = BrilligOpcode::BinaryIntOp {
+            destination: RegisterIndex::from(0),
+            op: BinaryIntOp::Add,
+            bit_size: 32,
+            lhs: r2,
+            rhs: r2,
+        };
+
+        let mut artifact = BrilligArtifact::default();
+        artifact.push_opcode(BrilligOpcode::Const { destination: r2, value: Value::from(1_usize) });
+        artifact.push_opcode(BrilligOpcode::Const { destination: r2, value: Value::from(1_usize) });
+        artifact.push_opcode(BrilligOpcode::Mov { destination: r3, source: r3 });
+        artifact.add_unresolved_jump(BrilligOpcode::Jump { location: 0 }, "next".into());
+        artifact.add_label_at_position("next".into(), artifact.index_of_next_opcode());
+        artifact.add_unresolved_jump(
+            BrilligOpcode::JumpIfNot { condition: r2, location: 0 },
+            "end".into(),
+        );
+        artifact.push_opcode(add.clone());
+        artifact.add_label_at_position("end".into(), artifact.index_of_next_opcode());
+        artifact.push_opcode(BrilligOpcode::Stop);
+
+        artifact.optimize();
+        assert_eq!(
+            artifact.finish(),
+            vec![
+                BrilligOpcode::Const { destination: r2, value: Value::from(1_usize) },
+                BrilligOpcode::JumpIfNot { condition: r2, location: 3 },
+                add,
+                BrilligOpcode::Stop,
+            ]
+        );
+    }
+
+    #[test]
+    fn optimized_loop_computes_the_same_results() {
+        // let mut sum = 0; for i in 0..x { sum += i * y } sum + y
+        let src = "
+            brillig fn main f0 {
+              b0(v0: u32, v1: u32):
+                jmp b1(u32 0, u32 0)
+              b1(v2: u32, v3: u32):
+                v4 = lt v2, v0
+                jmpif v4 then: b2, else: b3
+              b2():
+                v5 = mul v2, v1
+                v6 = add v3, v5
+                v7 = add v2, u32 1
+                jmp b1(v7, v6)
+              b3():
+                v8 = add v3, v1
+                return v8
+            }
+        ";
+        assert_optimized_equivalently(src, &[[0, 7], [5, 3], [10, 2]]);
+    }
+
+    #[test]
+    fn optimized_calls_compute_the_same_results() {
+        let src = "
+            brillig fn main f0 {
+              b0(v0: u32, v1: u32):
+                v2 = call f1(v0, v1) -> u32
+                v3 = call f1(v2, v0) -> u32
+                return v3
+            }
+            brillig fn square_sum f1 {
+              b0(v0: u32, v1: u32):
+                v2 = mul v0, v0
+                v3 = mul v1, v1
+                v4 = add v2, v3
+                return v4
+            }
+        ";
+        assert_optimized_equivalently(src, &[[0, 0], [1, 2], [3, 4]]);
+    }
+}
@@ -8,8 +8,6 @@ use super::ReservedRegisters;
 /// Each has a stack base pointer from which all stack allocations can be offset.
 pub(crate) struct BrilligRegistersContext {
     /// A free-list of registers that have been deallocated and can be used again.
-    /// TODO(AD): currently, register deallocation is only done with immediate values.
-    /// TODO(AD): See https://github.com/noir-lang/noir/issues/1720
     deallocated_registers: Vec<RegisterIndex>,
     /// A usize indicating the next un-used register.
     next_free_register_index: usize,
@@ -31,11 +29,20 @@ impl BrilligRegistersContext {
             // If it could be allocated, check if it's in the deallocated list and remove it from there
             self.deallocated_registers.retain(|&r| r != register);
         } else {
-            // If it couldn't yet be, expand the register space.
+            // If it couldn't yet be, expand the register space, leaving the registers skipped
+            // over free for reuse.
+            self.deallocated_registers
+                .extend((self.next_free_register_index..index).map(RegisterIndex::from));
             self.next_free_register_index = index + 1;
         }
     }
 
+    /// Returns true if the register is not currently allocated.
+    pub(crate) fn is_free(&self, register: RegisterIndex) -> bool {
+        register.to_usize() >= self.next_free_register_index
+            || self.deallocated_registers.contains(&register)
+    }
+
     /// Lazily iterate over the used registers,
     /// counting to next_free_register_index while excluding deallocated and reserved registers.
     pub(crate) fn used_registers_iter(&self) -> impl Iterator<Item = RegisterIndex> + '_ {
@@ -58,8 +65,6 @@ impl BrilligRegistersContext {
     }
 
     /// Push a register to the deallocation list, ready for reuse.
-    /// TODO(AD): currently, register deallocation is only done with immediate values.
-    /// TODO(AD): See https://github.com/noir-lang/noir/issues/1720
     pub(crate) fn deallocate_register(&mut self, register_index: RegisterIndex) {
         assert!(!self.deallocated_registers.contains(&register_index));
         self.deallocated_registers.push(register_index);
@@ -36,6 +36,23 @@ impl Brillig {
             }
         })
     }
+
+    /// Creates the entry point artifact of `func`, linked with every brillig function it calls.
+    pub(crate) fn link_entry_point(&self, func: &Function) -> BrilligArtifact {
+        let mut entry_point = BrilligArtifact::new_entry_point_artifact(
+            FunctionContext::parameters(func),
+            FunctionContext::return_values(func),
+            FunctionContext::function_id_to_function_label(func.id()),
+        );
+        // Link the entry point with all dependencies
+        while let Some(unresolved_fn_label) = entry_point.first_unresolved_function_call() {
+            let artifact = self
+                .find_by_function_label(unresolved_fn_label.clone())
+                .expect("Cannot find linked fn {unresolved_fn_label}");
+            entry_point.link_with(artifact);
+        }
+        entry_point
+    }
 }
 
 impl std::ops::Index<FunctionId> for Brillig {
@@ -5,6 +5,8 @@ use thiserror::Error;
 pub struct RuntimeError {
     pub location: Option<Location>,
     pub kind: RuntimeErrorKind,
+    /// The locations of the function calls leading to the error, outermost first
+    pub call_stack: Vec<Location>,
 }
 
 impl RuntimeError {
@@ -19,7 +21,11 @@ impl RuntimeError {
     }
 
     pub fn new(kind: RuntimeErrorKind, location: Option<Location>) -> RuntimeError {
-        RuntimeError { location, kind }
+        RuntimeError { location, kind, call_stack: Vec::new() }
+    }
+
+    pub fn with_call_stack(self, call_stack: Vec<Location>) -> RuntimeError {
+        RuntimeError { call_stack, ..self }
     }
 
     // Keep one of the two location which is Some, if possible
@@ -34,14 +40,16 @@ impl RuntimeError {
 
 impl From<RuntimeErrorKind> for RuntimeError {
     fn from(kind: RuntimeErrorKind) -> RuntimeError {
-        RuntimeError { location: None, kind }
+        RuntimeError::new(kind, None)
     }
 }
 
 impl From<RuntimeError> for FileDiagnostic {
-    fn from(err: RuntimeError) -> Self {
-        let file_id = err.location.map(|loc| loc.file).unwrap();
-        FileDiagnostic { file_id, diagnostic: err.into() }
+    fn from(mut err: RuntimeError) -> Self {
+        // Errors without a location are reported against the root file of the program
+        let file_id = err.location.map(|loc| loc.file).unwrap_or_default();
+        let call_stack = std::mem::take(&mut err.call_stack);
+        Diagnostic::from(err).in_file(file_id).with_call_stack(call_stack)
     }
 }
 
@@ -70,6 +78,12 @@ pub enum RuntimeErrorKind {
 
     #[error("Unsupported operation error")]
     UnsupportedOp { op: String, first_type: String, second_type: String },
+
+    #[error("Division by zero")]
+    DivisionByZero,
+
+    #[error("Failed constraint")]
+    FailedConstraint { message: String },
 }
 
 impl RuntimeErrorKind {
@@ -112,6 +126,14 @@ impl From<RuntimeError> for Diagnostic {
                 format!("function {func_name} can only be called in main"),
                 span,
             ),
+            RuntimeErrorKind::DivisionByZero => Diagnostic::simple_error(
+                "attempt to divide by zero".to_owned(),
+                "the divisor is always zero".to_owned(),
+                span,
+            ),
+            RuntimeErrorKind::FailedConstraint { message } => {
+                Diagnostic::simple_error("failed constraint".to_owned(), message.to_string(), span)
+            }
         }
     }
 }
@@ -493,10 +493,8 @@ impl DecisionTree {
             stack.push(ins2);
             Ok(())
         } else {
-            Err(RuntimeError {
-                location,
-                kind: RuntimeErrorKind::UnstructuredError { message: error_msg.to_string() },
-            })
+            let kind = RuntimeErrorKind::UnstructuredError { message: error_msg.to_string() };
+            Err(RuntimeError::new(kind, location))
         }
     }
 
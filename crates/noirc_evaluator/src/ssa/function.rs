@@ -8,6 +8,7 @@ use crate::ssa::{
     ssa_gen::IrGenerator,
     {block, builtin, node, ssa_form},
 };
+use crate::ssa_refactor::ssa_gen::{is_builtin, wrapping_operator};
 use iter_extended::try_vecmap;
 use noirc_frontend::monomorphization::ast::{Call, Definition, FuncId, LocalId, Type};
 use std::collections::{HashMap, VecDeque};
@@ -227,6 +228,20 @@ impl IrGenerator {
 
     //generates an instruction for calling the function
     pub(super) fn call(&mut self, call: &Call) -> Result<Vec<NodeId>, RuntimeError> {
+        // Unconstrained functions are compiled into the circuit like any other function in this
+        // SSA.
+        if is_builtin(&call.func, "unconstrained_is_supported") {
+            return Ok(vec![self.context.zero()]);
+        }
+        // Integer arithmetic always wraps in this SSA, so the wrapping builtins are plain
+        // binary operations.
+        if let Some(operator) = wrapping_operator(&call.func) {
+            let lhs = self.ssa_gen_expression(&call.arguments[0])?.unwrap_id();
+            let rhs = self.ssa_gen_expression(&call.arguments[1])?.unwrap_id();
+            let result = self.ssa_gen_infix_expression(lhs, rhs, operator, call.location)?;
+            return Ok(vec![result]);
+        }
+
         let func = self.ssa_gen_expression(&call.func)?.unwrap_id();
         let arguments = self.ssa_gen_expression_list(&call.arguments);
 
@@ -809,12 +809,10 @@ impl Binary {
 
     fn zero_div_error(&self, location: &Location) -> Result<(), RuntimeError> {
         if self.predicate.is_none() {
-            Err(RuntimeError {
-                location: Some(*location),
-                kind: RuntimeErrorKind::UnstructuredError {
-                    message: "Panic - division by zero".to_string(),
-                },
-            })
+            let kind = RuntimeErrorKind::UnstructuredError {
+                message: "Panic - division by zero".to_string(),
+            };
+            Err(RuntimeError::new(kind, Some(*location)))
         } else {
             Ok(())
         }
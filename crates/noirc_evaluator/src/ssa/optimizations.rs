@@ -329,7 +329,7 @@ fn cse_block_with_anchor(
                     anchor.use_array(*x, ctx.mem[*x].len as usize);
                     let prev_ins = anchor.get_mem_all(*x);
                     let into_runtime_error =
-                        |err: RuntimeErrorKind| RuntimeError { location: *location, kind: err };
+                        |err: RuntimeErrorKind| RuntimeError::new(err, *location);
                     match anchor.find_similar_mem_instruction(ctx, &operator, prev_ins) {
                         Ok(CseAction::Keep) => {
                             anchor
@@ -417,7 +417,7 @@ fn cse_block_with_anchor(
                             new_list.push(*ins_id);
                         }
                         Err(err) => {
-                            return Err(RuntimeError { location: *location, kind: err });
+                            return Err(RuntimeError::new(err, *location));
                         }
                     }
                 }
@@ -214,7 +214,7 @@ impl IrGenerator {
         }
     }
 
-    fn ssa_gen_infix_expression(
+    pub(super) fn ssa_gen_infix_expression(
         &mut self,
         lhs: NodeId,
         rhs: NodeId,
@@ -513,14 +513,12 @@ impl IrGenerator {
                 let lhs = self.ssa_gen_expression(&binary.lhs)?.to_node_ids();
                 let rhs = self.ssa_gen_expression(&binary.rhs)?.to_node_ids();
                 if lhs.len() != 1 || rhs.len() != 1 {
-                    return Err(RuntimeError {
-                        location: Some(binary.location),
-                        kind: RuntimeErrorKind::UnsupportedOp {
-                            op: binary.operator.to_string(),
-                            first_type: "struct/tuple".to_string(),
-                            second_type: "struct/tuple".to_string(),
-                        },
-                    });
+                    let kind = RuntimeErrorKind::UnsupportedOp {
+                        op: binary.operator.to_string(),
+                        first_type: "struct/tuple".to_string(),
+                        second_type: "struct/tuple".to_string(),
+                    };
+                    return Err(RuntimeError::new(kind, Some(binary.location)));
                 }
                 Ok(Value::Node(self.ssa_gen_infix_expression(
                     lhs[0],
@@ -9,50 +9,51 @@
 
 use crate::errors::RuntimeError;
 use acvm::acir::circuit::{Circuit, PublicInputs};
+use iter_extended::vecmap;
 use noirc_abi::Abi;
+use noirc_errors::debug_info::DebugInfo;
 
 use noirc_frontend::monomorphization::ast::Program;
 
 use self::{abi_gen::gen_abi, acir_gen::GeneratedAcir, ir::function::RuntimeType, ssa_gen::Ssa};
 
+pub use self::interpreter::{InterpreterError, SsaInterpreter};
+pub use self::pipeline::{SsaOptions, SsaPass, SsaPipeline};
+
 mod abi_gen;
 mod acir_gen;
+mod interpreter;
 pub mod ir;
 mod opt;
+#[cfg(test)]
+mod parser;
+mod pipeline;
 mod ssa_builder;
 pub mod ssa_gen;
+mod verifier;
 
 /// Optimize the given program by converting it into SSA
 /// form and performing optimizations there. When finished,
 /// convert the final SSA into ACIR and return it.
+///
+/// The passes run over ACIR programs, and which of them the SSA is printed after,
+/// are configured by `options`.
 pub(crate) fn optimize_into_acir(
     program: Program,
     allow_log_ops: bool,
-    print_ssa_passes: bool,
-) -> GeneratedAcir {
+    options: &SsaOptions,
+) -> Result<GeneratedAcir, RuntimeError> {
     let abi_distinctness = program.return_distinctness;
-    let mut ssa = ssa_gen::generate_ssa(program)
-        .print(print_ssa_passes, "Initial SSA:")
+    let mut ssa = ssa_gen::generate_ssa(program, options.check_overflow)
+        .print(options.print_ssa_passes, "Initial SSA:")
+        .verify(options.verify_ssa, "SSA generation")
         .defunctionalize()
-        .print(print_ssa_passes, "After Defunctionalization:");
+        .print(options.print_ssa_passes, "After Defunctionalization:")
+        .verify(options.verify_ssa, "defunctionalization");
 
     let brillig = ssa.to_brillig();
     if let RuntimeType::Acir = ssa.main().runtime() {
-        ssa = ssa
-            .inline_functions()
-            .print(print_ssa_passes, "After Inlining:")
-            .unroll_loops()
-            .print(print_ssa_passes, "After Unrolling:")
-            .simplify_cfg()
-            .print(print_ssa_passes, "After Simplifying:")
-            .flatten_cfg()
-            .print(print_ssa_passes, "After Flattening:")
-            .mem2reg()
-            .print(print_ssa_passes, "After Mem2Reg:")
-            .fold_constants()
-            .print(print_ssa_passes, "After Constant Folding:")
-            .dead_instruction_elimination()
-            .print(print_ssa_passes, "After Dead Instruction Elimination:");
+        ssa = options.pipeline.run(ssa, options);
     }
     ssa.into_acir(brillig, abi_distinctness, allow_log_ops)
 }
@@ -60,15 +61,29 @@ pub(crate) fn optimize_into_acir(
 /// Compiles the Program into ACIR and applies optimizations to the arithmetic gates
 /// This is analogous to `ssa:create_circuit` and this method is called when one wants
 /// to use the new ssa module to process Noir code.
-// TODO: This no longer needs to return a result, but it is kept to match the signature of `create_circuit`
+///
+/// Alongside the circuit and its ABI, this returns the name of the source function
+/// each opcode of the circuit was generated for and the debug info relating the circuit
+/// back to the source.
+///
+/// Fails if the program contains an operation which can never succeed, such as an array access
+/// out of bounds or a division by zero, that is only found once the program is optimized.
 pub fn experimental_create_circuit(
     program: Program,
-    enable_logging: bool,
+    options: &SsaOptions,
     show_output: bool,
-) -> Result<(Circuit, Abi), RuntimeError> {
+) -> Result<(Circuit, Abi, Vec<String>, DebugInfo), RuntimeError> {
     let func_sig = program.main_function_signature.clone();
-    let GeneratedAcir { current_witness_index, opcodes, return_witnesses } =
-        optimize_into_acir(program, show_output, enable_logging);
+    let GeneratedAcir {
+        current_witness_index,
+        opcodes,
+        return_witnesses,
+        opcode_origins,
+        opcode_locations,
+        variables,
+    } = optimize_into_acir(program, show_output, options)?;
+    let opcode_origins = vecmap(opcode_origins, |origin| origin.to_string());
+    let debug_info = DebugInfo { opcode_locations, variables };
 
     let abi = gen_abi(func_sig, return_witnesses.clone());
     let public_abi = abi.clone().public_abi();
@@ -79,7 +94,7 @@ pub fn experimental_create_circuit(
 
     let circuit = Circuit { current_witness_index, opcodes, public_parameters, return_values };
 
-    Ok((circuit, abi))
+    Ok((circuit, abi, opcode_origins, debug_info))
 }
 
 impl Ssa {
@@ -89,4 +104,16 @@ impl Ssa {
         }
         self
     }
+
+    /// Checks that the SSA is well formed after `pass`, if `verify_ssa` is set.
+    ///
+    /// Malformed SSA is a bug in the compiler, so this panics with the offending instruction.
+    fn verify(self, verify_ssa: bool, pass: &str) -> Ssa {
+        if verify_ssa {
+            if let Err(error) = self.check_well_formed() {
+                panic!("ICE: the SSA is malformed after {pass}: {error}");
+            }
+        }
+        self
+    }
 }
@@ -1,6 +1,7 @@
 use super::{errors::AcirGenError, generated_acir::GeneratedAcir};
 use crate::brillig::brillig_gen::brillig_directive;
 use crate::ssa_refactor::acir_gen::AcirValue;
+use crate::ssa_refactor::ir::dfg::CallStack;
 use crate::ssa_refactor::ir::types::Type as SsaType;
 use crate::ssa_refactor::ir::{instruction::Endian, types::NumericType};
 use acvm::acir::{
@@ -10,15 +11,16 @@ use acvm::acir::{
 
 use acvm::{
     acir::{
-        circuit::opcodes::FunctionInput,
+        circuit::opcodes::{BlockId, FunctionInput, MemOp},
         native_types::{Expression, Witness},
         BlackBoxFunc,
     },
     FieldElement,
 };
 use iter_extended::vecmap;
+use noirc_errors::{debug_info::DebugVariable, Location};
 use std::collections::HashMap;
-use std::{borrow::Cow, hash::Hash};
+use std::{borrow::Cow, hash::Hash, rc::Rc};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// High level Type descriptor for Variables.
@@ -104,8 +106,30 @@ pub(crate) struct AcirContext {
     /// then the `acir_ir` will be populated to assert this
     /// addition.
     acir_ir: GeneratedAcir,
+
+    /// The memory blocks holding arrays which are indexed at runtime, indexed by their
+    /// `MemoryBlockId`.
+    memory_blocks: Vec<MemoryBlock>,
+
+    /// The index in `acir_ir.variables` of each variable of an inlined function which is still in
+    /// scope, alongside the call stack it was declared in.
+    variable_scopes: Vec<(usize, CallStack)>,
+}
+
+/// The operations on a memory block so far. These are added to the ACIR as a single RAM or ROM
+/// opcode once the whole program has been converted.
+#[derive(Debug)]
+struct MemoryBlock {
+    len: u32,
+    trace: Vec<MemOp>,
+    /// Whether the block is written to after it is initialized
+    is_written: bool,
 }
 
+/// A reference to a memory block created by `AcirContext::initialize_memory_block`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct MemoryBlockId(usize);
+
 impl AcirContext {
     /// Adds a constant to the context and assigns a Variable to represent it
     pub(crate) fn add_constant(&mut self, constant: FieldElement) -> AcirVar {
@@ -245,7 +269,7 @@ impl AcirContext {
             // max - ((max - a) AND (max -b))
             // Subtracting from max flips the bits, so this is effectively:
             // (NOT a) NAND (NOT b)
-            let max = self.add_constant(FieldElement::from((1_u128 << bit_size) - 1));
+            let max = self.add_constant(max_unsigned_value(bit_size));
             let a = self.sub_var(max, lhs)?;
             let b = self.sub_var(max, rhs)?;
             let inputs = vec![AcirValue::Var(a, typ.clone()), AcirValue::Var(b, typ)];
@@ -355,6 +379,21 @@ impl AcirContext {
         Ok(result)
     }
 
+    /// Adds a new Variable to context whose value is congruent to the multiplication of `lhs` and
+    /// `rhs` modulo 2^`bit_size`, for integers too wide for their product to fit in the field.
+    /// The result must still be truncated to `bit_size` bits.
+    pub(crate) fn wide_mul_var(
+        &mut self,
+        lhs: AcirVar,
+        rhs: AcirVar,
+        bit_size: u32,
+    ) -> Result<AcirVar, AcirGenError> {
+        let lhs_expr = self.vars[&lhs].to_expression();
+        let rhs_expr = self.vars[&rhs].to_expression();
+        let product = self.acir_ir.wide_multiplication(&lhs_expr, &rhs_expr, bit_size)?;
+        Ok(self.add_data(AcirVarData::Expr(product)))
+    }
+
     /// Adds a new Variable to context whose value will
     /// be constrained to be the subtraction of `lhs` and `rhs`
     pub(crate) fn sub_var(&mut self, lhs: AcirVar, rhs: AcirVar) -> Result<AcirVar, AcirGenError> {
@@ -382,7 +421,7 @@ impl AcirContext {
     pub(crate) fn not_var(&mut self, x: AcirVar, typ: AcirType) -> Result<AcirVar, AcirGenError> {
         let bit_size = typ.bit_size();
         // Subtracting from max flips the bits
-        let max = self.add_constant(FieldElement::from((1_u128 << bit_size) - 1));
+        let max = self.add_constant(max_unsigned_value(bit_size));
         self.sub_var(max, x)
     }
 
@@ -541,6 +580,8 @@ impl AcirContext {
     }
 
     /// Returns an `AcirVar` which will be constrained to be lhs mod 2^{rhs}
+    ///
+    /// `lhs` is returned unchanged if `max_bit_size` shows it already fits within `rhs` bits.
     pub(crate) fn truncate_var(
         &mut self,
         lhs: AcirVar,
@@ -548,6 +589,9 @@ impl AcirContext {
         max_bit_size: u32,
     ) -> Result<AcirVar, AcirGenError> {
         let lhs_data = &self.vars[&lhs];
+        if max_bit_size <= rhs {
+            return Ok(lhs);
+        }
         let lhs_expr = lhs_data.to_expression();
 
         let result_expr = self.acir_ir.truncate(&lhs_expr, rhs, max_bit_size)?;
@@ -759,6 +803,96 @@ impl AcirContext {
         }
     }
 
+    /// Attributes every opcode generated since the last call to the source function `origin`,
+    /// generated from the source `location` if known.
+    pub(crate) fn attribute_opcodes_to(&mut self, origin: &Rc<str>, location: Option<Location>) {
+        self.acir_ir.attribute_opcodes_to(origin, location);
+    }
+
+    /// Records the variables `vars` as the value of the source variable `name`, declared in the
+    /// source function `function` inlined through `call_stack`, as of the opcodes generated so
+    /// far.
+    pub(crate) fn record_variable(
+        &mut self,
+        name: &str,
+        function: &str,
+        vars: &[AcirVar],
+        call_stack: &CallStack,
+    ) {
+        let value = vecmap(vars, |var| self.vars[var].to_expression().into_owned());
+        if !call_stack.is_empty() {
+            self.variable_scopes.push((self.acir_ir.variables.len(), call_stack.clone()));
+        }
+        self.acir_ir.variables.push(DebugVariable {
+            name: name.to_owned(),
+            function: function.to_owned(),
+            value,
+            defined_at: self.acir_ir.opcodes.len(),
+            dropped_at: None,
+        });
+    }
+
+    /// Marks the variables of inlined function calls which `call_stack` has returned from as going
+    /// out of scope before the next opcode to be generated.
+    pub(crate) fn close_variable_scopes(&mut self, call_stack: &CallStack) {
+        let opcode_index = self.acir_ir.opcodes.len();
+        let variables = &mut self.acir_ir.variables;
+        self.variable_scopes.retain(|(index, scope)| {
+            let in_scope = scope.len() <= call_stack.len()
+                && scope.iter().zip(call_stack.iter()).all(|(scope, call)| scope == call);
+            if !in_scope {
+                variables[*index].dropped_at = Some(opcode_index);
+            }
+            in_scope
+        });
+    }
+
+    /// Creates a memory block initialized with `values`, which can be read and written at
+    /// indices only known at runtime.
+    pub(crate) fn initialize_memory_block(&mut self, values: &[AcirVar]) -> MemoryBlockId {
+        let trace = values.iter().enumerate().map(|(index, value)| MemOp {
+            operation: Expression::one(),
+            index: Expression::from_field(FieldElement::from(index as u128)),
+            value: self.vars[value].to_expression().into_owned(),
+        });
+        let len = values.len() as u32;
+        self.memory_blocks.push(MemoryBlock { len, trace: trace.collect(), is_written: false });
+        MemoryBlockId(self.memory_blocks.len() - 1)
+    }
+
+    /// Returns a variable holding the value of the memory block `block` at `index`.
+    pub(crate) fn read_from_memory(&mut self, block: MemoryBlockId, index: AcirVar) -> AcirVar {
+        let value = self.acir_ir.next_witness_index();
+        self.memory_blocks[block.0].trace.push(MemOp {
+            operation: Expression::zero(),
+            index: self.vars[&index].to_expression().into_owned(),
+            value: value.into(),
+        });
+        self.add_data(AcirVarData::Witness(value))
+    }
+
+    /// Writes `value` into the memory block `block` at `index`.
+    pub(crate) fn write_to_memory(&mut self, block: MemoryBlockId, index: AcirVar, value: AcirVar) {
+        let block = &mut self.memory_blocks[block.0];
+        block.trace.push(MemOp {
+            operation: Expression::one(),
+            index: self.vars[&index].to_expression().into_owned(),
+            value: self.vars[&value].to_expression().into_owned(),
+        });
+        block.is_written = true;
+    }
+
+    /// Adds the operations on each memory block to the ACIR, as a RAM opcode if the block is
+    /// written to after it is initialized or as a ROM opcode otherwise.
+    ///
+    /// This is done once every operation has been converted, as each block is a single opcode.
+    pub(crate) fn close_memory_blocks(&mut self) {
+        for (id, block) in std::mem::take(&mut self.memory_blocks).into_iter().enumerate() {
+            let id = BlockId(id as u32);
+            self.acir_ir.memory_block(id, block.len, block.trace, !block.is_written);
+        }
+    }
+
     /// Terminates the context and takes the resulting `GeneratedAcir`
     pub(crate) fn finish(self) -> GeneratedAcir {
         self.acir_ir
@@ -928,3 +1062,8 @@ impl AcirVarData {
 /// A Reference to an `AcirVarData`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct AcirVar(usize);
+
+/// Returns 2^`bit_size` - 1, the maximum value of an unsigned integer of `bit_size` bits.
+fn max_unsigned_value(bit_size: u32) -> FieldElement {
+    FieldElement::from(2_i128).pow(&FieldElement::from(bit_size as i128)) - FieldElement::one()
+}
@@ -1,11 +1,15 @@
 use acvm::FieldElement;
 
+use crate::errors::RuntimeErrorKind;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub(crate) enum AcirGenError {
     InvalidRangeConstraint { num_bits: u32 },
     IndexOutOfBounds { index: usize, array_size: usize },
     UnsupportedIntegerSize { num_bits: u32, max_num_bits: u32 },
     BadConstantEquality { lhs: FieldElement, rhs: FieldElement },
+    DivisionByZero,
+    DynamicIndexOfNestedArray,
 }
 
 impl AcirGenError {
@@ -25,6 +29,34 @@ impl AcirGenError {
             AcirGenError::BadConstantEquality { lhs, rhs } => {
                 format!("{lhs} and {rhs} constrained to be equal though they never can be")
             }
+            AcirGenError::DivisionByZero => "Attempted to divide by zero".to_owned(),
+            AcirGenError::DynamicIndexOfNestedArray => {
+                "Indexing arrays of arrays at runtime is not yet supported".to_owned()
+            }
+        }
+    }
+}
+
+impl From<AcirGenError> for RuntimeErrorKind {
+    fn from(error: AcirGenError) -> RuntimeErrorKind {
+        match error {
+            AcirGenError::IndexOutOfBounds { index, array_size } => {
+                RuntimeErrorKind::ArrayOutOfBounds {
+                    index: index as u128,
+                    bound: array_size as u128,
+                }
+            }
+            AcirGenError::BadConstantEquality { .. } => {
+                RuntimeErrorKind::FailedConstraint { message: error.message() }
+            }
+            AcirGenError::DivisionByZero => RuntimeErrorKind::DivisionByZero,
+            AcirGenError::DynamicIndexOfNestedArray => {
+                RuntimeErrorKind::Unimplemented(error.message())
+            }
+            AcirGenError::InvalidRangeConstraint { .. }
+            | AcirGenError::UnsupportedIntegerSize { .. } => {
+                RuntimeErrorKind::UnstructuredError { message: error.message() }
+            }
         }
     }
 }
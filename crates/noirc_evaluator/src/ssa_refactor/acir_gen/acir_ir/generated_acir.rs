@@ -8,7 +8,9 @@ use acvm::acir::{
     circuit::{
         brillig::{Brillig as AcvmBrillig, BrilligInputs, BrilligOutputs},
         directives::{LogInfo, QuotientDirective},
-        opcodes::{BlackBoxFuncCall, FunctionInput, Opcode as AcirOpcode},
+        opcodes::{
+            BlackBoxFuncCall, BlockId, FunctionInput, MemOp, MemoryBlock, Opcode as AcirOpcode,
+        },
     },
     native_types::Witness,
     BlackBoxFunc,
@@ -18,7 +20,9 @@ use acvm::{
     FieldElement,
 };
 use iter_extended::vecmap;
+use noirc_errors::{debug_info::DebugVariable, Location};
 use num_bigint::BigUint;
+use std::{collections::HashMap, rc::Rc};
 
 #[derive(Debug, Default)]
 /// The output of the Acir-gen pass
@@ -36,6 +40,23 @@ pub(crate) struct GeneratedAcir {
     /// Note: This may contain repeated indices, which is necessary for later mapping into the
     /// abi's return type.
     pub(crate) return_witnesses: Vec<Witness>,
+
+    /// The name of the source function each opcode was generated for.
+    ///
+    /// Opcodes are attributed in bulk by `attribute_opcodes_to`, so this may be shorter
+    /// than `opcodes` until the last opcodes have been attributed.
+    pub(crate) opcode_origins: Vec<Rc<str>>,
+
+    /// The source location each opcode was generated from, attributed alongside
+    /// `opcode_origins`.
+    pub(crate) opcode_locations: Vec<Option<Location>>,
+
+    /// The named source variables whose values can be read from the witness map.
+    pub(crate) variables: Vec<DebugVariable>,
+
+    /// The fewest bits each witness has been range constrained to, so that constraints implied
+    /// by an earlier one are not repeated.
+    range_constraints: HashMap<Witness, u32>,
 }
 
 impl GeneratedAcir {
@@ -49,6 +70,13 @@ impl GeneratedAcir {
         self.opcodes.push(opcode);
     }
 
+    /// Attributes every opcode added since the last call to the source function `origin`,
+    /// generated from the source `location` if known.
+    pub(crate) fn attribute_opcodes_to(&mut self, origin: &Rc<str>, location: Option<Location>) {
+        self.opcode_origins.resize(self.opcodes.len(), origin.clone());
+        self.opcode_locations.resize(self.opcodes.len(), location);
+    }
+
     /// Updates the witness index counter and returns
     /// the next witness index.
     pub(crate) fn next_witness_index(&mut self) -> Witness {
@@ -410,6 +438,12 @@ impl GeneratedAcir {
         // Constrain r < rhs
         self.bound_constraint_with_offset(&r_witness.into(), rhs, predicate, max_bit_size)?;
 
+        // The product `rhs * q` of wide integers could wrap around the field modulus, which
+        // would let the prover pick a different quotient satisfying the constraint below.
+        if 2 * max_bit_size >= FieldElement::max_num_bits() {
+            self.constrain_product_fits(rhs, q_witness, max_bit_size)?;
+        }
+
         // a * predicate == (b * q + r) * predicate
         // => predicate * ( a - b * q - r) == 0
         // When the predicate is 0, the equation always passes.
@@ -426,6 +460,84 @@ impl GeneratedAcir {
         Ok((q_witness, r_witness))
     }
 
+    /// Returns an expression equal to `lhs * rhs` modulo 2^{bit_size}, for integers too wide for
+    /// their product to fit within the field.
+    ///
+    /// Each operand is split into a high and a low limb of half its bit size:
+    ///
+    /// lhs * rhs = lhs_high * rhs_high * 2^{2 * limb_bits}
+    ///           + (lhs_high * rhs_low + lhs_low * rhs_high) * 2^{limb_bits}
+    ///           + lhs_low * rhs_low
+    ///
+    /// The first term is a multiple of 2^{bit_size}, so it is dropped, and the remaining terms
+    /// occupy at most `wide_product_bit_size(bit_size)` bits. The result still needs truncating
+    /// to `bit_size`. As an overflow of the first term alone is not visible in the result, checking
+    /// the multiplication for overflow also requires the product of the high limbs to be zero.
+    pub(crate) fn wide_multiplication(
+        &mut self,
+        lhs: &Expression,
+        rhs: &Expression,
+        bit_size: u32,
+    ) -> Result<Expression, AcirGenError> {
+        let limb_bits = (bit_size + 1) / 2;
+        let limb_base = FieldElement::from(2_i128).pow(&FieldElement::from(limb_bits as i128));
+
+        let (lhs_high, lhs_low) = self.split_into_limbs(lhs, limb_bits)?;
+        let (rhs_high, rhs_low) = self.split_into_limbs(rhs, limb_bits)?;
+
+        let mut product = Expression::default();
+        product.push_multiplication_term(limb_base, lhs_high, rhs_low);
+        product.push_multiplication_term(limb_base, lhs_low, rhs_high);
+        product.push_multiplication_term(FieldElement::one(), lhs_low, rhs_low);
+        Ok(product)
+    }
+
+    /// Returns the number of bits the result of `wide_multiplication` may occupy, for operands of
+    /// `bit_size` bits: 3 * limb_bits + 2.
+    pub(crate) fn wide_product_bit_size(bit_size: u32) -> u32 {
+        let limb_bits = (bit_size + 1) / 2;
+        3 * limb_bits + 2
+    }
+
+    /// Constrains the product of `a` and `b`, both integers of `bit_size` bits, to be less
+    /// than 2^{3 * limb_bits + 2}, so that it does not wrap around the field modulus.
+    ///
+    /// This holds when the product of their high limbs is zero, as in `wide_multiplication`.
+    fn constrain_product_fits(
+        &mut self,
+        a: &Expression,
+        b: Witness,
+        bit_size: u32,
+    ) -> Result<(), AcirGenError> {
+        let limb_bits = (bit_size + 1) / 2;
+        let (a_high, _) = self.split_into_limbs(a, limb_bits)?;
+        let (b_high, _) = self.split_into_limbs(&b.into(), limb_bits)?;
+
+        let mut high_product = Expression::default();
+        high_product.push_multiplication_term(FieldElement::one(), a_high, b_high);
+        self.push_opcode(AcirOpcode::Arithmetic(high_product));
+        Ok(())
+    }
+
+    /// Splits `value`, which must fit within 2 * `limb_bits` bits, into its high and low limbs
+    /// of `limb_bits` bits each.
+    fn split_into_limbs(
+        &mut self,
+        value: &Expression,
+        limb_bits: u32,
+    ) -> Result<(Witness, Witness), AcirGenError> {
+        let limb_base = FieldElement::from(2_i128).pow(&FieldElement::from(limb_bits as i128));
+        let (high, low) =
+            self.quotient_directive(value.clone(), limb_base.into(), None, limb_bits, limb_bits)?;
+
+        // Add constraint : value = high * 2^{limb_bits} + low
+        let mut limbs = Expression::default();
+        limbs.push_addition_term(limb_base, high);
+        limbs.push_addition_term(FieldElement::one(), low);
+        self.push_opcode(AcirOpcode::Arithmetic(&limbs - value));
+        Ok((high, low))
+    }
+
     /// Generate constraints that are satisfied iff
     /// lhs < rhs , when offset is 1, or
     /// lhs <= rhs, when offset is 0
@@ -475,12 +587,15 @@ impl GeneratedAcir {
             // we now have lhs+offset <= rhs <=> lhs_offset <= rhs_offset
 
             let bit_size = bit_size_u128(rhs_offset);
-            // r = 2^bit_size - rhs_offset
-            let r = (1_u128 << bit_size) - rhs_offset - 1;
+            // r = 2^bit_size - rhs_offset - 1, computed in the field as bit_size may be 128
+            let two_pow_bit_size =
+                FieldElement::from(2_i128).pow(&FieldElement::from(bit_size as i128));
+            let r = two_pow_bit_size - FieldElement::from(rhs_offset) - FieldElement::one();
             // witness = lhs_offset + r
-            assert!(bits + bit_size < FieldElement::max_num_bits()); //we need to ensure lhs_offset + r does not overflow
+            //we need to ensure lhs_offset + r does not overflow
+            assert!(std::cmp::max(bits, bit_size) + 1 < FieldElement::max_num_bits());
             let mut aor = lhs_offset;
-            aor.q_c += FieldElement::from(r);
+            aor.q_c += r;
             let witness = self.create_witness_for_expression(&aor);
             // lhs_offset<=rhs_offset <=> lhs_offset + r < rhs_offset + r = 2^bit_size <=> witness < 2^bit_size
             self.range_constraint(witness, bit_size)?;
@@ -639,6 +754,8 @@ impl GeneratedAcir {
 
     /// Adds a constraint which ensure thats `witness` is an
     /// integer within the range `[0, 2^{num_bits} - 1]`
+    ///
+    /// No constraint is added if `witness` is already constrained to `num_bits` or fewer.
     pub(crate) fn range_constraint(
         &mut self,
         witness: Witness,
@@ -652,6 +769,11 @@ impl GeneratedAcir {
             });
         };
 
+        if self.is_range_constrained(witness, num_bits) {
+            return Ok(());
+        }
+        self.range_constraints.insert(witness, num_bits);
+
         let constraint = AcirOpcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
             input: FunctionInput { witness, num_bits },
         });
@@ -660,6 +782,11 @@ impl GeneratedAcir {
         Ok(())
     }
 
+    /// Returns true if `witness` has been constrained to fit within `num_bits`.
+    fn is_range_constrained(&self, witness: Witness, num_bits: u32) -> bool {
+        self.range_constraints.get(&witness).map_or(false, |constrained| *constrained <= num_bits)
+    }
+
     /// Adds a directive which injects witnesses with values `q = a / b` and `r = a % b`.
     ///
     /// Suitable range constraints are also applied to `q` and `r`.
@@ -795,6 +922,31 @@ impl GeneratedAcir {
             sort_by: vec![0],
         }));
     }
+
+    /// Adds the operations `trace` on the memory block `id` of length `len`, as a ROM opcode if
+    /// the block is only read after it is initialized and as a RAM opcode otherwise.
+    ///
+    /// The indices and values of memory operations must be witnesses, so any which are not are
+    /// assigned to new witnesses.
+    pub(crate) fn memory_block(
+        &mut self,
+        id: BlockId,
+        len: u32,
+        trace: Vec<MemOp>,
+        is_read_only: bool,
+    ) {
+        let trace = vecmap(trace, |operation| MemOp {
+            operation: operation.operation,
+            index: self.get_or_create_witness(&operation.index).into(),
+            value: self.get_or_create_witness(&operation.value).into(),
+        });
+        let block = MemoryBlock { id, len, trace };
+        self.push_opcode(if is_read_only {
+            AcirOpcode::ROM(block)
+        } else {
+            AcirOpcode::RAM(block)
+        });
+    }
 }
 
 /// This function will return the number of inputs that a blackbox function
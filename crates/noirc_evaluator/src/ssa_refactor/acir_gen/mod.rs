@@ -1,19 +1,17 @@
 //! This file holds the pass to convert from Noir's SSA IR to ACIR.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
-use crate::brillig::{
-    brillig_gen::brillig_fn::FunctionContext as BrilligFunctionContext,
-    brillig_ir::artifact::BrilligArtifact, Brillig,
-};
+use crate::{brillig::Brillig, errors::RuntimeError};
 
 use self::acir_ir::{
-    acir_variable::{AcirContext, AcirType, AcirVar},
+    acir_variable::{AcirContext, AcirType, AcirVar, MemoryBlockId},
     errors::AcirGenError,
 };
 use super::{
     ir::{
-        dfg::DataFlowGraph,
+        basic_block::BasicBlock,
+        dfg::{CallStack, DataFlowGraph},
         function::{Function, RuntimeType},
         instruction::{
             Binary, BinaryOp, Instruction, InstructionId, Intrinsic, TerminatorInstruction,
@@ -21,6 +19,7 @@ use super::{
         map::Id,
         types::{NumericType, Type},
         value::{Value, ValueId},
+        value_range::ValueRanges,
     },
     ssa_gen::Ssa,
 };
@@ -28,7 +27,7 @@ use acvm::{
     acir::{brillig::Opcode, native_types::Expression},
     FieldElement,
 };
-use iter_extended::vecmap;
+use iter_extended::{try_vecmap, vecmap};
 
 pub(crate) use acir_ir::generated_acir::GeneratedAcir;
 use noirc_abi::AbiDistinctness;
@@ -52,6 +51,19 @@ struct Context {
 
     /// Manages and builds the `AcirVar`s to which the converted SSA values refer.
     acir_context: AcirContext,
+
+    /// The memory block holding each array which has been indexed at runtime.
+    ///
+    /// The array written to by an `ArraySet` with a runtime index is updated in place when the
+    /// `ArraySet` is its only use, so the result of the `ArraySet` is only held in the memory
+    /// block until it is used in a way which needs all of its elements.
+    memory_blocks: HashMap<ValueId, MemoryBlockId>,
+
+    /// The number of times each value is used in the block being converted
+    value_uses: HashMap<ValueId, usize>,
+
+    /// The bit sizes the values of the function being converted are known to fit within
+    value_ranges: ValueRanges,
 }
 
 #[derive(Debug, Clone)]
@@ -82,11 +94,12 @@ impl Ssa {
         brillig: Brillig,
         abi_distinctness: AbiDistinctness,
         allow_log_ops: bool,
-    ) -> GeneratedAcir {
+    ) -> Result<GeneratedAcir, RuntimeError> {
+        let main_name: Rc<str> = self.main().name().into();
         let context = Context::new();
-        let mut generated_acir = context.convert_ssa(self, brillig, allow_log_ops);
+        let mut generated_acir = context.convert_ssa(self, brillig, allow_log_ops)?;
 
-        match abi_distinctness {
+        let mut generated_acir = match abi_distinctness {
             AbiDistinctness::Distinct => {
                 // Create a witness for each return witness we have
                 // to guarantee that the return witnesses are distinct
@@ -104,7 +117,9 @@ impl Ssa {
                 generated_acir
             }
             AbiDistinctness::DuplicationAllowed => generated_acir,
-        }
+        };
+        generated_acir.attribute_opcodes_to(&main_name, None);
+        Ok(generated_acir)
     }
 }
 
@@ -113,15 +128,27 @@ impl Context {
         let mut acir_context = AcirContext::default();
         let current_side_effects_enabled_var = acir_context.add_constant(FieldElement::one());
 
-        Context { ssa_values: HashMap::new(), current_side_effects_enabled_var, acir_context }
+        Context {
+            ssa_values: HashMap::new(),
+            current_side_effects_enabled_var,
+            acir_context,
+            memory_blocks: HashMap::new(),
+            value_uses: HashMap::new(),
+            value_ranges: ValueRanges::default(),
+        }
     }
 
     /// Converts SSA into ACIR
-    fn convert_ssa(self, ssa: Ssa, brillig: Brillig, allow_log_ops: bool) -> GeneratedAcir {
+    fn convert_ssa(
+        self,
+        ssa: Ssa,
+        brillig: Brillig,
+        allow_log_ops: bool,
+    ) -> Result<GeneratedAcir, RuntimeError> {
         let main_func = ssa.main();
         match main_func.runtime() {
             RuntimeType::Acir => self.convert_acir_main(main_func, &ssa, brillig, allow_log_ops),
-            RuntimeType::Brillig => self.convert_brillig_main(main_func, brillig),
+            RuntimeType::Brillig => Ok(self.convert_brillig_main(main_func, brillig)),
         }
     }
 
@@ -131,19 +158,41 @@ impl Context {
         ssa: &Ssa,
         brillig: Brillig,
         allow_log_ops: bool,
-    ) -> GeneratedAcir {
+    ) -> Result<GeneratedAcir, RuntimeError> {
         let dfg = &main_func.dfg;
         let entry_block = &dfg[main_func.entry_block()];
+        let main_name: Rc<str> = main_func.name().into();
 
         self.convert_ssa_block_params(entry_block.parameters(), dfg);
+        self.acir_context.attribute_opcodes_to(&main_name, None);
+        self.record_variables(entry_block.parameters(), dfg, &main_name, &CallStack::new());
+        self.value_uses = count_value_uses(entry_block, dfg);
+        self.value_ranges = ValueRanges::with_function(main_func);
 
         for instruction_id in entry_block.instructions() {
-            self.convert_ssa_instruction(*instruction_id, dfg, ssa, &brillig, allow_log_ops);
+            let call_stack = dfg.call_stack(*instruction_id);
+            // Instructions inserted by the optimizations have no location nor call stack, so
+            // they don't tell which inlined call is being executed.
+            if dfg.location(*instruction_id).is_some() {
+                self.acir_context.close_variable_scopes(&call_stack);
+            }
+            self.convert_ssa_instruction(*instruction_id, dfg, ssa, &brillig, allow_log_ops)
+                .map_err(|error| {
+                    let location = dfg.location(*instruction_id);
+                    let call_stack = dfg.call_stack(*instruction_id).into_iter().collect();
+                    RuntimeError::new(error.into(), location).with_call_stack(call_stack)
+                })?;
+            let origin = dfg.origin(*instruction_id).unwrap_or(&main_name);
+            self.acir_context.attribute_opcodes_to(origin, dfg.location(*instruction_id));
+            let results = dfg.instruction_results(*instruction_id);
+            self.record_variables(results, dfg, origin, &call_stack);
         }
 
         self.convert_ssa_return(entry_block.terminator().unwrap(), dfg);
+        self.acir_context.close_memory_blocks();
+        self.acir_context.attribute_opcodes_to(&main_name, None);
 
-        self.acir_context.finish()
+        Ok(self.acir_context.finish())
     }
 
     fn convert_brillig_main(mut self, main_func: &Function, brillig: Brillig) -> GeneratedAcir {
@@ -170,9 +219,31 @@ impl Context {
             self.acir_context.return_var(acir_var);
         }
 
+        self.acir_context.attribute_opcodes_to(&main_func.name().into(), None);
         self.acir_context.finish()
     }
 
+    /// Records the current value of each of `values` which holds a named source variable, so
+    /// that it can be inspected when debugging. The variables were declared in `function`,
+    /// inlined through `call_stack`.
+    fn record_variables(
+        &mut self,
+        values: &[ValueId],
+        dfg: &DataFlowGraph,
+        function: &str,
+        call_stack: &CallStack,
+    ) {
+        for value_id in values {
+            let (Some(name), Some(value)) =
+                (dfg.variable_name(*value_id), self.ssa_values.get(value_id))
+            else {
+                continue;
+            };
+            let vars = vecmap(value.clone().flatten(), |(var, _)| var);
+            self.acir_context.record_variable(name, function, &vars, call_stack);
+        }
+    }
+
     /// Adds and binds `AcirVar`s for each numeric block parameter or block parameter array element.
     fn convert_ssa_block_params(&mut self, params: &[ValueId], dfg: &DataFlowGraph) {
         for param_id in params {
@@ -226,7 +297,10 @@ impl Context {
         acir_var
     }
 
-    /// Converts an SSA instruction into its ACIR representation
+    /// Converts an SSA instruction into its ACIR representation.
+    ///
+    /// This fails if the instruction can never succeed, such as an array access at a constant
+    /// index which is out of bounds, or a constraint which is never satisfied.
     fn convert_ssa_instruction(
         &mut self,
         instruction_id: InstructionId,
@@ -234,26 +308,20 @@ impl Context {
         ssa: &Ssa,
         brillig: &Brillig,
         allow_log_ops: bool,
-    ) {
+    ) -> Result<(), AcirGenError> {
         let instruction = &dfg[instruction_id];
 
         match instruction {
             Instruction::Binary(binary) => {
-                let result_acir_var = self
-                    .convert_ssa_binary(binary, dfg)
-                    .expect("add Result types to all methods so errors bubble up");
+                let result_acir_var = self.convert_ssa_binary(binary, dfg)?;
                 self.define_result_var(dfg, instruction_id, result_acir_var);
             }
             Instruction::Constrain(value_id) => {
                 let constrain_condition = self.convert_numeric_value(*value_id, dfg);
-                self.acir_context
-                    .assert_eq_one(constrain_condition)
-                    .expect("add Result types to all methods so errors bubble up");
+                self.acir_context.assert_eq_one(constrain_condition)?;
             }
             Instruction::Cast(value_id, typ) => {
-                let result_acir_var = self
-                    .convert_ssa_cast(value_id, typ, dfg)
-                    .expect("add Result types to all methods so errors bubble up");
+                let result_acir_var = self.convert_ssa_cast(value_id, typ, dfg)?;
                 self.define_result_var(dfg, instruction_id, result_acir_var);
             }
             Instruction::Call { func, arguments } => {
@@ -311,16 +379,12 @@ impl Context {
                     AcirValue::Var(acir_var, typ) => (acir_var, typ),
                     _ => unreachable!("NOT is only applied to numerics"),
                 };
-                let result_acir_var = self
-                    .acir_context
-                    .not_var(acir_var, typ)
-                    .expect("add Result types to all methods so errors bubble up");
+                let result_acir_var = self.acir_context.not_var(acir_var, typ)?;
                 self.define_result_var(dfg, instruction_id, result_acir_var);
             }
             Instruction::Truncate { value, bit_size, max_bit_size } => {
-                let result_acir_var = self
-                    .convert_ssa_truncate(*value, *bit_size, *max_bit_size, dfg)
-                    .expect("add Result types to all methods so errors bubble up");
+                let result_acir_var =
+                    self.convert_ssa_truncate(*value, *bit_size, *max_bit_size, dfg)?;
                 self.define_result_var(dfg, instruction_id, result_acir_var);
             }
             Instruction::EnableSideEffects { condition } => {
@@ -328,10 +392,10 @@ impl Context {
                 self.current_side_effects_enabled_var = acir_var;
             }
             Instruction::ArrayGet { array, index } => {
-                self.handle_array_operation(instruction_id, *array, *index, None, dfg);
+                self.handle_array_operation(instruction_id, *array, *index, None, dfg)?;
             }
             Instruction::ArraySet { array, index, value } => {
-                self.handle_array_operation(instruction_id, *array, *index, Some(*value), dfg);
+                self.handle_array_operation(instruction_id, *array, *index, Some(*value), dfg)?;
             }
             Instruction::Allocate => {
                 unreachable!("Expected all allocate instructions to be removed before acir_gen")
@@ -343,22 +407,12 @@ impl Context {
                 unreachable!("Expected all load instructions to be removed before acir_gen")
             }
         }
+        Ok(())
     }
 
     fn gen_brillig_for(&self, func: &Function, brillig: &Brillig) -> Vec<Opcode> {
-        // Create the entry point artifact
-        let mut entry_point = BrilligArtifact::new_entry_point_artifact(
-            BrilligFunctionContext::parameters(func),
-            BrilligFunctionContext::return_values(func),
-            BrilligFunctionContext::function_id_to_function_label(func.id()),
-        );
-        // Link the entry point with all dependencies
-        while let Some(unresolved_fn_label) = entry_point.first_unresolved_function_call() {
-            let artifact = &brillig
-                .find_by_function_label(unresolved_fn_label.clone())
-                .expect("Cannot find linked fn {unresolved_fn_label}");
-            entry_point.link_with(artifact);
-        }
+        let mut entry_point = brillig.link_entry_point(func);
+        entry_point.optimize();
         // Generate the final bytecode
         entry_point.finish()
     }
@@ -366,6 +420,8 @@ impl Context {
     /// Handles an ArrayGet or ArraySet instruction.
     /// To set an index of the array (and create a new array in doing so), pass Some(value) for
     /// store_value. To just retrieve an index of the array, pass None for store_value.
+    ///
+    /// Arrays indexed at runtime, and the results of setting them, are held in memory blocks.
     fn handle_array_operation(
         &mut self,
         instruction: InstructionId,
@@ -373,7 +429,20 @@ impl Context {
         index: ValueId,
         store_value: Option<ValueId>,
         dfg: &DataFlowGraph,
-    ) {
+    ) -> Result<(), AcirGenError> {
+        let array = dfg.resolve(array);
+        let index_is_constant = dfg.get_numeric_constant(index).is_some();
+        let is_in_memory =
+            self.memory_blocks.contains_key(&array) && !self.ssa_values.contains_key(&array);
+        if !index_is_constant || is_in_memory {
+            return match store_value {
+                Some(store_value) => {
+                    self.set_in_memory(instruction, array, index, store_value, dfg)
+                }
+                None => self.get_from_memory(instruction, array, index, dfg),
+            };
+        }
+
         let array = self.convert_array_value(array, dfg);
         let index = dfg
             .get_numeric_constant(index)
@@ -384,13 +453,12 @@ impl Context {
         if index >= array.len() {
             // Ignore the error if side effects are disabled.
             if self.acir_context.is_constant_one(&self.current_side_effects_enabled_var) {
-                // TODO: Can we save a source Location for this error?
-                panic!("Index {} is out of bounds for array of length {}", index, array.len());
+                return Err(AcirGenError::IndexOutOfBounds { index, array_size: array.len() });
             }
             let result_type = dfg.type_of_value(dfg.instruction_results(instruction)[0]);
             let value = self.create_default_value(&result_type);
             self.define_result(dfg, instruction, value);
-            return;
+            return Ok(());
         }
 
         let value = match store_value {
@@ -402,6 +470,123 @@ impl Context {
         };
 
         self.define_result(dfg, instruction, value);
+        Ok(())
+    }
+
+    /// Reads the element of `array` at `index` from the memory block holding the array.
+    fn get_from_memory(
+        &mut self,
+        instruction: InstructionId,
+        array: ValueId,
+        index: ValueId,
+        dfg: &DataFlowGraph,
+    ) -> Result<(), AcirGenError> {
+        let block = self.memory_block(array, dfg)?;
+        let index = self.predicated_index(index, dfg)?;
+        let value = self.acir_context.read_from_memory(block, index);
+        self.define_result_var(dfg, instruction, value);
+        Ok(())
+    }
+
+    /// Writes `store_value` into a memory block holding `array` at `index`, giving the result
+    /// of the instruction. The write only takes effect if side effects are enabled.
+    fn set_in_memory(
+        &mut self,
+        instruction: InstructionId,
+        array: ValueId,
+        index: ValueId,
+        store_value: ValueId,
+        dfg: &DataFlowGraph,
+    ) -> Result<(), AcirGenError> {
+        // Update the block holding the array in place if nothing else uses the array,
+        // otherwise copy the array into a new block.
+        let block = match self.memory_blocks.get(&array) {
+            Some(block) if self.value_uses.get(&array) == Some(&1) => {
+                let block = *block;
+                self.memory_blocks.remove(&array);
+                block
+            }
+            _ => {
+                let elements = self.convert_array_value(array, dfg);
+                self.initialize_memory_block(elements)?
+            }
+        };
+
+        let index = self.predicated_index(index, dfg)?;
+        let mut value = self.convert_numeric_value(store_value, dfg);
+        let predicate = self.current_side_effects_enabled_var;
+        if !self.acir_context.is_constant_one(&predicate) {
+            // Write back the current value when side effects are disabled:
+            // current + predicate * (value - current)
+            let current = self.acir_context.read_from_memory(block, index);
+            let difference = self.acir_context.sub_var(value, current)?;
+            let difference = self.acir_context.mul_var(difference, predicate)?;
+            value = self.acir_context.add_var(current, difference)?;
+        }
+        self.acir_context.write_to_memory(block, index, value);
+
+        let result = dfg.resolve(dfg.instruction_results(instruction)[0]);
+        self.memory_blocks.insert(result, block);
+        Ok(())
+    }
+
+    /// Returns the memory block holding `array`, creating one initialized with its elements if
+    /// the array is not held in a block yet.
+    fn memory_block(
+        &mut self,
+        array: ValueId,
+        dfg: &DataFlowGraph,
+    ) -> Result<MemoryBlockId, AcirGenError> {
+        if let Some(block) = self.memory_blocks.get(&array) {
+            return Ok(*block);
+        }
+        let elements = self.convert_array_value(array, dfg);
+        let block = self.initialize_memory_block(elements)?;
+        self.memory_blocks.insert(array, block);
+        Ok(block)
+    }
+
+    /// Creates a memory block initialized with `elements`, which must all be numeric.
+    fn initialize_memory_block(
+        &mut self,
+        elements: im::Vector<AcirValue>,
+    ) -> Result<MemoryBlockId, AcirGenError> {
+        let elements = try_vecmap(elements, |element| match element {
+            AcirValue::Var(var, _) => Ok(var),
+            AcirValue::Array(_) => Err(AcirGenError::DynamicIndexOfNestedArray),
+        })?;
+        Ok(self.acir_context.initialize_memory_block(&elements))
+    }
+
+    /// Converts the index of a memory operation, which is zeroed when side effects are disabled
+    /// so that the operation cannot fail by indexing out of bounds.
+    fn predicated_index(
+        &mut self,
+        index: ValueId,
+        dfg: &DataFlowGraph,
+    ) -> Result<AcirVar, AcirGenError> {
+        let index = self.convert_numeric_value(index, dfg);
+        let predicate = self.current_side_effects_enabled_var;
+        if self.acir_context.is_constant_one(&predicate) {
+            Ok(index)
+        } else {
+            self.acir_context.mul_var(index, predicate)
+        }
+    }
+
+    /// Reads each element of an array of type `typ` from the memory block `block`, for uses of
+    /// the array which need all of its elements.
+    fn read_array_from_memory(&mut self, block: MemoryBlockId, typ: &Type) -> AcirValue {
+        let Type::Array(element_types, length) = typ else {
+            unreachable!("ICE: only arrays are held in memory blocks, found {typ}");
+        };
+        let elements = (0..element_types.len() * length).map(|index| {
+            let element_type = &element_types[index % element_types.len()];
+            let index = self.acir_context.add_constant(FieldElement::from(index as u128));
+            let element = self.acir_context.read_from_memory(block, index);
+            AcirValue::Var(element, element_type.into())
+        });
+        AcirValue::Array(elements.collect())
     }
 
     /// Remember the result of an instruction returning a single value
@@ -475,6 +660,9 @@ impl Context {
             Value::ForeignFunction(_) => unimplemented!(
                 "Oracle calls directly in constrained functions are not yet available."
             ),
+            Value::Instruction { typ, .. } if self.memory_blocks.contains_key(&value_id) => {
+                self.read_array_from_memory(self.memory_blocks[&value_id], typ)
+            }
             Value::Instruction { .. } | Value::Param { .. } => {
                 unreachable!("ICE: Should have been in cache {value_id} {value:?}")
             }
@@ -507,6 +695,15 @@ impl Context {
         binary: &Binary,
         dfg: &DataFlowGraph,
     ) -> Result<AcirVar, AcirGenError> {
+        // Ignore division by zero if side effects are disabled, as the result is unused.
+        let divides_by_zero = matches!(binary.operator, BinaryOp::Div | BinaryOp::Mod)
+            && dfg.get_numeric_constant(binary.rhs).map_or(false, |rhs| rhs.is_zero());
+        if divides_by_zero
+            && self.acir_context.is_constant_one(&self.current_side_effects_enabled_var)
+        {
+            return Err(AcirGenError::DivisionByZero);
+        }
+
         let lhs = self.convert_numeric_value(binary.lhs, dfg);
         let rhs = self.convert_numeric_value(binary.rhs, dfg);
 
@@ -514,10 +711,10 @@ impl Context {
         match &binary_type {
             Type::Numeric(NumericType::Unsigned { bit_size })
             | Type::Numeric(NumericType::Signed { bit_size }) => {
-                // Conservative max bit size that is small enough such that two operands can be
-                // multiplied and still fit within the field modulus. This is necessary for the
-                // truncation technique: result % 2^bit_size to be valid.
-                let max_integer_bit_size = FieldElement::max_num_bits() / 2;
+                // Max bit size small enough that the product of two operands, once split into
+                // limbs by `wide_mul_var`, fits within the field modulus. This is necessary for
+                // the truncation technique: result % 2^bit_size to be valid.
+                let max_integer_bit_size = 128;
                 if *bit_size > max_integer_bit_size {
                     return Err(AcirGenError::UnsupportedIntegerSize {
                         num_bits: *bit_size,
@@ -534,6 +731,9 @@ impl Context {
         match binary.operator {
             BinaryOp::Add => self.acir_context.add_var(lhs, rhs),
             BinaryOp::Sub => self.acir_context.sub_var(lhs, rhs),
+            BinaryOp::Mul if self.product_may_wrap(binary, dfg) => {
+                self.acir_context.wide_mul_var(lhs, rhs, bit_count)
+            }
             BinaryOp::Mul => self.acir_context.mul_var(lhs, rhs),
             BinaryOp::Div => self.acir_context.div_var(lhs, rhs, binary_type),
             // Note: that this produces unnecessary constraints when
@@ -554,6 +754,18 @@ impl Context {
         }
     }
 
+    /// Returns true if `binary` is a multiplication of integers whose product may not fit
+    /// within the field modulus.
+    fn product_may_wrap(&self, binary: &Binary, dfg: &DataFlowGraph) -> bool {
+        let is_integer = matches!(
+            dfg.type_of_value(binary.lhs),
+            Type::Numeric(NumericType::Signed { .. } | NumericType::Unsigned { .. })
+        );
+        let lhs_bit_size = self.value_ranges.max_bit_size(dfg, binary.lhs);
+        let rhs_bit_size = self.value_ranges.max_bit_size(dfg, binary.rhs);
+        is_integer && lhs_bit_size + rhs_bit_size >= FieldElement::max_num_bits()
+    }
+
     /// Operands in a binary operation are checked to have the same type.
     ///
     /// In Noir, binary operands should have the same type due to the language
@@ -629,7 +841,10 @@ impl Context {
                 if incoming_type.is_signed() {
                     todo!("Cast from unsigned to signed")
                 }
-                let max_bit_size = incoming_type.bit_size();
+                let max_bit_size = std::cmp::min(
+                    incoming_type.bit_size(),
+                    self.value_ranges.max_bit_size(dfg, *value_id),
+                );
                 if max_bit_size <= *bit_size {
                     // Incoming variable already fits into target bit size -  this is a no-op
                     return Ok(variable);
@@ -641,6 +856,8 @@ impl Context {
     }
 
     /// Returns an `AcirVar`that is constrained to be result of the truncation.
+    ///
+    /// The truncation is skipped if the value is known to already fit within `bit_size`.
     fn convert_ssa_truncate(
         &mut self,
         value_id: ValueId,
@@ -658,11 +875,29 @@ impl Context {
             // Subtractions must first have the integer modulus added before truncation can be
             // applied. This is done in order to prevent underflow.
             let integer_modulus =
-                self.acir_context.add_constant(FieldElement::from(2_u128.pow(bit_size)));
+                FieldElement::from(2_i128).pow(&FieldElement::from(bit_size as i128));
+            let integer_modulus = self.acir_context.add_constant(integer_modulus);
             var = self.acir_context.add_var(var, integer_modulus)?;
+            self.acir_context.truncate_var(var, bit_size, max_bit_size)
+        } else {
+            let mut known_bit_size = self.value_ranges.max_bit_size(dfg, value_id);
+            if let Instruction::Binary(binary @ Binary { operator: BinaryOp::Mul, .. }) =
+                truncation_target
+            {
+                // Products split into limbs are narrower than the product of their operands,
+                // and the quotient of the truncation must be bounded by their actual width.
+                if self.product_may_wrap(binary, dfg) {
+                    let operand_bit_size =
+                        AcirType::from(self.type_of_binary_operation(binary, dfg)).bit_size();
+                    known_bit_size = std::cmp::min(
+                        known_bit_size,
+                        GeneratedAcir::wide_product_bit_size(operand_bit_size),
+                    );
+                }
+            }
+            let max_bit_size = std::cmp::min(max_bit_size, known_bit_size);
+            self.acir_context.truncate_var(var, bit_size, max_bit_size)
         }
-
-        self.acir_context.truncate_var(var, bit_size, max_bit_size)
     }
 
     /// Returns a vector of `AcirVar`s constrained to be result of the function call.
@@ -830,13 +1065,37 @@ impl Context {
     }
 }
 
+/// Counts the uses of each value by the instructions and terminator of `block`, including its
+/// uses as an element of an array.
+fn count_value_uses(block: &BasicBlock, dfg: &DataFlowGraph) -> HashMap<ValueId, usize> {
+    fn count_use(uses: &mut HashMap<ValueId, usize>, value: ValueId, dfg: &DataFlowGraph) {
+        let value = dfg.resolve(value);
+        *uses.entry(value).or_default() += 1;
+        if let Value::Array { array, .. } = &dfg[value] {
+            for element in array {
+                count_use(uses, *element, dfg);
+            }
+        }
+    }
+
+    let mut uses = HashMap::new();
+    for instruction in block.instructions() {
+        dfg[*instruction].for_each_value(|value| count_use(&mut uses, value, dfg));
+    }
+    block.unwrap_terminator().for_each_value(|value| count_use(&mut uses, value, dfg));
+    uses
+}
+
 #[cfg(test)]
 mod tests {
     use std::rc::Rc;
 
     use acvm::{
         acir::{
-            circuit::Opcode,
+            circuit::{
+                opcodes::{BlackBoxFuncCall, MemoryBlock},
+                Opcode,
+            },
             native_types::{Expression, Witness},
         },
         FieldElement,
@@ -844,9 +1103,11 @@ mod tests {
 
     use crate::{
         brillig::Brillig,
+        errors::RuntimeErrorKind,
         ssa_refactor::{
             ir::{function::RuntimeType, map::Id, types::Type},
             ssa_builder::FunctionBuilder,
+            ssa_gen::Ssa,
         },
     };
 
@@ -871,11 +1132,173 @@ mod tests {
         let ssa = builder.finish();
 
         let context = Context::new();
-        let acir = context.convert_ssa(ssa, Brillig::default(), false);
+        let acir = context.convert_ssa(ssa, Brillig::default(), false).unwrap();
 
         let expected_opcodes =
             vec![Opcode::Arithmetic(&Expression::one() - &Expression::from(Witness(1)))];
         assert_eq!(acir.opcodes, expected_opcodes);
         assert_eq!(acir.return_witnesses, vec![Witness(1)]);
     }
+
+    fn memory_opcodes(src: &str) -> Vec<Opcode> {
+        let ssa: Ssa = src.parse().unwrap();
+        let acir = Context::new().convert_ssa(ssa, Brillig::default(), false).unwrap();
+        let is_memory_opcode = |opcode: &Opcode| matches!(opcode, Opcode::RAM(_) | Opcode::ROM(_));
+        acir.opcodes.into_iter().filter(is_memory_opcode).collect()
+    }
+
+    #[test]
+    fn reads_arrays_indexed_at_runtime_from_rom() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: [Field; 3], v1: u32):
+                v2 = array_get v0, index v1 -> Field
+                v3 = array_get v0, index u32 2 -> Field
+                return v2, v3
+            }
+        ";
+        let opcodes = memory_opcodes(src);
+
+        // The block is initialized with the three elements, then read once
+        let [Opcode::ROM(MemoryBlock { len: 3, trace, .. })] = opcodes.as_slice() else {
+            panic!("expected a single ROM opcode, found {opcodes:?}");
+        };
+        assert_eq!(trace.len(), 4);
+        assert_eq!(trace[3].operation, Expression::zero());
+    }
+
+    #[test]
+    fn writes_arrays_set_at_runtime_to_ram() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: [Field; 2], v1: u32, v2: u1):
+                v3 = array_set v0, index v1, value Field 5
+                enable_side_effects v2
+                v4 = array_set v3, index v1, value Field 6
+                enable_side_effects u1 1
+                v5 = array_get v4, index v1 -> Field
+                return v5
+            }
+        ";
+        let opcodes = memory_opcodes(src);
+
+        // Both writes update the same block in place. The predicated write first reads the
+        // current value at the index.
+        let [Opcode::RAM(MemoryBlock { len: 2, trace, .. })] = opcodes.as_slice() else {
+            panic!("expected a single RAM opcode, found {opcodes:?}");
+        };
+        let operations = trace.iter().map(|operation| operation.operation.clone());
+        let one = Expression::one();
+        let zero = Expression::zero();
+        let expected = vec![one.clone(), one.clone(), one.clone(), zero.clone(), one, zero];
+        assert_eq!(operations.collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn skips_truncations_of_values_known_to_fit() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u8):
+                v1 = cast v0 as u32
+                v2 = cast v1 as u8
+                v3 = mul v1, u32 2
+                v4 = cast v3 as u8
+                v5 = cast v0 as u16
+                v6 = truncate v5 to 8 bits, max_bit_size: 16
+                return v2, v4, v6
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        let acir = Context::new().convert_ssa(ssa, Brillig::default(), false).unwrap();
+        let range_constraints = acir.opcodes.iter().filter_map(|opcode| match opcode {
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => Some(input.num_bits),
+            _ => None,
+        });
+
+        // Only the input and the 10 bit product are constrained, the latter with a 2 bit
+        // quotient rather than a 24 bit one.
+        assert_eq!(range_constraints.collect::<Vec<_>>(), vec![8, 2, 8]);
+    }
+
+    #[test]
+    fn splits_wide_integer_multiplications_into_limbs() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u128, v1: u128):
+                v2 = mul v0, v1
+                v3 = truncate v2 to 128 bits, max_bit_size: 256
+                return v3
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        let acir = Context::new().convert_ssa(ssa, Brillig::default(), false).unwrap();
+        let range_constraints = acir.opcodes.iter().filter_map(|opcode| match opcode {
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => Some(input.num_bits),
+            _ => None,
+        });
+
+        // Each input is split into two 64 bit limbs, whose partial products add up to at most
+        // 194 bits, which are then truncated to 128 bits.
+        let expected = vec![128, 128, 64, 64, 64, 64, 66, 128];
+        assert_eq!(range_constraints.collect::<Vec<_>>(), expected);
+    }
+
+    fn conversion_error(src: &str) -> RuntimeErrorKind {
+        let ssa: Ssa = src.parse().unwrap();
+        Context::new().convert_ssa(ssa, Brillig::default(), false).unwrap_err().kind
+    }
+
+    #[test]
+    fn reports_constant_indices_out_of_bounds() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: [Field; 2], v1: u1):
+                enable_side_effects v1
+                v2 = array_get v0, index u32 2 -> Field
+                enable_side_effects u1 1
+                v3 = array_get v0, index u32 3 -> Field
+                return v2, v3
+            }
+        ";
+        // Only the access made while side effects are enabled is an error.
+        let error = conversion_error(src);
+        assert!(matches!(error, RuntimeErrorKind::ArrayOutOfBounds { index: 3, bound: 2 }));
+    }
+
+    #[test]
+    fn reports_division_by_zero() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u32):
+                v1 = div v0, u32 0
+                return v1
+            }
+        ";
+        assert!(matches!(conversion_error(src), RuntimeErrorKind::DivisionByZero));
+    }
+
+    #[test]
+    fn reports_constraints_which_are_never_satisfied() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field):
+                constrain u1 0
+                return v0
+            }
+        ";
+        assert!(matches!(conversion_error(src), RuntimeErrorKind::FailedConstraint { .. }));
+    }
+
+    #[test]
+    fn reports_dynamic_indices_of_nested_arrays() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: [[Field; 2]; 2], v1: u32):
+                v2 = array_get v0, index v1 -> [Field; 2]
+                v3 = array_get v2, index u32 0 -> Field
+                return v3
+            }
+        ";
+        assert!(matches!(conversion_error(src), RuntimeErrorKind::Unimplemented(..)));
+    }
 }
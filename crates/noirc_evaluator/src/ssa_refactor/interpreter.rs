@@ -0,0 +1,872 @@
+//! The interpreter executes the SSA of a program with concrete inputs, without generating ACIR.
+//!
+//! Since it can run the SSA at any point of the pass pipeline, comparing its results before and
+//! after a pass is a quick way to check that the pass preserves the semantics of the program.
+//! Values are treated the same way ACIR generation treats them: arithmetic is performed in the
+//! field and integers are only reduced by explicit `truncate` and `cast` instructions.
+use std::collections::{BTreeMap, HashMap};
+
+use acvm::acir::native_types::{Witness, WitnessMap};
+use acvm::FieldElement;
+use iter_extended::{try_vecmap, vecmap};
+use noirc_abi::{errors::AbiError, Abi};
+use noirc_frontend::monomorphization::ast::Program;
+use num_bigint::BigUint;
+use thiserror::Error;
+
+use super::{
+    abi_gen::gen_abi,
+    ir::{
+        basic_block::BasicBlockId,
+        function::{Function, FunctionId, RuntimeType},
+        instruction::{
+            radix_decomposition, Binary, BinaryOp, Endian, Instruction, InstructionId, Intrinsic,
+            TerminatorInstruction,
+        },
+        types::{NumericType, Type},
+        value::{self, ValueId},
+    },
+    ssa_gen::{generate_ssa, Ssa},
+};
+
+/// An error encountered while interpreting a program.
+#[derive(Debug, Error)]
+pub enum InterpreterError {
+    #[error("Failed constraint in `{function}`")]
+    FailedConstraint { function: String },
+    #[error("Index {index} is out of bounds for array of length {length}")]
+    IndexOutOfBounds { index: usize, length: usize },
+    #[error("Attempted to divide by zero")]
+    DivisionByZero,
+    #[error("{value} does not fit in {limb_count} limbs of radix {radix}")]
+    RadixDecompositionOverflow { value: FieldElement, radix: u32, limb_count: u32 },
+    #[error("Expected {expected} input values for `main` but {found} were given")]
+    WrongInputCount { expected: usize, found: usize },
+    #[error("The interpreter does not support {0}")]
+    Unsupported(String),
+    #[error(transparent)]
+    AbiError(#[from] AbiError),
+    #[error("ICE: {0}")]
+    Internal(String),
+}
+
+/// Executes a program by interpreting its SSA instead of compiling it into ACIR, which is faster
+/// and doesn't depend on the later passes being correct.
+pub struct SsaInterpreter {
+    ssa: Ssa,
+    abi: Abi,
+}
+
+impl SsaInterpreter {
+    pub fn new(program: Program) -> SsaInterpreter {
+        let (parameters, return_type) = program.main_function_signature.clone();
+
+        // There is no circuit to assign witnesses to the return values, so they are assigned
+        // those following the parameters.
+        let parameter_count: u32 = parameters.iter().map(|param| param.typ.field_count()).sum();
+        let return_count = return_type.as_ref().map_or(0, |typ| typ.field_count());
+        let return_witnesses =
+            vecmap(parameter_count + 1..=parameter_count + return_count, Witness);
+
+        let abi = gen_abi((parameters, return_type), return_witnesses);
+        SsaInterpreter { ssa: generate_ssa(program, false), abi }
+    }
+
+    /// The ABI of the program's main function, in which the witnesses of the return values
+    /// follow those of the parameters.
+    pub fn abi(&self) -> &Abi {
+        &self.abi
+    }
+
+    /// Executes the program with the parameters in `initial_witness`, returning the witness map
+    /// extended with the return values.
+    pub fn execute(&self, initial_witness: &WitnessMap) -> Result<WitnessMap, InterpreterError> {
+        let witnesses: BTreeMap<_, _> = initial_witness.clone().into_iter().collect();
+        let inputs = vecmap(&witnesses, |(_, value)| *value);
+
+        let return_values = self.ssa.interpret(&inputs)?;
+        if return_values.len() != self.abi.return_witnesses.len() {
+            return Err(InterpreterError::Internal(format!(
+                "main returned {} values but its ABI expects {}",
+                return_values.len(),
+                self.abi.return_witnesses.len()
+            )));
+        }
+
+        let mut witnesses = witnesses;
+        witnesses.extend(self.abi.return_witnesses.iter().copied().zip(return_values));
+        Ok(WitnessMap::from(witnesses))
+    }
+}
+
+/// A value computed by the interpreter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    Numeric(FieldElement),
+    /// The flattened elements of an array or slice.
+    Array(im::Vector<Value>),
+    /// An index into the interpreter's memory, created by an `allocate` instruction.
+    Reference(usize),
+    Function(FunctionId),
+    Intrinsic(Intrinsic),
+    ForeignFunction(String),
+}
+
+impl Value {
+    /// Returns the default value of `typ`, which is used for the results of instructions whose
+    /// side effects are disabled.
+    fn default_of(typ: &Type) -> Value {
+        match typ {
+            Type::Array(element_types, length) => {
+                let elements = element_types.iter().cycle().take(element_types.len() * length);
+                Value::Array(elements.map(Value::default_of).collect())
+            }
+            Type::Slice(_) => Value::Array(im::Vector::new()),
+            Type::Numeric(_) | Type::Reference | Type::Function => {
+                Value::Numeric(FieldElement::zero())
+            }
+        }
+    }
+
+    /// Appends the field elements making up this value to `fields`.
+    fn flatten_into(&self, fields: &mut Vec<FieldElement>) -> Result<(), InterpreterError> {
+        match self {
+            Value::Numeric(value) => fields.push(*value),
+            Value::Array(elements) => {
+                for element in elements {
+                    element.flatten_into(fields)?;
+                }
+            }
+            other => {
+                return Err(InterpreterError::Unsupported(format!("returning {other:?} from main")))
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Ssa {
+    /// Executes the `main` function of the program with the given inputs, returning the values it
+    /// returns.
+    ///
+    /// Inputs and return values are flattened into field elements in the same order as the
+    /// witnesses of the circuit's ABI.
+    pub(crate) fn interpret(
+        &self,
+        inputs: &[FieldElement],
+    ) -> Result<Vec<FieldElement>, InterpreterError> {
+        let main = self.main();
+        let parameter_types = vecmap(main.parameters(), |param| main.dfg.type_of_value(*param));
+
+        let expected = parameter_types.iter().map(field_count).sum::<Result<usize, _>>()?;
+        if inputs.len() != expected {
+            return Err(InterpreterError::WrongInputCount { expected, found: inputs.len() });
+        }
+        let mut inputs = inputs.iter().copied();
+        let arguments = vecmap(&parameter_types, |typ| read_input(typ, &mut inputs));
+
+        let mut interpreter = Interpreter { ssa: self, memory: Vec::new() };
+        let return_values = interpreter.call(main, arguments)?;
+
+        let mut fields = Vec::new();
+        for value in &return_values {
+            value.flatten_into(&mut fields)?;
+        }
+        Ok(fields)
+    }
+}
+
+/// Returns the number of field elements an input of type `typ` is flattened into.
+fn field_count(typ: &Type) -> Result<usize, InterpreterError> {
+    match typ {
+        Type::Numeric(_) => Ok(1),
+        Type::Array(element_types, length) => {
+            let element_count = element_types.iter().map(field_count).sum::<Result<usize, _>>()?;
+            Ok(element_count * length)
+        }
+        other => Err(InterpreterError::Unsupported(format!("inputs of type {other}"))),
+    }
+}
+
+/// Reads a value of type `typ` from the flattened `inputs` to `main`, which are expected to
+/// contain enough field elements for it.
+fn read_input(typ: &Type, inputs: &mut impl Iterator<Item = FieldElement>) -> Value {
+    match typ {
+        Type::Array(element_types, length) => {
+            let element_types = element_types.iter().cycle().take(element_types.len() * length);
+            Value::Array(element_types.map(|typ| read_input(typ, inputs)).collect())
+        }
+        _ => Value::Numeric(inputs.next().expect("inputs should have been counted")),
+    }
+}
+
+struct Interpreter<'ssa> {
+    ssa: &'ssa Ssa,
+    /// The values stored at each allocated reference, which are None until they are first stored.
+    memory: Vec<Option<Value>>,
+}
+
+/// The state of a single function call.
+struct Frame<'f> {
+    function: &'f Function,
+    values: HashMap<ValueId, Value>,
+    /// False while the condition of the last `enable_side_effects` instruction is false.
+    side_effects_enabled: bool,
+}
+
+impl<'f> Frame<'f> {
+    fn lookup(&self, id: ValueId) -> Result<Value, InterpreterError> {
+        let dfg = &self.function.dfg;
+        let id = dfg.resolve(id);
+        match &dfg[id] {
+            value::Value::Instruction { .. } | value::Value::Param { .. } => {
+                self.values.get(&id).cloned().ok_or_else(|| {
+                    InterpreterError::Internal(format!(
+                        "{id} is used before it is defined in `{}`",
+                        self.function.name()
+                    ))
+                })
+            }
+            value::Value::NumericConstant { constant, .. } => Ok(Value::Numeric(*constant)),
+            value::Value::Array { array, .. } => {
+                let elements = try_vecmap(array, |element| self.lookup(*element))?;
+                Ok(Value::Array(elements.into()))
+            }
+            value::Value::Function(function) => Ok(Value::Function(*function)),
+            value::Value::Intrinsic(intrinsic) => Ok(Value::Intrinsic(*intrinsic)),
+            value::Value::ForeignFunction(name) => Ok(Value::ForeignFunction(name.clone())),
+        }
+    }
+
+    fn lookup_numeric(&self, id: ValueId) -> Result<FieldElement, InterpreterError> {
+        match self.lookup(id)? {
+            Value::Numeric(value) => Ok(value),
+            other => Err(InterpreterError::Internal(format!("expected a number, found {other:?}"))),
+        }
+    }
+
+    fn lookup_array(&self, id: ValueId) -> Result<im::Vector<Value>, InterpreterError> {
+        match self.lookup(id)? {
+            Value::Array(elements) => Ok(elements),
+            other => Err(InterpreterError::Internal(format!("expected an array, found {other:?}"))),
+        }
+    }
+
+    fn lookup_index(&self, id: ValueId) -> Result<usize, InterpreterError> {
+        let index = self.lookup_numeric(id)?;
+        index
+            .try_to_u64()
+            .map(|index| index as usize)
+            .ok_or_else(|| InterpreterError::Internal(format!("index {index} does not fit in u64")))
+    }
+
+    fn numeric_type(&self, id: ValueId) -> Result<NumericType, InterpreterError> {
+        match self.function.dfg.type_of_value(id) {
+            Type::Numeric(numeric_type) => Ok(numeric_type),
+            other => Err(InterpreterError::Internal(format!("expected a number, found {other}"))),
+        }
+    }
+}
+
+impl<'ssa> Interpreter<'ssa> {
+    fn call(
+        &mut self,
+        function: &'ssa Function,
+        mut arguments: Vec<Value>,
+    ) -> Result<Vec<Value>, InterpreterError> {
+        let mut frame = Frame { function, values: HashMap::new(), side_effects_enabled: true };
+        let dfg = &function.dfg;
+        let mut block = function.entry_block();
+
+        loop {
+            self.enter_block(&mut frame, block, arguments)?;
+            for instruction in dfg[block].instructions() {
+                self.interpret_instruction(&mut frame, *instruction)?;
+            }
+
+            match dfg[block].terminator() {
+                Some(TerminatorInstruction::Jmp { destination, arguments: jump_arguments }) => {
+                    arguments = try_vecmap(jump_arguments, |argument| frame.lookup(*argument))?;
+                    block = *destination;
+                }
+                Some(TerminatorInstruction::JmpIf {
+                    condition,
+                    then_destination,
+                    else_destination,
+                }) => {
+                    let condition = frame.lookup_numeric(*condition)?;
+                    arguments = Vec::new();
+                    block = if condition.is_zero() { *else_destination } else { *then_destination };
+                }
+                Some(TerminatorInstruction::Return { return_values }) => {
+                    return try_vecmap(return_values, |value| frame.lookup(*value));
+                }
+                None => {
+                    return Err(InterpreterError::Internal(format!(
+                        "{block} in `{}` has no terminator",
+                        function.name()
+                    )))
+                }
+            }
+        }
+    }
+
+    fn enter_block(
+        &self,
+        frame: &mut Frame,
+        block: BasicBlockId,
+        arguments: Vec<Value>,
+    ) -> Result<(), InterpreterError> {
+        let parameters = frame.function.dfg.block_parameters(block);
+        if parameters.len() != arguments.len() {
+            return Err(InterpreterError::Internal(format!(
+                "{block} in `{}` expects {} arguments but was given {}",
+                frame.function.name(),
+                parameters.len(),
+                arguments.len()
+            )));
+        }
+        frame.values.extend(parameters.iter().copied().zip(arguments));
+        Ok(())
+    }
+
+    fn interpret_instruction(
+        &mut self,
+        frame: &mut Frame,
+        id: InstructionId,
+    ) -> Result<(), InterpreterError> {
+        let function = frame.function;
+        let dfg = &function.dfg;
+        let results = dfg.instruction_results(id);
+        let result_type = |index: usize| dfg.type_of_value(results[index]);
+
+        let outputs = match &dfg[id] {
+            Instruction::Binary(binary) => vec![self.interpret_binary(frame, binary)?],
+            Instruction::Cast(value, typ) => {
+                let value = frame.lookup_numeric(*value)?;
+                let value = match typ {
+                    Type::Numeric(NumericType::NativeField) => value,
+                    Type::Numeric(
+                        NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size },
+                    ) => truncate(value, *bit_size),
+                    other => {
+                        return Err(InterpreterError::Internal(format!("cannot cast to {other}")))
+                    }
+                };
+                vec![Value::Numeric(value)]
+            }
+            Instruction::Not(value) => {
+                let bit_size = match frame.numeric_type(*value)? {
+                    NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size } => {
+                        bit_size
+                    }
+                    NumericType::NativeField => {
+                        return Err(InterpreterError::Internal("cannot negate a field".to_owned()))
+                    }
+                };
+                let max = truncate(-FieldElement::one(), bit_size);
+                vec![Value::Numeric(max - frame.lookup_numeric(*value)?)]
+            }
+            Instruction::Truncate { value, bit_size, .. } => {
+                vec![Value::Numeric(truncate(frame.lookup_numeric(*value)?, *bit_size))]
+            }
+            Instruction::Constrain(value) => {
+                if !frame.lookup_numeric(*value)?.is_one() {
+                    let function = frame.function.name().to_owned();
+                    return Err(InterpreterError::FailedConstraint { function });
+                }
+                Vec::new()
+            }
+            Instruction::Call { func, arguments } => {
+                let arguments = try_vecmap(arguments, |argument| frame.lookup(*argument))?;
+                match frame.lookup(*func)? {
+                    Value::Function(id) => {
+                        let ssa = self.ssa;
+                        let function = &ssa.functions[&id];
+                        // Brillig calls made while side effects are disabled are not executed,
+                        // and return zeroes instead.
+                        if function.runtime() == RuntimeType::Brillig && !frame.side_effects_enabled
+                        {
+                            vecmap(0..results.len(), |index| Value::default_of(&result_type(index)))
+                        } else {
+                            self.call(function, arguments)?
+                        }
+                    }
+                    Value::Intrinsic(intrinsic) => {
+                        let result_types = vecmap(0..results.len(), result_type);
+                        interpret_intrinsic(intrinsic, arguments, &result_types)?
+                    }
+                    Value::ForeignFunction(name) => {
+                        return Err(InterpreterError::Unsupported(format!(
+                            "calls to oracle {name}"
+                        )))
+                    }
+                    other => {
+                        return Err(InterpreterError::Internal(format!("cannot call {other:?}")))
+                    }
+                }
+            }
+            Instruction::Allocate => {
+                self.memory.push(None);
+                vec![Value::Reference(self.memory.len() - 1)]
+            }
+            Instruction::Load { address } => {
+                let address = self.lookup_reference(frame, *address)?;
+                let value = self.memory[address].clone().ok_or_else(|| {
+                    InterpreterError::Internal(format!("load from uninitialized memory in {id}"))
+                })?;
+                vec![value]
+            }
+            Instruction::Store { address, value } => {
+                let address = self.lookup_reference(frame, *address)?;
+                self.memory[address] = Some(frame.lookup(*value)?);
+                Vec::new()
+            }
+            Instruction::EnableSideEffects { condition } => {
+                frame.side_effects_enabled = !frame.lookup_numeric(*condition)?.is_zero();
+                Vec::new()
+            }
+            Instruction::ArrayGet { array, index } => {
+                let array = frame.lookup_array(*array)?;
+                let index = frame.lookup_index(*index)?;
+                match array.get(index) {
+                    Some(element) => vec![element.clone()],
+                    None => vec![self.out_of_bounds(frame, index, array.len(), &result_type(0))?],
+                }
+            }
+            Instruction::ArraySet { array, index, value } => {
+                let array = frame.lookup_array(*array)?;
+                let index = frame.lookup_index(*index)?;
+                if index < array.len() {
+                    vec![Value::Array(array.update(index, frame.lookup(*value)?))]
+                } else {
+                    vec![self.out_of_bounds(frame, index, array.len(), &result_type(0))?]
+                }
+            }
+        };
+
+        if outputs.len() != results.len() {
+            return Err(InterpreterError::Internal(format!(
+                "{id} produced {} values but has {} results",
+                outputs.len(),
+                results.len()
+            )));
+        }
+        frame.values.extend(results.iter().copied().zip(outputs));
+        Ok(())
+    }
+
+    fn lookup_reference(&self, frame: &Frame, id: ValueId) -> Result<usize, InterpreterError> {
+        match frame.lookup(id)? {
+            Value::Reference(address) => Ok(address),
+            other => {
+                Err(InterpreterError::Internal(format!("expected a reference, found {other:?}")))
+            }
+        }
+    }
+
+    /// Out of bounds array accesses are an error, unless side effects are disabled in which case
+    /// they produce a default value like they do in ACIR.
+    fn out_of_bounds(
+        &self,
+        frame: &Frame,
+        index: usize,
+        length: usize,
+        result_type: &Type,
+    ) -> Result<Value, InterpreterError> {
+        if frame.side_effects_enabled {
+            Err(InterpreterError::IndexOutOfBounds { index, length })
+        } else {
+            Ok(Value::default_of(result_type))
+        }
+    }
+
+    fn interpret_binary(&self, frame: &Frame, binary: &Binary) -> Result<Value, InterpreterError> {
+        let lhs = frame.lookup_numeric(binary.lhs)?;
+        let rhs = frame.lookup_numeric(binary.rhs)?;
+        let typ = frame.numeric_type(binary.lhs)?;
+
+        let value = match binary.operator {
+            BinaryOp::Add => lhs + rhs,
+            BinaryOp::Sub => lhs - rhs,
+            BinaryOp::Mul => match typ {
+                NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size }
+                    if lhs.num_bits() + rhs.num_bits() >= FieldElement::max_num_bits() =>
+                {
+                    wrapping_mul(lhs, rhs, bit_size)
+                }
+                _ => lhs * rhs,
+            },
+            BinaryOp::Eq => FieldElement::from(lhs == rhs),
+            BinaryOp::Div | BinaryOp::Mod if rhs.is_zero() => {
+                return Err(InterpreterError::DivisionByZero)
+            }
+            BinaryOp::Div if typ == NumericType::NativeField => lhs / rhs,
+            BinaryOp::Lt if typ == NumericType::NativeField => FieldElement::from(lhs < rhs),
+            BinaryOp::Shl => {
+                let shift = to_u128(rhs)?;
+                if shift >= FieldElement::max_num_bits() as u128 {
+                    return Err(InterpreterError::Unsupported(format!("shifting left by {shift}")));
+                }
+                let power = BigUint::from(2u128).pow(shift as u32);
+                lhs * FieldElement::from_be_bytes_reduce(&power.to_bytes_be())
+            }
+            _ if matches!(typ, NumericType::Signed { bit_size } if bit_size > 128) => {
+                return Err(InterpreterError::Unsupported(format!("{typ} integers")));
+            }
+            operator => {
+                let (lhs, rhs) = (to_u128(lhs)?, to_u128(rhs)?);
+                let value = match (operator, typ) {
+                    (BinaryOp::Div, NumericType::Signed { bit_size }) => {
+                        from_signed(to_signed(lhs, bit_size) / to_signed(rhs, bit_size), bit_size)
+                    }
+                    (BinaryOp::Mod, NumericType::Signed { bit_size }) => {
+                        from_signed(to_signed(lhs, bit_size) % to_signed(rhs, bit_size), bit_size)
+                    }
+                    (BinaryOp::Lt, NumericType::Signed { bit_size }) => {
+                        (to_signed(lhs, bit_size) < to_signed(rhs, bit_size)) as u128
+                    }
+                    (BinaryOp::Div, _) => lhs / rhs,
+                    (BinaryOp::Mod, _) => lhs % rhs,
+                    (BinaryOp::Lt, _) => (lhs < rhs) as u128,
+                    (BinaryOp::And, _) => lhs & rhs,
+                    (BinaryOp::Or, _) => lhs | rhs,
+                    (BinaryOp::Xor, _) => lhs ^ rhs,
+                    (BinaryOp::Shr, _) => {
+                        lhs.checked_shr(rhs.try_into().unwrap_or(u32::MAX)).unwrap_or(0)
+                    }
+                    (operator, _) => unreachable!("{operator} is handled above"),
+                };
+                FieldElement::from(value)
+            }
+        };
+        Ok(Value::Numeric(value))
+    }
+}
+
+fn interpret_intrinsic(
+    intrinsic: Intrinsic,
+    mut arguments: Vec<Value>,
+    result_types: &[Type],
+) -> Result<Vec<Value>, InterpreterError> {
+    let numeric_argument = |arguments: &[Value], index: usize| match arguments.get(index) {
+        Some(Value::Numeric(value)) => to_u128(*value),
+        other => Err(InterpreterError::Internal(format!("expected a number, found {other:?}"))),
+    };
+    let mut slice = match arguments.first() {
+        Some(Value::Array(elements)) => elements.clone(),
+        _ => im::Vector::new(),
+    };
+    // The number of flattened values making up each element of a slice.
+    let element_size = result_types.len().saturating_sub(1);
+
+    let results = match intrinsic {
+        Intrinsic::ArrayLen => {
+            vec![Value::Numeric(FieldElement::from(slice.len() as u128))]
+        }
+        Intrinsic::SlicePushBack => {
+            slice.extend(arguments.drain(1..));
+            vec![Value::Array(slice)]
+        }
+        Intrinsic::SlicePushFront => {
+            for element in arguments.drain(1..).rev() {
+                slice.push_front(element);
+            }
+            vec![Value::Array(slice)]
+        }
+        Intrinsic::SlicePopBack => {
+            let elements = slice.split_off(slice.len().saturating_sub(element_size));
+            std::iter::once(Value::Array(slice)).chain(elements).collect()
+        }
+        Intrinsic::SlicePopFront => {
+            let rest = slice.split_off(element_size.min(slice.len()));
+            slice.into_iter().chain(std::iter::once(Value::Array(rest))).collect()
+        }
+        Intrinsic::SliceInsert => {
+            let index = numeric_argument(&arguments, 1)? as usize;
+            for element in arguments.drain(2..).rev() {
+                slice.insert(index, element);
+            }
+            vec![Value::Array(slice)]
+        }
+        Intrinsic::SliceRemove => {
+            let index = numeric_argument(&arguments, 1)? as usize;
+            let mut removed = slice.split_off(index);
+            let rest = removed.split_off(element_size.min(removed.len()));
+            slice.append(rest);
+            std::iter::once(Value::Array(slice)).chain(removed).collect()
+        }
+        Intrinsic::ToBits(endian) => {
+            let limb_count = numeric_argument(&arguments, 1)? as u32;
+            vec![to_radix(endian, &arguments[0], 2, limb_count)?]
+        }
+        Intrinsic::ToRadix(endian) => {
+            let radix = numeric_argument(&arguments, 1)? as u32;
+            let limb_count = numeric_argument(&arguments, 2)? as u32;
+            vec![to_radix(endian, &arguments[0], radix, limb_count)?]
+        }
+        Intrinsic::Sort => {
+            let mut elements = try_vecmap(slice, |element| match element {
+                Value::Numeric(value) => Ok(value),
+                other => Err(InterpreterError::Unsupported(format!("sorting {other:?}"))),
+            })?;
+            elements.sort();
+            vec![Value::Array(elements.into_iter().map(Value::Numeric).collect())]
+        }
+        // Printing has no effect on the program's results.
+        Intrinsic::Println => vecmap(result_types, Value::default_of),
+        Intrinsic::BlackBox(function) => {
+            return Err(InterpreterError::Unsupported(format!("the {function} black box function")))
+        }
+    };
+    Ok(results)
+}
+
+fn to_radix(
+    endian: Endian,
+    value: &Value,
+    radix: u32,
+    limb_count: u32,
+) -> Result<Value, InterpreterError> {
+    let Value::Numeric(value) = *value else {
+        return Err(InterpreterError::Internal(format!("expected a number, found {value:?}")));
+    };
+    let bit_size = u32::BITS - (radix - 1).leading_zeros();
+    if value.num_bits() > bit_size * limb_count {
+        return Err(InterpreterError::RadixDecompositionOverflow { value, radix, limb_count });
+    }
+    let limbs = radix_decomposition(endian, value, radix, limb_count);
+    Ok(Value::Array(limbs.into_iter().map(Value::Numeric).collect()))
+}
+
+/// Reduces `value` modulo 2^`bit_size`.
+fn truncate(value: FieldElement, bit_size: u32) -> FieldElement {
+    let value = BigUint::from_bytes_be(&value.to_be_bytes());
+    let truncated = value % BigUint::from(2u128).pow(bit_size);
+    FieldElement::from_be_bytes_reduce(&truncated.to_bytes_be())
+}
+
+/// Multiplies two integers modulo 2^`bit_size`, as their product may not fit within the field.
+fn wrapping_mul(lhs: FieldElement, rhs: FieldElement, bit_size: u32) -> FieldElement {
+    let lhs = BigUint::from_bytes_be(&lhs.to_be_bytes());
+    let rhs = BigUint::from_bytes_be(&rhs.to_be_bytes());
+    let product = (lhs * rhs) % BigUint::from(2u128).pow(bit_size);
+    FieldElement::from_be_bytes_reduce(&product.to_bytes_be())
+}
+
+fn to_u128(value: FieldElement) -> Result<u128, InterpreterError> {
+    value
+        .try_into_u128()
+        .ok_or_else(|| InterpreterError::Unsupported(format!("integers as large as {value}")))
+}
+
+/// Interprets the lowest `bit_size` bits of `value` as a two's complement signed integer.
+fn to_signed(value: u128, bit_size: u32) -> i128 {
+    let shift = 128 - bit_size;
+    ((value << shift) as i128) >> shift
+}
+
+/// Returns the two's complement representation of `value` in `bit_size` bits.
+fn from_signed(value: i128, bit_size: u32) -> u128 {
+    let mask = u128::MAX >> (128 - bit_size);
+    value as u128 & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::FieldElement;
+
+    use crate::ssa_refactor::{
+        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        ssa_builder::FunctionBuilder,
+        ssa_gen::Ssa,
+    };
+
+    use super::InterpreterError;
+
+    fn fields(values: &[u128]) -> Vec<FieldElement> {
+        values.iter().map(|value| FieldElement::from(*value)).collect()
+    }
+
+    /// Returns the SSA for:
+    ///
+    /// fn main(x: Field, double: bool) -> Field {
+    ///     let mut sum = 0;
+    ///     for _ in 0..3 {
+    ///         sum += x;
+    ///     }
+    ///     if double {
+    ///         sum *= 2;
+    ///     }
+    ///     sum
+    /// }
+    fn sum_program() -> Ssa {
+        // fn main f0 {
+        //   b0(v0: Field, v1: u1):
+        //     v2 = allocate
+        //     store Field 0 at v2
+        //     jmp b1(Field 0)
+        //   b1(v3: Field):
+        //     v4 = lt v3, Field 3
+        //     jmpif v4, then: b2, else: b3
+        //   b2():
+        //     v5 = load v2
+        //     v6 = add v5, v0
+        //     store v6 at v2
+        //     v7 = add v3, Field 1
+        //     jmp b1(v7)
+        //   b3():
+        //     jmpif v1, then: b4, else: b5
+        //   b4():
+        //     v8 = load v2
+        //     v9 = mul v8, Field 2
+        //     store v9 at v2
+        //     jmp b5()
+        //   b5():
+        //     v10 = load v2
+        //     return v10
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::bool());
+
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+        let b4 = builder.insert_block();
+        let b5 = builder.insert_block();
+        let v3 = builder.add_block_parameter(b1, Type::field());
+
+        let zero = builder.field_constant(0u128);
+        let one = builder.field_constant(1u128);
+        let two = builder.field_constant(2u128);
+        let three = builder.field_constant(3u128);
+
+        let v2 = builder.insert_allocate();
+        builder.insert_store(v2, zero);
+        builder.terminate_with_jmp(b1, vec![zero]);
+
+        builder.switch_to_block(b1);
+        let v4 = builder.insert_binary(v3, BinaryOp::Lt, three);
+        builder.terminate_with_jmpif(v4, b2, b3);
+
+        builder.switch_to_block(b2);
+        let v5 = builder.insert_load(v2, Type::field());
+        let v6 = builder.insert_binary(v5, BinaryOp::Add, v0);
+        builder.insert_store(v2, v6);
+        let v7 = builder.insert_binary(v3, BinaryOp::Add, one);
+        builder.terminate_with_jmp(b1, vec![v7]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_jmpif(v1, b4, b5);
+
+        builder.switch_to_block(b4);
+        let v8 = builder.insert_load(v2, Type::field());
+        let v9 = builder.insert_binary(v8, BinaryOp::Mul, two);
+        builder.insert_store(v2, v9);
+        builder.terminate_with_jmp(b5, vec![]);
+
+        builder.switch_to_block(b5);
+        let v10 = builder.insert_load(v2, Type::field());
+        builder.terminate_with_return(vec![v10]);
+
+        builder.finish()
+    }
+
+    #[test]
+    fn passes_preserve_the_results_of_the_program() {
+        let passes: [(&str, fn(Ssa) -> Ssa); 7] = [
+            ("inlining", Ssa::inline_functions),
+            ("unrolling", Ssa::unroll_loops),
+            ("simplifying", Ssa::simplify_cfg),
+            ("flattening", Ssa::flatten_cfg),
+            ("mem2reg", Ssa::mem2reg),
+            ("constant folding", Ssa::fold_constants),
+            ("dead instruction elimination", Ssa::dead_instruction_elimination),
+        ];
+        let cases = [(fields(&[5, 1]), fields(&[30])), (fields(&[5, 0]), fields(&[15]))];
+
+        let mut ssa = sum_program();
+        for (inputs, expected) in &cases {
+            assert_eq!(&ssa.interpret(inputs).unwrap(), expected);
+        }
+        for (pass, run_pass) in passes {
+            ssa = run_pass(ssa);
+            for (inputs, expected) in &cases {
+                let results = ssa.interpret(inputs).unwrap();
+                assert_eq!(&results, expected, "results changed after {pass}:\n{ssa}");
+            }
+        }
+    }
+
+    #[test]
+    fn integers_are_reduced_by_truncation() {
+        // fn main f0 {
+        //   b0(v0: u8, v1: u8):
+        //     v2 = add v0, v1
+        //     v3 = truncate v2 to 8 bits, max_bit_size: 9
+        //     v4 = lt v3, v0
+        //     return v3, v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let v1 = builder.add_parameter(Type::unsigned(8));
+        let v2 = builder.insert_binary(v0, BinaryOp::Add, v1);
+        let v3 = builder.insert_truncate(v2, 8, 9);
+        let v4 = builder.insert_binary(v3, BinaryOp::Lt, v0);
+        builder.terminate_with_return(vec![v3, v4]);
+        let ssa = builder.finish();
+
+        assert_eq!(ssa.interpret(&fields(&[200, 100])).unwrap(), fields(&[44, 1]));
+        assert_eq!(ssa.interpret(&fields(&[20, 10])).unwrap(), fields(&[30, 0]));
+    }
+
+    #[test]
+    fn wide_integer_products_are_not_reduced_by_the_field_modulus() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u128, v1: u128):
+                v2 = mul v0, v1
+                v3 = truncate v2 to 128 bits, max_bit_size: 256
+                v4 = div v3, v1
+                v5 = lt v3, v0
+                return v3, v4, v5
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1, which is 1 modulo 2^128
+        assert_eq!(ssa.interpret(&fields(&[u128::MAX, u128::MAX])).unwrap(), fields(&[1, 0, 1]));
+        assert_eq!(ssa.interpret(&fields(&[1 << 64, 3])).unwrap(), fields(&[3 << 64, 1 << 64, 0]));
+    }
+
+    #[test]
+    fn reports_failed_constraints() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = eq v0, Field 1
+        //     constrain v1
+        //     return
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let one = builder.field_constant(1u128);
+        let v1 = builder.insert_binary(v0, BinaryOp::Eq, one);
+        builder.insert_constrain(v1);
+        builder.terminate_with_return(vec![]);
+        let ssa = builder.finish();
+
+        assert!(ssa.interpret(&fields(&[1])).unwrap().is_empty());
+        assert!(matches!(
+            ssa.interpret(&fields(&[2])),
+            Err(InterpreterError::FailedConstraint { function }) if function == "main"
+        ));
+        assert!(matches!(
+            ssa.interpret(&fields(&[1, 2])),
+            Err(InterpreterError::WrongInputCount { expected: 1, found: 2 })
+        ));
+    }
+}
@@ -15,12 +15,13 @@ use super::{
 
 use acvm::FieldElement;
 use iter_extended::vecmap;
+use noirc_errors::Location;
 
 /// The DataFlowGraph contains most of the actual data in a function including
 /// its blocks, instructions, and values. This struct is largely responsible for
 /// owning most data in a function and handing out Ids to this data that can be
 /// shared without worrying about ownership.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub(crate) struct DataFlowGraph {
     /// All of the instructions in a function
     instructions: DenseMap<Instruction>,
@@ -69,6 +70,16 @@ pub(crate) struct DataFlowGraph {
     /// for that of another. This information is purely used for printing the SSA, and has no
     /// material effect on the SSA itself.
     replaced_value_ids: HashMap<ValueId, ValueId>,
+
+    /// Where each instruction came from in the source program. See [`InstructionSource`].
+    ///
+    /// This is used to attribute the cost of the final circuit to source functions and to
+    /// relate the final circuit back to the source when debugging. It has no effect on the SSA
+    /// itself.
+    sources: HashMap<InstructionId, InstructionSource>,
+
+    /// The names of the source variables bound to values, for debugging.
+    variable_names: HashMap<ValueId, Rc<str>>,
 }
 
 impl DataFlowGraph {
@@ -142,6 +153,25 @@ impl DataFlowGraph {
         instruction: Instruction,
         block: BasicBlockId,
         ctrl_typevars: Option<Vec<Type>>,
+    ) -> InsertInstructionResult {
+        self.insert_instruction_with_source(
+            instruction,
+            block,
+            ctrl_typevars,
+            InstructionSource::default(),
+        )
+    }
+
+    /// Inserts a new instruction at the end of the given block and returns its results.
+    ///
+    /// If the instruction is not simplified away, it is recorded as coming from `source`.
+    /// See [`DataFlowGraph::source`].
+    pub(crate) fn insert_instruction_with_source(
+        &mut self,
+        instruction: Instruction,
+        block: BasicBlockId,
+        ctrl_typevars: Option<Vec<Type>>,
+        source: InstructionSource,
     ) -> InsertInstructionResult {
         use InsertInstructionResult::*;
         match instruction.simplify(self, block) {
@@ -153,11 +183,54 @@ impl DataFlowGraph {
             SimplifyResult::None => {
                 let id = self.make_instruction(instruction, ctrl_typevars);
                 self.blocks[block].insert_instruction(id);
+                if source != InstructionSource::default() {
+                    self.sources.insert(id, source);
+                }
                 InsertInstructionResult::Results(self.instruction_results(id))
             }
         }
     }
 
+    /// Returns the name of the function the given instruction was inlined from, or None if
+    /// it originates from the function owning this DataFlowGraph.
+    pub(crate) fn origin(&self, instruction: InstructionId) -> Option<&Rc<str>> {
+        self.sources.get(&instruction).and_then(|source| source.origin.as_ref())
+    }
+
+    /// Returns the location in the source program the given instruction was generated from.
+    pub(crate) fn location(&self, instruction: InstructionId) -> Option<Location> {
+        self.sources.get(&instruction).and_then(|source| source.location)
+    }
+
+    /// Returns the locations of the calls the given instruction was inlined through, outermost
+    /// first. This is empty for instructions written in the function owning this DataFlowGraph.
+    pub(crate) fn call_stack(&self, instruction: InstructionId) -> CallStack {
+        self.sources.get(&instruction).map(|source| source.call_stack.clone()).unwrap_or_default()
+    }
+
+    /// Returns the origin, location and call stack of the given instruction.
+    pub(crate) fn source(&self, instruction: InstructionId) -> InstructionSource {
+        self.sources.get(&instruction).cloned().unwrap_or_default()
+    }
+
+    /// Records that `value` holds the source variable `name`, unless it already holds another
+    /// variable, e.g. for `let y = x;` where `x` keeps its name.
+    pub(crate) fn set_variable_name(&mut self, value: ValueId, name: Rc<str>) {
+        self.variable_names.entry(value).or_insert(name);
+    }
+
+    /// Gives `new_value` the variable name of `old_value` when replacing one with the other.
+    pub(crate) fn copy_variable_name(&mut self, old_value: ValueId, new_value: ValueId) {
+        if let Some(name) = self.variable_names.get(&old_value).cloned() {
+            self.set_variable_name(new_value, name);
+        }
+    }
+
+    /// Returns the name of the source variable held by `value`, if any.
+    pub(crate) fn variable_name(&self, value: ValueId) -> Option<&Rc<str>> {
+        self.variable_names.get(&value)
+    }
+
     /// Insert a value into the dfg's storage and return an id to reference it.
     /// Until the value is used in an instruction it is unreachable.
     pub(crate) fn make_value(&mut self, value: Value) -> ValueId {
@@ -441,6 +514,22 @@ impl std::ops::IndexMut<BasicBlockId> for DataFlowGraph {
     }
 }
 
+/// Where an instruction came from in the source program.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct InstructionSource {
+    /// The name of the function the instruction was originally written in, for instructions
+    /// which were inlined from another function. None if the instruction originates from the
+    /// function owning the DataFlowGraph.
+    pub(crate) origin: Option<Rc<str>>,
+    /// The source location the instruction was generated from, if known.
+    pub(crate) location: Option<Location>,
+    /// The locations of the calls the instruction was inlined through, outermost first.
+    pub(crate) call_stack: CallStack,
+}
+
+/// The locations of the function calls leading to an instruction, outermost first.
+pub(crate) type CallStack = im::Vector<Location>;
+
 // The result of calling DataFlowGraph::insert_instruction can
 // be a list of results or a single ValueId if the instruction was simplified
 // to an existing value.
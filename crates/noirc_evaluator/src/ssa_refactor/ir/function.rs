@@ -48,6 +48,12 @@ impl Function {
         Self { name, id, entry_block, dfg, runtime: RuntimeType::Acir }
     }
 
+    /// Creates a copy of `another` with the new id `id`, compiled for `runtime`.
+    pub(crate) fn clone_with_id(id: FunctionId, another: &Function, runtime: RuntimeType) -> Self {
+        let dfg = another.dfg.clone();
+        Self { name: another.name.clone(), id, entry_block: another.entry_block, dfg, runtime }
+    }
+
     /// The name of the function.
     /// Used exclusively for debugging purposes.
     pub(crate) fn name(&self) -> &str {
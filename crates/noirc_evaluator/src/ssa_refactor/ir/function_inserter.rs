@@ -69,7 +69,7 @@ impl<'f> FunctionInserter<'f> {
         instruction: Instruction,
         id: InstructionId,
         block: BasicBlockId,
-    ) -> InsertInstructionResult {
+    ) -> Vec<ValueId> {
         let results = self.function.dfg.instruction_results(id);
         let results = vecmap(results, |id| self.function.dfg.resolve(*id));
 
@@ -77,10 +77,23 @@ impl<'f> FunctionInserter<'f> {
             .requires_ctrl_typevars()
             .then(|| vecmap(&results, |result| self.function.dfg.type_of_value(*result)));
 
-        let new_results =
-            self.function.dfg.insert_instruction_and_results(instruction, block, ctrl_typevars);
+        let source = self.function.dfg.source(id);
+        let new_results = self.function.dfg.insert_instruction_with_source(
+            instruction,
+            block,
+            ctrl_typevars,
+            source,
+        );
 
         Self::insert_new_instruction_results(&mut self.values, &results, &new_results);
+        let new_results = match new_results {
+            InsertInstructionResult::InstructionRemoved => Vec::new(),
+            new_results => new_results.results().into_owned(),
+        };
+
+        for (old_result, new_result) in results.iter().zip(&new_results) {
+            self.function.dfg.copy_variable_name(*old_result, *new_result);
+        }
         new_results
     }
 
@@ -322,9 +322,11 @@ impl Instruction {
             }
             Instruction::Truncate { value, bit_size, .. } => {
                 if let Some((numeric_constant, typ)) = dfg.get_numeric_constant_with_type(*value) {
-                    let integer_modulus = 2_u128.pow(*bit_size);
-                    let truncated = numeric_constant.to_u128() % integer_modulus;
-                    SimplifiedTo(dfg.make_constant(truncated.into(), typ))
+                    let integer_modulus = BigUint::from(2u128).pow(*bit_size);
+                    let constant = BigUint::from_bytes_be(&numeric_constant.to_be_bytes());
+                    let truncated = constant % integer_modulus;
+                    let truncated = FieldElement::from_be_bytes_reduce(&truncated.to_bytes_be());
+                    SimplifiedTo(dfg.make_constant(truncated, typ))
                 } else {
                     None
                 }
@@ -511,6 +513,21 @@ fn constant_to_radix(
     limb_count: u32,
     dfg: &mut DataFlowGraph,
 ) -> ValueId {
+    let bit_size = u32::BITS - (radix - 1).leading_zeros();
+    let limbs = radix_decomposition(endian, field, radix, limb_count);
+    let result_constants =
+        limbs.into_iter().map(|limb| dfg.make_constant(limb, Type::unsigned(bit_size))).collect();
+    dfg.make_array(result_constants, Rc::new(vec![Type::unsigned(bit_size)]))
+}
+
+/// Returns the `limb_count` limbs of the radix decomposition of `field` in the given endianness,
+/// followed by zero padding up to 256 bits.
+pub(crate) fn radix_decomposition(
+    endian: Endian,
+    field: FieldElement,
+    radix: u32,
+    limb_count: u32,
+) -> Vec<FieldElement> {
     let bit_size = u32::BITS - (radix - 1).leading_zeros();
     let radix_big = BigUint::from(radix);
     assert_eq!(BigUint::from(2u128).pow(bit_size), radix_big, "ICE: Radix must be a power of 2");
@@ -534,9 +551,7 @@ fn constant_to_radix(
     while limbs.len() < limb_count_with_padding as usize {
         limbs.push(FieldElement::zero());
     }
-    let result_constants =
-        limbs.into_iter().map(|limb| dfg.make_constant(limb, Type::unsigned(bit_size))).collect();
-    dfg.make_array(result_constants, Rc::new(vec![Type::unsigned(bit_size)]))
+    limbs
 }
 
 /// The possible return values for Instruction::return_types
@@ -778,8 +793,23 @@ impl Binary {
         let value = match self.operator {
             BinaryOp::Add => lhs + rhs,
             BinaryOp::Sub => lhs - rhs,
+            // The product of wide integers could wrap around the field modulus before it is
+            // truncated, so it is not folded.
+            BinaryOp::Mul
+                if operand_type != Type::field()
+                    && lhs.num_bits() + rhs.num_bits() >= FieldElement::max_num_bits() =>
+            {
+                return None
+            }
             BinaryOp::Mul => lhs * rhs,
-            BinaryOp::Div => lhs / rhs,
+            // Integer division must round down, rather than multiply by the inverse of `rhs`
+            BinaryOp::Div => match operand_type {
+                Type::Numeric(NumericType::NativeField) => lhs / rhs,
+                Type::Numeric(NumericType::Unsigned { .. }) => {
+                    self.eval_constant_u128_operations(lhs, rhs)?
+                }
+                _ => return None,
+            },
             BinaryOp::Eq => {
                 operand_type = Type::bool();
                 (lhs == rhs).into()
@@ -810,7 +840,8 @@ impl Binary {
         let lhs = lhs.try_into_u128()?;
         let rhs = rhs.try_into_u128()?;
         match self.operator {
-            BinaryOp::Mod => Some((lhs % rhs).into()),
+            BinaryOp::Div => lhs.checked_div(rhs).map(Into::into),
+            BinaryOp::Mod => lhs.checked_rem(rhs).map(Into::into),
             BinaryOp::And => Some((lhs & rhs).into()),
             BinaryOp::Or => Some((lhs | rhs).into()),
             BinaryOp::Shr => Some((lhs >> rhs).into()),
@@ -824,14 +855,11 @@ impl Binary {
             // extra bits of the field. So we don't optimize it here.
             BinaryOp::Xor => None,
 
-            op @ (BinaryOp::Add
-            | BinaryOp::Sub
-            | BinaryOp::Mul
-            | BinaryOp::Div
-            | BinaryOp::Eq
-            | BinaryOp::Lt) => panic!(
-                "eval_constant_u128_operations invalid for {op:?} use eval_constants instead"
-            ),
+            op @ (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Eq | BinaryOp::Lt) => {
+                panic!(
+                    "eval_constant_u128_operations invalid for {op:?} use eval_constants instead"
+                )
+            }
         }
     }
 }
@@ -70,11 +70,10 @@ fn value(function: &Function, id: ValueId) -> String {
         Value::Intrinsic(intrinsic) => intrinsic.to_string(),
         Value::Array { array, .. } => {
             let elements = vecmap(array, |element| value(function, *element));
-            format!("[{}]", elements.join(", "))
-        }
-        Value::Param { .. } | Value::Instruction { .. } | Value::ForeignFunction(_) => {
-            id.to_string()
+            format!("{} [{}]", function.dfg.type_of_value(id), elements.join(", "))
         }
+        Value::ForeignFunction(name) => name.clone(),
+        Value::Param { .. } | Value::Instruction { .. } => id.to_string(),
     }
 }
 
@@ -88,6 +87,16 @@ fn value_list_with_types(function: &Function, values: &[ValueId]) -> String {
     .join(", ")
 }
 
+/// Display the types of the given results, for the instructions whose result types cannot be
+/// known from their operands. E.g. ` -> Field, u1`
+fn result_types(function: &Function, results: &[ValueId]) -> String {
+    if results.is_empty() {
+        return String::new();
+    }
+    let types = vecmap(results, |id| function.dfg.type_of_value(*id).to_string());
+    format!(" -> {}", types.join(", "))
+}
+
 /// Display each value separated by a comma
 fn value_list(function: &Function, values: &[ValueId]) -> String {
     vecmap(values, |id| value(function, *id)).join(", ")
@@ -149,10 +158,13 @@ pub(crate) fn display_instruction(
             writeln!(f, "constrain {}", show(*value))
         }
         Instruction::Call { func, arguments } => {
-            writeln!(f, "call {}({})", show(*func), value_list(function, arguments))
+            let arguments = value_list(function, arguments);
+            writeln!(f, "call {}({arguments}){}", show(*func), result_types(function, results))
         }
         Instruction::Allocate => writeln!(f, "allocate"),
-        Instruction::Load { address } => writeln!(f, "load {}", show(*address)),
+        Instruction::Load { address } => {
+            writeln!(f, "load {}{}", show(*address), result_types(function, results))
+        }
         Instruction::Store { address, value } => {
             writeln!(f, "store {} at {}", show(*value), show(*address))
         }
@@ -160,7 +172,8 @@ pub(crate) fn display_instruction(
             writeln!(f, "enable_side_effects {}", show(*condition))
         }
         Instruction::ArrayGet { array, index } => {
-            writeln!(f, "array_get {}, index {}", show(*array), show(*index))
+            let types = result_types(function, results);
+            writeln!(f, "array_get {}, index {}{types}", show(*array), show(*index))
         }
         Instruction::ArraySet { array, index, value } => {
             writeln!(
@@ -0,0 +1,173 @@
+//! Value range analysis computes an upper bound on the number of bits each numeric value of a
+//! function occupies, so that truncations and range constraints which are already implied by
+//! the way a value was computed can be skipped.
+//!
+//! The analysis relies on the invariant that function and block parameters fit within their
+//! type, as the inputs of a program are range constrained. The results of arithmetic may be wider
+//! than their type until they are truncated. Call results, loads and array elements may have
+//! been returned by unconstrained functions, so nothing is assumed about their size.
+use std::collections::HashMap;
+
+use acvm::FieldElement;
+
+use super::{
+    dfg::DataFlowGraph,
+    function::Function,
+    instruction::{Binary, BinaryOp, Instruction},
+    post_order::PostOrder,
+    types::{NumericType, Type},
+    value::{Value, ValueId},
+};
+
+/// The maximum number of bits each numeric instruction result of a function may occupy.
+#[derive(Debug, Default)]
+pub(crate) struct ValueRanges {
+    max_bit_sizes: HashMap<ValueId, u32>,
+}
+
+impl ValueRanges {
+    /// Computes the bit sizes of the instruction results in each reachable block of `function`.
+    pub(crate) fn with_function(function: &Function) -> Self {
+        let mut ranges = ValueRanges::default();
+        let dfg = &function.dfg;
+
+        // Visiting the blocks in reverse post order visits each instruction after those
+        // defining its operands, other than block parameters.
+        let post_order = PostOrder::with_function(function);
+        for block in post_order.as_slice().iter().rev() {
+            for instruction in dfg[*block].instructions() {
+                let results = dfg.instruction_results(*instruction);
+                if let [result] = results {
+                    let typ = dfg.type_of_value(*result);
+                    if let Type::Numeric(_) = typ {
+                        let bit_size = ranges.instruction_bit_size(dfg, &dfg[*instruction], &typ);
+                        ranges.max_bit_sizes.insert(*result, bit_size);
+                    }
+                }
+            }
+        }
+        ranges
+    }
+
+    /// Returns the maximum number of bits `value` may occupy.
+    pub(crate) fn max_bit_size(&self, dfg: &DataFlowGraph, value: ValueId) -> u32 {
+        let value = dfg.resolve(value);
+        if let Some(bit_size) = self.max_bit_sizes.get(&value) {
+            return *bit_size;
+        }
+        match &dfg[value] {
+            Value::NumericConstant { constant, .. } => constant.num_bits(),
+            _ => type_bit_size(&dfg.type_of_value(value)),
+        }
+    }
+
+    /// Returns the number of bits the result of `instruction`, of type `typ`, may occupy.
+    fn instruction_bit_size(
+        &self,
+        dfg: &DataFlowGraph,
+        instruction: &Instruction,
+        typ: &Type,
+    ) -> u32 {
+        let field_bits = FieldElement::max_num_bits();
+        let bit_size = match instruction {
+            Instruction::Binary(binary) => self.binary_bit_size(dfg, binary),
+            Instruction::Cast(value, Type::Numeric(NumericType::NativeField)) => {
+                self.max_bit_size(dfg, *value)
+            }
+            Instruction::Cast(value, _) => {
+                std::cmp::min(self.max_bit_size(dfg, *value), type_bit_size(typ))
+            }
+            Instruction::Truncate { value, bit_size, .. } => {
+                std::cmp::min(self.max_bit_size(dfg, *value), *bit_size)
+            }
+            // Subtracting a value wider than its type from the type's maximum wraps around
+            Instruction::Not(value) if self.max_bit_size(dfg, *value) <= type_bit_size(typ) => {
+                type_bit_size(typ)
+            }
+            Instruction::Not(_) => field_bits,
+            Instruction::Call { .. }
+            | Instruction::Load { .. }
+            | Instruction::ArrayGet { .. }
+            | Instruction::Allocate
+            | Instruction::Constrain(_)
+            | Instruction::Store { .. }
+            | Instruction::EnableSideEffects { .. }
+            | Instruction::ArraySet { .. } => field_bits,
+        };
+        std::cmp::min(bit_size, field_bits)
+    }
+
+    fn binary_bit_size(&self, dfg: &DataFlowGraph, binary: &Binary) -> u32 {
+        let field_bits = FieldElement::max_num_bits();
+        let lhs = self.max_bit_size(dfg, binary.lhs);
+        let rhs = self.max_bit_size(dfg, binary.rhs);
+        let typ = dfg.type_of_value(binary.lhs);
+        let shift = || dfg.get_numeric_constant(binary.rhs).map(|shift| shift.to_u128());
+
+        match (binary.operator, typ) {
+            (BinaryOp::Add, _) => std::cmp::max(lhs, rhs) + 1,
+            (BinaryOp::Mul, _) => lhs + rhs,
+            // Subtractions wrap around the field modulus, unless they are truncated
+            (BinaryOp::Sub, _) => field_bits,
+            (BinaryOp::Eq | BinaryOp::Lt, _) => 1,
+            (BinaryOp::And, _) => std::cmp::min(lhs, rhs),
+            (BinaryOp::Or | BinaryOp::Xor, _) => std::cmp::max(lhs, rhs),
+            (BinaryOp::Shl, _) => match shift() {
+                Some(shift) if shift < field_bits as u128 => lhs + shift as u32,
+                _ => field_bits,
+            },
+            (BinaryOp::Shr, _) => match shift() {
+                Some(shift) if shift < lhs as u128 => lhs - shift as u32,
+                Some(_) => 0,
+                None => lhs,
+            },
+            (BinaryOp::Div, Type::Numeric(NumericType::Unsigned { .. })) => lhs,
+            (BinaryOp::Mod, Type::Numeric(NumericType::Unsigned { .. })) => std::cmp::min(lhs, rhs),
+            (BinaryOp::Div | BinaryOp::Mod, typ) => type_bit_size(&typ),
+        }
+    }
+}
+
+/// The number of bits a value of the given type occupies.
+fn type_bit_size(typ: &Type) -> u32 {
+    match typ {
+        Type::Numeric(NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size }) => {
+            *bit_size
+        }
+        _ => FieldElement::max_num_bits(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::ssa_gen::Ssa;
+
+    use super::ValueRanges;
+
+    #[test]
+    fn tracks_bit_sizes_through_arithmetic() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u32, v1: u8):
+                v2 = cast v0 as u8
+                v3 = cast v1 as u32
+                v4 = mul v3, u32 300
+                v5 = add v4, v3
+                v6 = lt v0, v5
+                v7 = sub v0, v3
+                v8 = truncate v7 to 32 bits, max_bit_size: 33
+                return v2, v5, v6, v8
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        let function = ssa.main();
+        let ranges = ValueRanges::with_function(function);
+
+        let dfg = &function.dfg;
+        let bit_sizes = dfg[function.entry_block()]
+            .instructions()
+            .iter()
+            .map(|instruction| ranges.max_bit_size(dfg, dfg.instruction_results(*instruction)[0]));
+        assert_eq!(bit_sizes.collect::<Vec<_>>(), vec![8, 8, 17, 18, 1, 254, 32]);
+    }
+}
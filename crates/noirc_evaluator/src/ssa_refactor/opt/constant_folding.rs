@@ -71,15 +71,21 @@ impl Context {
             .requires_ctrl_typevars()
             .then(|| vecmap(&old_results, |result| function.dfg.type_of_value(*result)));
 
-        let new_results =
-            match function.dfg.insert_instruction_and_results(instruction, block, ctrl_typevars) {
-                InsertInstructionResult::SimplifiedTo(new_result) => vec![new_result],
-                InsertInstructionResult::SimplifiedToMultiple(new_results) => new_results,
-                InsertInstructionResult::Results(new_results) => new_results.to_vec(),
-                InsertInstructionResult::InstructionRemoved => vec![],
-            };
+        let source = function.dfg.source(id);
+        let new_results = match function.dfg.insert_instruction_with_source(
+            instruction,
+            block,
+            ctrl_typevars,
+            source,
+        ) {
+            InsertInstructionResult::SimplifiedTo(new_result) => vec![new_result],
+            InsertInstructionResult::SimplifiedToMultiple(new_results) => new_results,
+            InsertInstructionResult::Results(new_results) => new_results.to_vec(),
+            InsertInstructionResult::InstructionRemoved => vec![],
+        };
         assert_eq!(old_results.len(), new_results.len());
         for (old_result, new_result) in old_results.iter().zip(new_results) {
+            function.dfg.copy_variable_name(*old_result, new_result);
             function.dfg.set_value_from_id(*old_result, new_result);
         }
     }
@@ -95,7 +101,9 @@ mod test {
             types::Type,
             value::Value,
         },
+        parser::assert_ssa_equals,
         ssa_builder::FunctionBuilder,
+        ssa_gen::Ssa,
     };
 
     #[test]
@@ -195,4 +203,50 @@ mod test {
         // The return element is expected to refer to the new add instruction result.
         assert_eq!(main.dfg.resolve(new_add_instr_result), main.dfg.resolve(return_element));
     }
+
+    #[test]
+    fn folds_instructions_whose_operands_become_constant() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field):
+                v1 = add Field 2, Field 3
+                v2 = mul v0, v1
+                return v2
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: Field):
+                v1 = mul v0, Field 5
+                return v1
+            }
+        ";
+        assert_ssa_equals(&ssa.fold_constants(), expected);
+    }
+
+    #[test]
+    fn folds_u128_arithmetic() {
+        // (2^127 + 1) * 3 wraps around to 2^127 + 3, which is halved rounding down
+        let src = "
+            acir fn main f0 {
+              b0():
+                v0 = mul u128 170141183460469231731687303715884105729, u128 3
+                v1 = truncate v0 to 128 bits, max_bit_size: 256
+                v2 = div v1, u128 2
+                v3 = mod v1, u128 2
+                return v2, v3
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0():
+                return u128 85070591730234615865843651857942052865, u128 1
+            }
+        ";
+        assert_ssa_equals(&ssa.fold_constants(), expected);
+    }
 }
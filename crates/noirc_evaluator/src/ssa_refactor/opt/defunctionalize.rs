@@ -4,6 +4,10 @@
 //! with a non-literal target can be replaced with a call to an apply function.
 //! The apply function is a dispatch function that takes the function id as a parameter
 //! and dispatches to the correct target.
+//!
+//! Brillig code can only call Brillig functions, so each ACIR function which Brillig code calls,
+//! directly or as a function value, is copied into a Brillig function. Function values keep the
+//! id of the ACIR function, and the apply functions of Brillig code dispatch them to the copies.
 use std::collections::{HashMap, HashSet};
 
 use acvm::FieldElement;
@@ -64,15 +68,28 @@ struct ApplyFunction {
 #[derive(Debug, Clone)]
 struct DefunctionalizationContext {
     fn_to_runtime: HashMap<FunctionId, RuntimeType>,
-    variants: HashMap<FunctionSignature, Vec<FunctionId>>,
+    variants: HashMap<FunctionSignature, Vec<Variant>>,
     apply_functions: HashMap<FunctionSignature, ApplyFunction>,
 }
 
+/// A function an apply function dispatches to.
+#[derive(Debug, Clone, Copy)]
+struct Variant {
+    /// The id the function is passed around as when used as a value
+    id: FunctionId,
+    /// The function called for it, which is a Brillig copy of the function `id` when it is
+    /// called from Brillig code
+    target: FunctionId,
+}
+
 impl Ssa {
     pub(crate) fn defunctionalize(mut self) -> Ssa {
         // Find all functions used as value that share the same signature
         let variants = find_variants(&self);
 
+        let brillig_copies = copy_functions_called_from_brillig(&mut self, &variants);
+        let variants = add_brillig_variants(variants, &brillig_copies);
+
         let apply_functions = create_apply_functions(&mut self, &variants);
         let fn_to_runtime =
             self.functions.iter().map(|(func_id, func)| (*func_id, func.runtime())).collect();
@@ -225,9 +242,110 @@ fn functions_as_values(func: &Function) -> HashSet<FunctionId> {
     literal_functions
 }
 
+/// Copies each ACIR function which is called from Brillig code into a Brillig function, and
+/// makes the direct calls of Brillig code call the copies. Returns the copy of each function.
+///
+/// Every variant of a function value called from Brillig code is copied, as is every function
+/// called by a copy, since the copies are Brillig code themselves.
+fn copy_functions_called_from_brillig(
+    ssa: &mut Ssa,
+    variants: &HashMap<FunctionSignature, Vec<FunctionId>>,
+) -> HashMap<FunctionId, FunctionId> {
+    let mut copies = HashMap::new();
+    let mut brillig_functions: Vec<_> = ssa
+        .functions
+        .values()
+        .filter(|function| function.runtime() == RuntimeType::Brillig)
+        .map(Function::id)
+        .collect();
+
+    while let Some(function_id) = brillig_functions.pop() {
+        let function = &ssa.functions[&function_id];
+        let mut called_functions = Vec::new();
+        let mut direct_calls = Vec::new();
+        for block_id in function.reachable_blocks() {
+            for instruction_id in function.dfg[block_id].instructions() {
+                let Instruction::Call { func, arguments } = &function.dfg[*instruction_id] else {
+                    continue;
+                };
+                match function.dfg[*func] {
+                    Value::Function(id) => {
+                        called_functions.push(id);
+                        direct_calls.push((*instruction_id, id));
+                    }
+                    Value::Param { .. } | Value::Instruction { .. } => {
+                        let results = function.dfg.instruction_results(*instruction_id);
+                        let signature = FunctionSignature {
+                            parameters: vecmap(arguments, |arg| function.dfg.type_of_value(*arg)),
+                            returns: vecmap(results, |result| function.dfg.type_of_value(*result)),
+                            runtime: RuntimeType::Acir,
+                        };
+                        called_functions.extend(variants.get(&signature).into_iter().flatten());
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        // The blocks are visited in no particular order, so the copies are made in order of id
+        // to number them the same way on every compilation.
+        called_functions.sort();
+        called_functions.dedup();
+        for id in called_functions {
+            if ssa.functions[&id].runtime() == RuntimeType::Acir && !copies.contains_key(&id) {
+                let copy_id = ssa.next_id.next();
+                let copy =
+                    Function::clone_with_id(copy_id, &ssa.functions[&id], RuntimeType::Brillig);
+                ssa.functions.insert(copy_id, copy);
+                copies.insert(id, copy_id);
+                brillig_functions.push(copy_id);
+            }
+        }
+
+        let function = ssa.functions.get_mut(&function_id).unwrap();
+        for (instruction_id, id) in direct_calls {
+            if let Some(copy_id) = copies.get(&id) {
+                let copy = function.dfg.import_function(*copy_id);
+                let Instruction::Call { func, .. } = &mut function.dfg[instruction_id] else {
+                    unreachable!("Expected a call instruction");
+                };
+                *func = copy;
+            }
+        }
+    }
+    copies
+}
+
+/// Returns the variants of each signature, adding the Brillig copies of the variants of ACIR
+/// function values as the variants of the same signature in Brillig code.
+fn add_brillig_variants(
+    variants: HashMap<FunctionSignature, Vec<FunctionId>>,
+    brillig_copies: &HashMap<FunctionId, FunctionId>,
+) -> HashMap<FunctionSignature, Vec<Variant>> {
+    let mut all_variants: HashMap<_, Vec<_>> = HashMap::new();
+    for (signature, ids) in variants {
+        if signature.runtime == RuntimeType::Acir {
+            let copies: Vec<_> = ids
+                .iter()
+                .filter_map(|id| {
+                    brillig_copies.get(id).map(|copy| Variant { id: *id, target: *copy })
+                })
+                .collect();
+            if !copies.is_empty() {
+                let brillig_signature =
+                    FunctionSignature { runtime: RuntimeType::Brillig, ..signature.clone() };
+                all_variants.entry(brillig_signature).or_default().extend(copies);
+            }
+        }
+        let variants = ids.into_iter().map(|id| Variant { id, target: id });
+        all_variants.entry(signature).or_default().extend(variants);
+    }
+    all_variants
+}
+
 fn create_apply_functions(
     ssa: &mut Ssa,
-    variants_map: &HashMap<FunctionSignature, Vec<FunctionId>>,
+    variants_map: &HashMap<FunctionSignature, Vec<Variant>>,
 ) -> HashMap<FunctionSignature, ApplyFunction> {
     let mut apply_functions = HashMap::new();
     for (signature, variants) in variants_map.iter() {
@@ -235,7 +353,7 @@ fn create_apply_functions(
         let id = if dispatches_to_multiple_functions {
             create_apply_function(ssa, signature, variants)
         } else {
-            variants[0]
+            variants[0].target
         };
         apply_functions
             .insert(signature.clone(), ApplyFunction { id, dispatches_to_multiple_functions });
@@ -251,9 +369,9 @@ fn function_id_to_field(function_id: FunctionId) -> FieldElement {
 fn create_apply_function(
     ssa: &mut Ssa,
     signature: &FunctionSignature,
-    function_ids: &[FunctionId],
+    variants: &[Variant],
 ) -> FunctionId {
-    assert!(!function_ids.is_empty());
+    assert!(!variants.is_empty());
     ssa.add_fn(|id| {
         let mut function_builder = FunctionBuilder::new("apply".to_string(), id, signature.runtime);
         let target_id = function_builder.add_parameter(Type::field());
@@ -261,12 +379,12 @@ fn create_apply_function(
             vecmap(signature.parameters.clone(), |typ| function_builder.add_parameter(typ));
 
         let mut previous_target_block = None;
-        for (index, function_id) in function_ids.iter().enumerate() {
-            let is_last = index == function_ids.len() - 1;
+        for (index, variant) in variants.iter().enumerate() {
+            let is_last = index == variants.len() - 1;
             let mut next_function_block = None;
 
             let function_id_constant = function_builder.numeric_constant(
-                function_id_to_field(*function_id),
+                function_id_to_field(variant.id),
                 Type::Numeric(NumericType::NativeField),
             );
             let condition =
@@ -299,7 +417,7 @@ fn create_apply_function(
             previous_target_block = Some(target_block);
 
             // Call the function
-            let target_function_value = function_builder.import_function(*function_id);
+            let target_function_value = function_builder.import_function(variant.target);
             let call_results = function_builder
                 .insert_call(target_function_value, params_ids.clone(), signature.returns.clone())
                 .to_vec();
@@ -335,3 +453,56 @@ fn build_return_block(
     builder.switch_to_block(previous_block);
     return_block
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{parser::assert_ssa_equals, ssa_gen::Ssa};
+
+    #[test]
+    fn copies_functions_called_from_brillig_into_brillig() {
+        // The comparator of `sort_via` is a lambda, compiled as ACIR, which the unconstrained
+        // `get_sorting_indices` calls.
+        let src = "
+            acir fn main f0 {
+              b0(v0: u8, v1: u8):
+                v2 = call f1(v0, v1, f2) -> u1
+                return v2
+            }
+            brillig fn get_sorting_indices f1 {
+              b0(v0: u8, v1: u8, v2: function):
+                v3 = call v2(v0, v1) -> u1
+                return v3
+            }
+            acir fn lambda f2 {
+              b0(v0: u8, v1: u8):
+                v2 = lt v0, v1
+                return v2
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: u8, v1: u8):
+                v2 = call f1(v0, v1, Field 2) -> u1
+                return v2
+            }
+            brillig fn get_sorting_indices f1 {
+              b0(v0: u8, v1: u8, v2: Field):
+                v3 = call f3(v0, v1) -> u1
+                return v3
+            }
+            acir fn lambda f2 {
+              b0(v0: u8, v1: u8):
+                v2 = lt v0, v1
+                return v2
+            }
+            brillig fn lambda f3 {
+              b0(v0: u8, v1: u8):
+                v2 = lt v0, v1
+                return v2
+            }
+        ";
+        assert_ssa_equals(&ssa.defunctionalize(), expected);
+    }
+}
@@ -135,7 +135,9 @@ impl Context {
 mod test {
     use crate::ssa_refactor::{
         ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        parser::assert_ssa_equals,
         ssa_builder::FunctionBuilder,
+        ssa_gen::Ssa,
     };
 
     #[test]
@@ -218,4 +220,33 @@ mod test {
         assert_eq!(main.dfg[main.entry_block()].instructions().len(), 1);
         assert_eq!(main.dfg[b1].instructions().len(), 6);
     }
+
+    #[test]
+    fn removes_chains_of_unused_instructions() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field):
+                v1 = add v0, Field 1
+                v2 = mul v1, Field 2
+                v3 = allocate
+                v4 = load v3 -> Field
+                v5 = add v4, v2
+                v6 = eq v0, Field 3
+                constrain v6
+                return v1
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: Field):
+                v1 = add v0, Field 1
+                v2 = eq v0, Field 3
+                constrain v2
+                return v1
+            }
+        ";
+        assert_ssa_equals(&ssa.dead_instruction_elimination(), expected);
+    }
 }
@@ -661,7 +661,7 @@ impl<'f> Context<'f> {
         // Remember an allocate was created local to this branch so that we do not try to merge store
         // values across branches for it later.
         if is_allocate {
-            self.local_allocations.insert(results.first());
+            self.local_allocations.insert(results[0]);
         }
     }
 
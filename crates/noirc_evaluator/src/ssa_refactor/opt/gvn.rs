@@ -0,0 +1,253 @@
+//! Global value numbering merges each instruction which computes the same value as an identical
+//! instruction earlier in its block, or in a block which dominates it. The results of the later
+//! instruction are replaced with those of the earlier one, and the later instruction is removed.
+//!
+//! Only instructions without side effects are merged, along with repeated `constrain`s. After
+//! flattening, instructions which may fail or which are predicated in ACIR (constraints,
+//! division, array accesses and calls) are only merged when the same `enable_side_effects`
+//! condition applies to both of them.
+use std::collections::HashMap;
+
+use crate::ssa_refactor::{
+    ir::{
+        basic_block::BasicBlockId,
+        dfg::DataFlowGraph,
+        dom::DominatorTree,
+        function::Function,
+        instruction::{Binary, BinaryOp, Instruction, Intrinsic},
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// Removes instructions which recompute the results of an identical instruction that
+    /// dominates them.
+    pub(crate) fn global_value_numbering(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            global_value_numbering(function);
+        }
+        self
+    }
+}
+
+/// An instruction, with its operands resolved, along with the `enable_side_effects` condition
+/// it is executed under if the instruction depends upon it.
+type Expression = (Instruction, Option<ValueId>);
+
+fn global_value_numbering(function: &mut Function) {
+    let mut dominator_tree = DominatorTree::with_function(function);
+
+    // Visiting the blocks in reverse post order visits each block after those dominating it.
+    let mut blocks: Vec<_> = function.reachable_blocks().into_iter().collect();
+    blocks.sort_by(|a, b| dominator_tree.reverse_post_order_cmp(*a, *b));
+
+    // The blocks each expression has been computed in, with its results in that block
+    let mut computed: HashMap<Expression, Vec<(BasicBlockId, Vec<ValueId>)>> = HashMap::new();
+
+    for block in blocks {
+        let mut side_effects_condition = None;
+        let instructions = std::mem::take(function.dfg[block].instructions_mut());
+
+        for instruction_id in instructions {
+            let dfg = &function.dfg;
+            let instruction = dfg[instruction_id].map_values(|value| dfg.resolve(value));
+            if let Instruction::EnableSideEffects { condition } = instruction {
+                side_effects_condition = Some(condition);
+            }
+
+            if !can_be_merged(&instruction, dfg) {
+                function.dfg[block].insert_instruction(instruction_id);
+                continue;
+            }
+
+            let condition = if depends_on_side_effects_condition(&instruction) {
+                side_effects_condition
+            } else {
+                None
+            };
+            let candidates = computed.entry((normalize(instruction), condition)).or_default();
+            let existing = candidates
+                .iter()
+                .find(|(defining_block, _)| dominator_tree.dominates(*defining_block, block));
+
+            let results = function.dfg.instruction_results(instruction_id).to_vec();
+            match existing {
+                Some((_, existing_results)) => {
+                    for (result, existing_result) in results.into_iter().zip(existing_results) {
+                        function.dfg.set_value_from_id(result, *existing_result);
+                    }
+                }
+                None => {
+                    candidates.push((block, results));
+                    function.dfg[block].insert_instruction(instruction_id);
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if an instruction may be replaced by an identical one which dominates it.
+fn can_be_merged(instruction: &Instruction, dfg: &DataFlowGraph) -> bool {
+    match instruction {
+        Instruction::Binary(_)
+        | Instruction::Cast(..)
+        | Instruction::Not(_)
+        | Instruction::Truncate { .. }
+        | Instruction::Constrain(_)
+        | Instruction::ArrayGet { .. }
+        | Instruction::ArraySet { .. } => true,
+        Instruction::Call { func, .. } => {
+            matches!(&dfg[*func], Value::Intrinsic(intrinsic) if *intrinsic != Intrinsic::Println)
+        }
+        Instruction::Allocate
+        | Instruction::Load { .. }
+        | Instruction::Store { .. }
+        | Instruction::EnableSideEffects { .. } => false,
+    }
+}
+
+/// Returns true if an instruction may fail, or is predicated when it is converted into ACIR, so
+/// that it only computes the same value as an identical instruction under the same condition.
+fn depends_on_side_effects_condition(instruction: &Instruction) -> bool {
+    match instruction {
+        Instruction::Binary(binary) => matches!(binary.operator, BinaryOp::Div | BinaryOp::Mod),
+        Instruction::Constrain(_)
+        | Instruction::ArrayGet { .. }
+        | Instruction::ArraySet { .. }
+        | Instruction::Call { .. } => true,
+        _ => false,
+    }
+}
+
+/// Orders the operands of commutative binary instructions, so that `add v0, v1` and
+/// `add v1, v0` are numbered as the same expression.
+fn normalize(instruction: Instruction) -> Instruction {
+    match instruction {
+        Instruction::Binary(Binary { lhs, operator, rhs }) if is_commutative(operator) => {
+            let (lhs, rhs) = if rhs < lhs { (rhs, lhs) } else { (lhs, rhs) };
+            Instruction::Binary(Binary { lhs, operator, rhs })
+        }
+        instruction => instruction,
+    }
+}
+
+fn is_commutative(operator: BinaryOp) -> bool {
+    use BinaryOp::*;
+    matches!(operator, Add | Mul | Eq | And | Or | Xor)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa_refactor::{parser::assert_ssa_equals, ssa_gen::Ssa};
+
+    #[test]
+    fn merges_instructions_repeated_in_dominated_blocks() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field, v1: u1):
+                v2 = add v0, Field 1
+                jmpif v1 then: b1, else: b2
+              b1():
+                v3 = add Field 1, v0
+                v4 = mul v3, v3
+                jmp b3(v4)
+              b2():
+                v5 = mul v2, v2
+                jmp b3(v5)
+              b3(v6: Field):
+                return v6
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        // The multiplications in b1 and b2 are kept as neither block dominates the other.
+        let expected = "
+            acir fn main f0 {
+              b0(v0: Field, v1: u1):
+                v2 = add v0, Field 1
+                jmpif v1 then: b1, else: b2
+              b1():
+                v3 = mul v2, v2
+                jmp b3(v3)
+              b2():
+                v4 = mul v2, v2
+                jmp b3(v4)
+              b3(v5: Field):
+                return v5
+            }
+        ";
+        assert_ssa_equals(&ssa.global_value_numbering(), expected);
+    }
+
+    #[test]
+    fn only_merges_predicated_instructions_under_the_same_condition() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u32, v1: u32, v2: u1):
+                enable_side_effects v2
+                v3 = div v0, v1
+                v4 = mul v0, v1
+                constrain v2
+                enable_side_effects u1 1
+                v5 = div v0, v1
+                v6 = mul v1, v0
+                constrain v2
+                v7 = div v0, v1
+                constrain v2
+                return v3, v4, v5, v6, v7
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: u32, v1: u32, v2: u1):
+                enable_side_effects v2
+                v3 = div v0, v1
+                v4 = mul v0, v1
+                constrain v2
+                enable_side_effects u1 1
+                v5 = div v0, v1
+                constrain v2
+                return v3, v4, v5, v4, v5
+            }
+        ";
+        assert_ssa_equals(&ssa.global_value_numbering(), expected);
+    }
+
+    #[test]
+    fn merges_recomputed_merkle_paths() {
+        // The shape of `merkle_insert` once inlined: the path from the same index through the
+        // same hash path is computed once for the old leaf and once for the new leaf, and only
+        // the hashes (stood in for by `add`) differ between the two.
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field, v1: [Field; 2], v2: Field, v3: Field):
+                v4 = call to_le_bits(v0, u32 2) -> [u1; 2]
+                v5 = array_get v4, index u32 0 -> u1
+                v6 = array_get v1, index u32 0 -> Field
+                v7 = add v2, v6
+                v8 = call to_le_bits(v0, u32 2) -> [u1; 2]
+                v9 = array_get v8, index u32 0 -> u1
+                v10 = array_get v1, index u32 0 -> Field
+                v11 = add v3, v10
+                return v5, v7, v9, v11
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: Field, v1: [Field; 2], v2: Field, v3: Field):
+                v4 = call to_le_bits(v0, u32 2) -> [u1; 2]
+                v5 = array_get v4, index u32 0 -> u1
+                v6 = array_get v1, index u32 0 -> Field
+                v7 = add v2, v6
+                v8 = add v3, v6
+                return v5, v7, v5, v8
+            }
+        ";
+        assert_ssa_equals(&ssa.global_value_numbering(), expected);
+    }
+}
@@ -2,14 +2,17 @@
 //! The purpose of this pass is to inline the instructions of each function call
 //! within the function caller. If all function calls are known, there will only
 //! be a single function remaining when the pass finishes.
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 use iter_extended::vecmap;
 
 use crate::ssa_refactor::{
     ir::{
         basic_block::BasicBlockId,
-        dfg::InsertInstructionResult,
+        dfg::{CallStack, InsertInstructionResult, InstructionSource},
         function::{Function, FunctionId, RuntimeType},
         instruction::{Instruction, InstructionId, TerminatorInstruction},
         value::{Value, ValueId},
@@ -53,6 +56,10 @@ struct InlineContext {
     /// inlining we can remove all other functions from the resulting Ssa struct and keep only
     /// the function that was inlined into.
     failed_to_inline_a_call: bool,
+
+    /// The locations of the calls currently being inlined, outermost first. This is recorded
+    /// as the call stack of each instruction inlined from them.
+    call_stack: CallStack,
 }
 
 /// The per-function inlining context contains information that is only valid for one function.
@@ -87,6 +94,10 @@ struct PerFunctionContext<'function> {
 
     /// True if we're currently working on the main function.
     inlining_main: bool,
+
+    /// The name of the source function, recorded as the origin of each instruction inlined
+    /// from it so that the cost of the final circuit can be attributed to it.
+    source_name: Rc<str>,
 }
 
 impl InlineContext {
@@ -98,7 +109,12 @@ impl InlineContext {
     fn new(ssa: &Ssa) -> InlineContext {
         let main_name = ssa.main().name().to_owned();
         let builder = FunctionBuilder::new(main_name, ssa.next_id.next(), RuntimeType::Acir);
-        Self { builder, recursion_level: 0, failed_to_inline_a_call: false }
+        Self {
+            builder,
+            recursion_level: 0,
+            failed_to_inline_a_call: false,
+            call_stack: CallStack::new(),
+        }
     }
 
     /// Start inlining the main function and all functions reachable from it.
@@ -116,6 +132,9 @@ impl InlineContext {
         for parameter in original_parameters {
             let typ = context.source_function.dfg.type_of_value(*parameter);
             let new_parameter = context.context.builder.add_block_parameter(entry_block, typ);
+            if let Some(name) = context.source_function.dfg.variable_name(*parameter) {
+                context.context.builder.name_value(new_parameter, name.clone());
+            }
             context.values.insert(*parameter, new_parameter);
         }
 
@@ -189,6 +208,7 @@ impl<'function> PerFunctionContext<'function> {
             instructions: HashMap::new(),
             values: HashMap::new(),
             inlining_main: false,
+            source_name: source_function.name().into(),
         }
     }
 
@@ -370,7 +390,13 @@ impl<'function> PerFunctionContext<'function> {
     ) {
         let old_results = self.source_function.dfg.instruction_results(call_id);
         let arguments = vecmap(arguments, |arg| self.translate_value(*arg));
+
+        let mut call_stack = self.translate_call_stack(call_id);
+        call_stack.extend(self.source_function.dfg.location(call_id));
+        let caller_call_stack = std::mem::replace(&mut self.context.call_stack, call_stack);
         let new_results = self.context.inline_function(ssa, function, &arguments);
+        self.context.call_stack = caller_call_stack;
+
         let new_results = InsertInstructionResult::Results(&new_results);
         Self::insert_new_instruction_results(&mut self.values, old_results, new_results);
     }
@@ -386,8 +412,36 @@ impl<'function> PerFunctionContext<'function> {
             .requires_ctrl_typevars()
             .then(|| vecmap(&results, |result| self.source_function.dfg.type_of_value(*result)));
 
-        let new_results = self.context.builder.insert_instruction(instruction, ctrl_typevars);
+        // Instructions keep their origin if they were already inlined from elsewhere
+        let origin = match self.source_function.dfg.origin(id) {
+            Some(origin) => Some(origin.clone()),
+            None if self.inlining_main => None,
+            None => Some(self.source_name.clone()),
+        };
+
+        let location = self.source_function.dfg.location(id);
+        let call_stack = self.translate_call_stack(id);
+        let source = InstructionSource { origin, location, call_stack };
+
+        let new_results =
+            self.context.builder.insert_instruction_with_source(instruction, ctrl_typevars, source);
         Self::insert_new_instruction_results(&mut self.values, &results, new_results);
+
+        for result in &results {
+            let name = self.source_function.dfg.variable_name(*result);
+            if let (Some(name), Some(new_result)) = (name, self.values.get(result)) {
+                self.context.builder.name_value(*new_result, name.clone());
+            }
+        }
+    }
+
+    /// Returns the call stack of the given instruction from the source function once it is
+    /// inlined: the calls currently being inlined, followed by any calls it was already
+    /// inlined through.
+    fn translate_call_stack(&self, id: InstructionId) -> CallStack {
+        let mut call_stack = self.context.call_stack.clone();
+        call_stack.append(self.source_function.dfg.call_stack(id));
+        call_stack
     }
 
     /// Modify the values HashMap to remember the mapping between an instruction result's previous
@@ -477,6 +531,8 @@ impl<'function> PerFunctionContext<'function> {
 #[cfg(test)]
 mod test {
     use acvm::FieldElement;
+    use fm::FileId;
+    use noirc_errors::{Location, Span};
 
     use crate::ssa_refactor::{
         ir::{
@@ -520,6 +576,134 @@ mod test {
         assert_eq!(inlined.functions.len(), 1);
     }
 
+    #[test]
+    fn inlined_instructions_remember_their_origin() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = call f1(v0)
+        //     v2 = add v1, v0
+        //     return v2
+        // }
+        // fn square f1 {
+        //   b0(v0: Field):
+        //     v1 = mul v0, v0
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let square_id = Id::test_new(1);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        let square = builder.import_function(square_id);
+        let main_v1 = builder.insert_call(square, vec![main_v0], vec![Type::field()])[0];
+        let main_v2 = builder.insert_binary(main_v1, BinaryOp::Add, main_v0);
+        builder.terminate_with_return(vec![main_v2]);
+
+        builder.new_function("square".into(), square_id);
+        let square_v0 = builder.add_parameter(Type::field());
+        let square_v1 = builder.insert_binary(square_v0, BinaryOp::Mul, square_v0);
+        builder.terminate_with_return(vec![square_v1]);
+
+        let ssa = builder.finish().inline_functions();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 2);
+
+        let origins: Vec<_> =
+            instructions.iter().map(|id| main.dfg.origin(*id).map(|name| name.as_ref())).collect();
+        assert_eq!(origins, vec![Some("square"), None]);
+    }
+
+    #[test]
+    fn inlined_instructions_record_their_call_stack() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = call f1(v0)    // at 1
+        //     return v1
+        // }
+        // fn outer f1 {
+        //   b0(v0: Field):
+        //     v1 = call f2(v0)    // at 2
+        //     return v1
+        // }
+        // fn square f2 {
+        //   b0(v0: Field):
+        //     v1 = mul v0, v0     // at 3
+        //     return v1
+        // }
+        let location = |position| Location::new(Span::single_char(position), FileId::dummy());
+        let main_id = Id::test_new(0);
+        let outer_id = Id::test_new(1);
+        let square_id = Id::test_new(2);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        let outer = builder.import_function(outer_id);
+        builder.set_location(location(1));
+        let main_v1 = builder.insert_call(outer, vec![main_v0], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![main_v1]);
+
+        builder.new_function("outer".into(), outer_id);
+        let outer_v0 = builder.add_parameter(Type::field());
+        let square = builder.import_function(square_id);
+        builder.set_location(location(2));
+        let outer_v1 = builder.insert_call(square, vec![outer_v0], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![outer_v1]);
+
+        builder.new_function("square".into(), square_id);
+        let square_v0 = builder.add_parameter(Type::field());
+        builder.set_location(location(3));
+        let square_v1 = builder.insert_binary(square_v0, BinaryOp::Mul, square_v0);
+        builder.terminate_with_return(vec![square_v1]);
+
+        let ssa = builder.finish().inline_functions();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+
+        assert_eq!(main.dfg.location(instructions[0]), Some(location(3)));
+        let call_stack: Vec<_> = main.dfg.call_stack(instructions[0]).into_iter().collect();
+        assert_eq!(call_stack, vec![location(1), location(2)]);
+    }
+
+    #[test]
+    fn inlined_values_keep_their_variable_names() {
+        // fn main f0 {
+        //   b0(x: Field):
+        //     v1 = call f1(x)
+        //     return v1
+        // }
+        // fn square f1 {
+        //   b0(v0: Field):
+        //     squared = mul v0, v0
+        //     return squared
+        // }
+        let main_id = Id::test_new(0);
+        let square_id = Id::test_new(1);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        builder.name_value(main_v0, "x".into());
+        let square = builder.import_function(square_id);
+        let main_v1 = builder.insert_call(square, vec![main_v0], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![main_v1]);
+
+        builder.new_function("square".into(), square_id);
+        let square_v0 = builder.add_parameter(Type::field());
+        let square_v1 = builder.insert_binary(square_v0, BinaryOp::Mul, square_v0);
+        builder.name_value(square_v1, "squared".into());
+        builder.terminate_with_return(vec![square_v1]);
+
+        let ssa = builder.finish().inline_functions();
+        let main = ssa.main();
+        let parameter = main.parameters()[0];
+        assert_eq!(main.dfg.variable_name(parameter).map(|name| name.as_ref()), Some("x"));
+
+        let instructions = main.dfg[main.entry_block()].instructions();
+        let result = main.dfg.instruction_results(instructions[0])[0];
+        assert_eq!(main.dfg.variable_name(result).map(|name| name.as_ref()), Some("squared"));
+    }
+
     #[test]
     fn complex_inlining() {
         // This SSA is from issue #1327 which previously failed to inline properly
@@ -0,0 +1,246 @@
+//! Loop-invariant code motion (LICM) moves instructions which compute the same value on every
+//! iteration of a loop out of the loop, into the block which enters it. Run before unrolling,
+//! this computes each such value once rather than once per unrolled iteration.
+//!
+//! A hoisted instruction is executed even when the loop body is not, so only instructions
+//! without side effects which cannot fail are hoisted. An instruction is invariant when each of
+//! its operands is defined outside of the loop, or is the result of another hoisted instruction.
+use std::collections::HashSet;
+
+use crate::ssa_refactor::{
+    ir::{
+        basic_block::BasicBlockId,
+        cfg::ControlFlowGraph,
+        dfg::DataFlowGraph,
+        dom::DominatorTree,
+        function::Function,
+        instruction::{BinaryOp, Instruction, InstructionId},
+        value::ValueId,
+    },
+    ssa_gen::Ssa,
+};
+
+use super::unrolling::{find_loops, Loop};
+
+impl Ssa {
+    /// Hoists the loop invariant instructions of each loop into the loop's pre-header.
+    pub(crate) fn loop_invariant_code_motion(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            loop_invariant_code_motion(function);
+        }
+        self
+    }
+}
+
+fn loop_invariant_code_motion(function: &mut Function) {
+    let cfg = ControlFlowGraph::with_function(function);
+    let mut dom_tree = DominatorTree::with_function(function);
+
+    // Inner loops come first, so that instructions hoisted out of an inner loop can then be
+    // hoisted out of the loops enclosing it.
+    for loop_ in find_loops(function, &cfg, &mut dom_tree) {
+        if let Some(pre_header) = get_pre_header(&cfg, &loop_) {
+            hoist_loop_invariants(function, &dom_tree, &loop_, pre_header);
+        }
+    }
+}
+
+/// Returns the block outside of the loop which enters it, if there is exactly one.
+pub(super) fn get_pre_header(cfg: &ControlFlowGraph, loop_: &Loop) -> Option<BasicBlockId> {
+    let mut predecessors =
+        cfg.predecessors(loop_.header).filter(|predecessor| !loop_.blocks.contains(predecessor));
+    let pre_header = predecessors.next()?;
+    predecessors.next().is_none().then_some(pre_header)
+}
+
+fn hoist_loop_invariants(
+    function: &mut Function,
+    dom_tree: &DominatorTree,
+    loop_: &Loop,
+    pre_header: BasicBlockId,
+) {
+    let dfg = &function.dfg;
+    let mut defined_in_loop: HashSet<ValueId> = HashSet::new();
+    for block in &loop_.blocks {
+        defined_in_loop.extend(dfg.block_parameters(*block));
+        for instruction in dfg[*block].instructions() {
+            defined_in_loop.extend(dfg.instruction_results(*instruction));
+        }
+    }
+
+    // Visiting the blocks in reverse post order visits each instruction after those defining
+    // its operands, so chains of invariant instructions are hoisted in order.
+    let mut blocks: Vec<_> = loop_.blocks.iter().copied().collect();
+    blocks.sort_by(|a, b| dom_tree.reverse_post_order_cmp(*a, *b));
+
+    for block in blocks {
+        let instructions = std::mem::take(function.dfg[block].instructions_mut());
+        for instruction in instructions {
+            if is_loop_invariant(&function.dfg, instruction, &defined_in_loop) {
+                for result in function.dfg.instruction_results(instruction) {
+                    defined_in_loop.remove(result);
+                }
+                function.dfg[pre_header].insert_instruction(instruction);
+            } else {
+                function.dfg[block].insert_instruction(instruction);
+            }
+        }
+    }
+}
+
+fn is_loop_invariant(
+    dfg: &DataFlowGraph,
+    instruction: InstructionId,
+    defined_in_loop: &HashSet<ValueId>,
+) -> bool {
+    let instruction = &dfg[instruction];
+    let mut operands_are_invariant = true;
+    instruction.for_each_value(|value| {
+        operands_are_invariant &= !defined_in_loop.contains(&dfg.resolve(value));
+    });
+    operands_are_invariant && can_be_hoisted(instruction, dfg)
+}
+
+/// Returns true if an instruction has no side effects and cannot fail, so that executing it
+/// when the loop body would not have been executed is harmless.
+fn can_be_hoisted(instruction: &Instruction, dfg: &DataFlowGraph) -> bool {
+    match instruction {
+        Instruction::Binary(binary) => match binary.operator {
+            BinaryOp::Div | BinaryOp::Mod => {
+                dfg.get_numeric_constant(binary.rhs).map_or(false, |rhs| !rhs.is_zero())
+            }
+            _ => true,
+        },
+        Instruction::Cast(..) | Instruction::Not(_) | Instruction::Truncate { .. } => true,
+        Instruction::Constrain(_)
+        | Instruction::Call { .. }
+        | Instruction::Allocate
+        | Instruction::Load { .. }
+        | Instruction::Store { .. }
+        | Instruction::EnableSideEffects { .. }
+        | Instruction::ArrayGet { .. }
+        | Instruction::ArraySet { .. } => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa_refactor::{parser::assert_ssa_equals, ssa_gen::Ssa};
+
+    #[test]
+    fn hoists_invariant_instructions_out_of_nested_loops() {
+        // for i in 0..3 { for j in 0..4 { assert(x * y + i != j / 0) } }
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field, v1: Field):
+                jmp b1(Field 0)
+              b1(v2: Field):
+                v3 = lt v2, Field 3
+                jmpif v3 then: b2, else: b3
+              b2():
+                jmp b4(Field 0)
+              b3():
+                return
+              b4(v4: Field):
+                v5 = lt v4, Field 4
+                jmpif v5 then: b5, else: b6
+              b5():
+                v6 = mul v0, v1
+                v7 = add v6, v2
+                v8 = div v4, Field 0
+                v9 = eq v7, v8
+                v10 = not v9
+                constrain v10
+                v11 = add v4, Field 1
+                jmp b4(v11)
+              b6():
+                v12 = add v2, Field 1
+                jmp b1(v12)
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        // The product is hoisted out of both loops, while the sum only out of the inner one.
+        // The division may fail, so it is never hoisted.
+        let expected = "
+            acir fn main f0 {
+              b0(v0: Field, v1: Field):
+                v2 = mul v0, v1
+                jmp b1(Field 0)
+              b1(v3: Field):
+                v4 = lt v3, Field 3
+                jmpif v4 then: b2, else: b3
+              b2():
+                v5 = add v2, v3
+                jmp b4(Field 0)
+              b3():
+                return
+              b4(v6: Field):
+                v7 = lt v6, Field 4
+                jmpif v7 then: b5, else: b6
+              b5():
+                v8 = div v6, Field 0
+                v9 = eq v5, v8
+                v10 = not v9
+                constrain v10
+                v11 = add v6, Field 1
+                jmp b4(v11)
+              b6():
+                v12 = add v3, Field 1
+                jmp b1(v12)
+            }
+        ";
+        assert_ssa_equals(&ssa.loop_invariant_code_motion(), expected);
+    }
+
+    #[test]
+    fn keeps_array_reads_in_the_loop() {
+        // The round constants of `poseidon`, offset by the round number `r` of an outer loop:
+        // for i in 0..3 { state[i] += ark[r * 3 + i] }
+        let src = "
+            acir fn main f0 {
+              b0(v0: [Field; 3], v1: [Field; 9], v2: u32):
+                jmp b1(u32 0, v0)
+              b1(v3: u32, v4: [Field; 3]):
+                v5 = lt v3, u32 3
+                jmpif v5 then: b2, else: b3
+              b2():
+                v6 = mul v2, u32 3
+                v7 = add v6, v3
+                v8 = array_get v1, index v7 -> Field
+                v9 = array_get v4, index v3 -> Field
+                v10 = add v9, v8
+                v11 = array_set v4, index v3, value v10
+                v12 = add v3, u32 1
+                jmp b1(v12, v11)
+              b3():
+                return v4
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        // The offset of the round is hoisted, while the reads may be out of bounds when the loop
+        // body is not executed, so they stay in the loop.
+        let expected = "
+            acir fn main f0 {
+              b0(v0: [Field; 3], v1: [Field; 9], v2: u32):
+                v3 = mul v2, u32 3
+                jmp b1(u32 0, v0)
+              b1(v4: u32, v5: [Field; 3]):
+                v6 = lt v4, u32 3
+                jmpif v6 then: b2, else: b3
+              b2():
+                v7 = add v3, v4
+                v8 = array_get v1, index v7 -> Field
+                v9 = array_get v5, index v4 -> Field
+                v10 = add v9, v8
+                v11 = array_set v5, index v4, value v10
+                v12 = add v4, u32 1
+                jmp b1(v12, v11)
+              b3():
+                return v5
+            }
+        ";
+        assert_ssa_equals(&ssa.loop_invariant_code_motion(), expected);
+    }
+}
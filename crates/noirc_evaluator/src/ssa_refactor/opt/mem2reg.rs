@@ -191,7 +191,9 @@ mod tests {
             map::Id,
             types::Type,
         },
+        parser::assert_ssa_equals,
         ssa_builder::FunctionBuilder,
+        ssa_gen::Ssa,
     };
 
     #[test]
@@ -393,4 +395,38 @@ mod tests {
             _ => unreachable!(),
         };
     }
+
+    #[test]
+    fn substitutes_loads_of_values_stored_in_the_same_block() {
+        let src = "
+            acir fn main f0 {
+              b0():
+                v0 = allocate
+                store Field 5 at v0
+                v1 = load v0 -> Field
+                jmp b1(v1)
+              b1(v2: Field):
+                v3 = load v0 -> Field
+                store Field 6 at v0
+                v4 = load v0 -> Field
+                return v2, v3, v4
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        // The load in b1 which reads the store from b0 is kept, along with the stores it needs.
+        let expected = "
+            acir fn main f0 {
+              b0():
+                v0 = allocate
+                store Field 5 at v0
+                jmp b1(Field 5)
+              b1(v1: Field):
+                v2 = load v0 -> Field
+                store Field 6 at v0
+                return v1, v2, Field 6
+            }
+        ";
+        assert_ssa_equals(&ssa.mem2reg(), expected);
+    }
 }
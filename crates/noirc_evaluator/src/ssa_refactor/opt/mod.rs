@@ -7,7 +7,11 @@ mod constant_folding;
 mod defunctionalize;
 mod die;
 mod flatten_cfg;
+mod gvn;
 mod inlining;
+mod licm;
 mod mem2reg;
+mod remove_truncates;
 mod simplify_cfg;
+mod strength_reduction;
 mod unrolling;
@@ -0,0 +1,108 @@
+//! This pass removes each `truncate` whose value is already known to fit within the bit size it
+//! is truncated to, such as the sum of two values cast from `u8` into `u32`. Truncations which
+//! remain have their `max_bit_size` lowered to the bit size of their value, when that is known to
+//! be smaller, as the cost of a truncation in ACIR grows with its `max_bit_size`.
+//!
+//! The bit sizes of values are found by [`ValueRanges`].
+use crate::ssa_refactor::{
+    ir::{
+        function::Function,
+        instruction::{Binary, BinaryOp, Instruction},
+        value::{Value, ValueId},
+        value_range::ValueRanges,
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// Removes truncations which are implied by the way their value was computed.
+    pub(crate) fn remove_redundant_truncates(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            remove_redundant_truncates(function);
+        }
+        self
+    }
+}
+
+fn remove_redundant_truncates(function: &mut Function) {
+    let ranges = ValueRanges::with_function(function);
+
+    for block in function.reachable_blocks() {
+        let instructions = std::mem::take(function.dfg[block].instructions_mut());
+        for instruction in instructions {
+            let dfg = &function.dfg;
+            let Instruction::Truncate { value, bit_size, max_bit_size } = dfg[instruction] else {
+                function.dfg[block].insert_instruction(instruction);
+                continue;
+            };
+
+            // A subtraction has the integer modulus added to it before it is truncated in ACIR,
+            // so its `max_bit_size` accounts for more than the value itself.
+            if is_subtraction(function, value) {
+                function.dfg[block].insert_instruction(instruction);
+                continue;
+            }
+
+            let value_bit_size = ranges.max_bit_size(dfg, value);
+            if value_bit_size <= bit_size {
+                let result = dfg.instruction_results(instruction)[0];
+                function.dfg.set_value_from_id(result, value);
+            } else {
+                let max_bit_size = std::cmp::min(max_bit_size, value_bit_size);
+                function.dfg[instruction] = Instruction::Truncate { value, bit_size, max_bit_size };
+                function.dfg[block].insert_instruction(instruction);
+            }
+        }
+    }
+}
+
+fn is_subtraction(function: &Function, value: ValueId) -> bool {
+    let dfg = &function.dfg;
+    match &dfg[dfg.resolve(value)] {
+        Value::Instruction { instruction, .. } => {
+            matches!(dfg[*instruction], Instruction::Binary(Binary { operator: BinaryOp::Sub, .. }))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa_refactor::{parser::assert_ssa_equals, ssa_gen::Ssa};
+
+    #[test]
+    fn removes_truncates_of_values_which_already_fit() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u8, v1: u8, v2: u32):
+                v3 = cast v0 as u32
+                v4 = cast v1 as u32
+                v5 = add v3, v4
+                v6 = truncate v5 to 32 bits, max_bit_size: 33
+                v7 = mul v6, v2
+                v8 = truncate v7 to 32 bits, max_bit_size: 64
+                v9 = sub v2, v6
+                v10 = truncate v9 to 32 bits, max_bit_size: 33
+                return v8, v10
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        // The product of a 9 bit value and a 32 bit one still needs truncating, but occupies at
+        // most 41 bits.
+        let expected = "
+            acir fn main f0 {
+              b0(v0: u8, v1: u8, v2: u32):
+                v3 = cast v0 as u32
+                v4 = cast v1 as u32
+                v5 = add v3, v4
+                v6 = mul v5, v2
+                v7 = truncate v6 to 32 bits, max_bit_size: 41
+                v8 = sub v2, v5
+                v9 = truncate v8 to 32 bits, max_bit_size: 33
+                return v7, v9
+            }
+        ";
+        assert_ssa_equals(&ssa.remove_redundant_truncates(), expected);
+    }
+}
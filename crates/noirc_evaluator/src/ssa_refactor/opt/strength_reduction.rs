@@ -0,0 +1,303 @@
+//! Strength reduction replaces each multiplication of a loop's induction variable by a constant,
+//! `i * c`, with a new induction variable which starts at `start * c` and is incremented by
+//! `step * c` on each iteration of the loop. This trades a multiplication on every iteration for
+//! an addition, and leaves a loop which fails to unroll cheaper to execute.
+//!
+//! Only loops in the shape generated for `for` loops are reduced: a header with the induction
+//! variable as its first parameter, entered from a single pre-header, and a single back edge
+//! which increments the induction variable by a constant step.
+use std::collections::HashMap;
+
+use acvm::FieldElement;
+
+use crate::ssa_refactor::{
+    ir::{
+        basic_block::BasicBlockId,
+        cfg::ControlFlowGraph,
+        dfg::DataFlowGraph,
+        dom::DominatorTree,
+        function::Function,
+        instruction::{Binary, BinaryOp, Instruction, InstructionId, TerminatorInstruction},
+        value::ValueId,
+    },
+    ssa_gen::Ssa,
+};
+
+use super::{
+    licm::get_pre_header,
+    unrolling::{find_loops, Loop},
+};
+
+impl Ssa {
+    /// Replaces multiplications of each loop's induction variable by a constant with additions.
+    pub(crate) fn strength_reduction(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            strength_reduction(function);
+        }
+        self
+    }
+}
+
+fn strength_reduction(function: &mut Function) {
+    let cfg = ControlFlowGraph::with_function(function);
+    let mut dom_tree = DominatorTree::with_function(function);
+
+    for loop_ in find_loops(function, &cfg, &mut dom_tree) {
+        if cfg.predecessors(loop_.header).count() != 2 {
+            continue;
+        }
+        let Some(pre_header) = get_pre_header(&cfg, &loop_) else {
+            continue;
+        };
+        if let Some(induction_variable) = find_induction_variable(&function.dfg, &loop_) {
+            reduce_multiplications(function, &loop_, pre_header, induction_variable);
+        }
+    }
+}
+
+/// A loop's induction variable `i`, which takes the value `start` on entry to the loop and is
+/// incremented by the constant `step` on each iteration.
+struct InductionVariable {
+    value: ValueId,
+    step: FieldElement,
+}
+
+/// Returns the first parameter of the loop's header if it is incremented by a constant on each
+/// iteration of the loop.
+fn find_induction_variable(dfg: &DataFlowGraph, loop_: &Loop) -> Option<InductionVariable> {
+    let value = *dfg.block_parameters(loop_.header).first()?;
+    let Some(TerminatorInstruction::Jmp { arguments, .. }) =
+        dfg[loop_.back_edge_start].terminator()
+    else {
+        return None;
+    };
+
+    let next_value = dfg.resolve(*arguments.first()?);
+    let instruction = dfg[loop_.back_edge_start]
+        .instructions()
+        .iter()
+        .find(|instruction| dfg.instruction_results(**instruction).contains(&next_value))?;
+    let Instruction::Binary(Binary { lhs, operator: BinaryOp::Add, rhs }) = &dfg[*instruction]
+    else {
+        return None;
+    };
+
+    let (lhs, rhs) = (dfg.resolve(*lhs), dfg.resolve(*rhs));
+    let step = if lhs == value {
+        dfg.get_numeric_constant(rhs)
+    } else if rhs == value {
+        dfg.get_numeric_constant(lhs)
+    } else {
+        None
+    };
+    step.map(|step| InductionVariable { value, step })
+}
+
+/// Returns the constant the induction variable is multiplied by, if `instruction` is such a
+/// multiplication.
+fn multiplication_by_constant(
+    dfg: &DataFlowGraph,
+    instruction: InstructionId,
+    induction_variable: &InductionVariable,
+) -> Option<ValueId> {
+    let Instruction::Binary(Binary { lhs, operator: BinaryOp::Mul, rhs }) = &dfg[instruction]
+    else {
+        return None;
+    };
+    let (lhs, rhs) = (dfg.resolve(*lhs), dfg.resolve(*rhs));
+    if lhs == induction_variable.value && dfg.get_numeric_constant(rhs).is_some() {
+        Some(rhs)
+    } else if rhs == induction_variable.value && dfg.get_numeric_constant(lhs).is_some() {
+        Some(lhs)
+    } else {
+        None
+    }
+}
+
+fn reduce_multiplications(
+    function: &mut Function,
+    loop_: &Loop,
+    pre_header: BasicBlockId,
+    induction_variable: InductionVariable,
+) {
+    let dfg = &function.dfg;
+    let Some(TerminatorInstruction::Jmp { arguments, .. }) = dfg[pre_header].terminator() else {
+        return;
+    };
+    let start = arguments[0];
+
+    let mut multiplications = Vec::new();
+    for block in &loop_.blocks {
+        for instruction in dfg[*block].instructions() {
+            if let Some(constant) =
+                multiplication_by_constant(dfg, *instruction, &induction_variable)
+            {
+                multiplications.push((*block, *instruction, constant));
+            }
+        }
+    }
+
+    // The induction variable replacing the multiplications by each constant
+    let mut reduced_values: HashMap<ValueId, ValueId> = HashMap::new();
+
+    for (block, instruction, constant) in multiplications {
+        let dfg = &mut function.dfg;
+        let result = dfg.instruction_results(instruction)[0];
+        dfg[block].instructions_mut().retain(|existing| *existing != instruction);
+
+        if let Some(reduced) = reduced_values.get(&constant) {
+            dfg.set_value_from_id(result, *reduced);
+            continue;
+        }
+        let typ = dfg.type_of_value(result);
+
+        // The value of `i * c` on entry to the loop, passed to the header by the pre-header
+        let initial = Instruction::binary(BinaryOp::Mul, start, constant);
+        let initial = dfg.insert_instruction_and_results(initial, pre_header, None).first();
+
+        // The value of `i * c` on the next iteration, passed to the header by the back edge.
+        // Like the increment of `i` itself, this increment is not checked for overflow.
+        let reduced = dfg.add_block_parameter(loop_.header, typ.clone());
+        let step = dfg.get_numeric_constant(constant).unwrap() * induction_variable.step;
+        let step = dfg.make_constant(step, typ);
+        let next = Instruction::binary(BinaryOp::Add, reduced, step);
+        let next = dfg.insert_instruction_and_results(next, loop_.back_edge_start, None).first();
+
+        push_jmp_argument(dfg, pre_header, initial);
+        push_jmp_argument(dfg, loop_.back_edge_start, next);
+
+        dfg.set_value_from_id(result, reduced);
+        reduced_values.insert(constant, reduced);
+    }
+}
+
+fn push_jmp_argument(dfg: &mut DataFlowGraph, block: BasicBlockId, argument: ValueId) {
+    match dfg[block].unwrap_terminator_mut() {
+        TerminatorInstruction::Jmp { arguments, .. } => arguments.push(argument),
+        other => unreachable!("Expected a jmp to the loop header, found {other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa_refactor::{parser::assert_ssa_equals, ssa_gen::Ssa};
+
+    #[test]
+    fn replaces_multiplications_of_the_induction_variable() {
+        // for i in 1..4 { assert(i * 3 == x) }
+        let src = "
+            acir fn main f0 {
+              b0(v0: u32):
+                jmp b1(u32 1)
+              b1(v1: u32):
+                v2 = lt v1, u32 4
+                jmpif v2 then: b2, else: b3
+              b2():
+                v3 = mul v1, u32 3
+                v4 = eq v3, v0
+                constrain v4
+                v5 = add v1, u32 1
+                jmp b1(v5)
+              b3():
+                return
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: u32):
+                jmp b1(u32 1, u32 3)
+              b1(v1: u32, v2: u32):
+                v3 = lt v1, u32 4
+                jmpif v3 then: b2, else: b3
+              b2():
+                v4 = eq v2, v0
+                constrain v4
+                v5 = add v1, u32 1
+                v6 = add v2, u32 3
+                jmp b1(v5, v6)
+              b3():
+                return
+            }
+        ";
+        assert_ssa_equals(&ssa.strength_reduction(), expected);
+    }
+
+    #[test]
+    fn leaves_loops_without_a_constant_step() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u32):
+                jmp b1(u32 0)
+              b1(v1: u32):
+                v2 = lt v1, u32 4
+                jmpif v2 then: b2, else: b3
+              b2():
+                v3 = mul v1, u32 3
+                v4 = add v1, v0
+                jmp b1(v4)
+              b3():
+                return
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        assert_ssa_equals(&ssa.strength_reduction(), src);
+    }
+
+    #[test]
+    fn shares_the_reduced_value_of_equal_constants() {
+        // The byte offsets of `sha256`'s message words:
+        // for i in 0..4 { assert(msg[i * 4] + msg[i * 4 + 1] == x[i * 2]) }
+        let src = "
+            acir fn main f0 {
+              b0(v0: [u8; 16], v1: [u8; 8]):
+                jmp b1(u32 0)
+              b1(v2: u32):
+                v3 = lt v2, u32 4
+                jmpif v3 then: b2, else: b3
+              b2():
+                v4 = mul v2, u32 4
+                v5 = array_get v0, index v4 -> u8
+                v6 = mul u32 4, v2
+                v7 = add v6, u32 1
+                v8 = array_get v0, index v7 -> u8
+                v9 = add v5, v8
+                v10 = mul v2, u32 2
+                v11 = array_get v1, index v10 -> u8
+                v12 = eq v9, v11
+                constrain v12
+                v13 = add v2, u32 1
+                jmp b1(v13)
+              b3():
+                return
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: [u8; 16], v1: [u8; 8]):
+                jmp b1(u32 0, u32 0, u32 0)
+              b1(v4: u32, v5: u32, v6: u32):
+                v7 = lt v4, u32 4
+                jmpif v7 then: b2, else: b3
+              b2():
+                v8 = array_get v0, index v5 -> u8
+                v9 = add v5, u32 1
+                v10 = array_get v0, index v9 -> u8
+                v11 = add v8, v10
+                v12 = array_get v1, index v6 -> u8
+                v13 = eq v11, v12
+                constrain v13
+                v14 = add v4, u32 1
+                v15 = add v5, u32 4
+                v16 = add v6, u32 2
+                jmp b1(v14, v15, v16)
+              b3():
+                return
+            }
+        ";
+        assert_ssa_equals(&ssa.strength_reduction(), expected);
+    }
+}
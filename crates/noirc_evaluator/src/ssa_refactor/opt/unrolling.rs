@@ -34,17 +34,17 @@ impl Ssa {
     }
 }
 
-struct Loop {
+pub(super) struct Loop {
     /// The header block of a loop is the block which dominates all the
     /// other blocks in the loop.
-    header: BasicBlockId,
+    pub(super) header: BasicBlockId,
 
     /// The start of the back_edge n -> d is the block n at the end of
     /// the loop that jumps back to the header block d which restarts the loop.
-    back_edge_start: BasicBlockId,
+    pub(super) back_edge_start: BasicBlockId,
 
     /// All the blocks contained within the loop, including `header` and `back_edge_start`.
-    blocks: HashSet<BasicBlockId>,
+    pub(super) blocks: HashSet<BasicBlockId>,
 }
 
 struct Loops {
@@ -64,7 +64,24 @@ fn find_all_loops(function: &Function) -> Loops {
     let cfg = ControlFlowGraph::with_function(function);
     let post_order = PostOrder::with_function(function);
     let mut dom_tree = DominatorTree::with_cfg_and_post_order(&cfg, &post_order);
+    let loops = find_loops(function, &cfg, &mut dom_tree);
+
+    Loops {
+        failed_to_unroll: HashSet::new(),
+        yet_to_unroll: loops,
+        modified_blocks: HashSet::new(),
+        cfg,
+        dom_tree,
+    }
+}
 
+/// Returns each loop in the function, ordered from the fewest to the most blocks, so that
+/// the inner loops of nested loops come before their outer loops.
+pub(super) fn find_loops(
+    function: &Function,
+    cfg: &ControlFlowGraph,
+    dom_tree: &mut DominatorTree,
+) -> Vec<Loop> {
     let mut loops = vec![];
 
     for (block, _) in function.dfg.basic_blocks_iter() {
@@ -73,7 +90,7 @@ fn find_all_loops(function: &Function) -> Loops {
             for predecessor in cfg.predecessors(block) {
                 if dom_tree.is_reachable(predecessor) && dom_tree.dominates(block, predecessor) {
                     // predecessor -> block is the back-edge of a loop
-                    loops.push(find_blocks_in_loop(block, predecessor, &cfg));
+                    loops.push(find_blocks_in_loop(block, predecessor, cfg));
                 }
             }
         }
@@ -83,14 +100,7 @@ fn find_all_loops(function: &Function) -> Loops {
     // This is needed because inner loops may use the induction variable from their outer loops in
     // their loop range.
     loops.sort_by_key(|loop_| loop_.blocks.len());
-
-    Loops {
-        failed_to_unroll: HashSet::new(),
-        yet_to_unroll: loops,
-        modified_blocks: HashSet::new(),
-        cfg,
-        dom_tree,
-    }
+    loops
 }
 
 impl Loops {
@@ -153,12 +163,12 @@ fn find_blocks_in_loop(
 /// Returns Err(()) if it failed to unroll and Ok(()) otherwise.
 fn unroll_loop(function: &mut Function, cfg: &ControlFlowGraph, loop_: &Loop) -> Result<(), ()> {
     let mut unroll_into = get_pre_header(cfg, loop_);
-    let mut jump_value = get_induction_variable(function, unroll_into)?;
+    let mut jump_values = get_induction_variables(function, unroll_into)?;
 
-    while let Some(context) = unroll_loop_header(function, loop_, unroll_into, jump_value)? {
-        let (last_block, last_value) = context.unroll_loop_iteration();
+    while let Some(context) = unroll_loop_header(function, loop_, unroll_into, jump_values)? {
+        let (last_block, last_values) = context.unroll_loop_iteration();
         unroll_into = last_block;
-        jump_value = last_value;
+        jump_values = last_values;
     }
 
     Ok(())
@@ -177,21 +187,21 @@ fn get_pre_header(cfg: &ControlFlowGraph, loop_: &Loop) -> BasicBlockId {
     pre_header.remove(0)
 }
 
-/// Return the induction value of the current iteration of the loop, from the given block's jmp arguments.
+/// Return the induction values of the current iteration of the loop, from the given block's jmp arguments.
 ///
-/// Expects the current block to terminate in `jmp h(N)` where h is the loop header and N is
-/// a Field value.
-fn get_induction_variable(function: &Function, block: BasicBlockId) -> Result<ValueId, ()> {
+/// Expects the current block to terminate in `jmp h(N, ..)` where h is the loop header and each
+/// argument is a constant. The first is the loop's induction variable, and any others are
+/// derived from it by strength reduction.
+fn get_induction_variables(function: &Function, block: BasicBlockId) -> Result<Vec<ValueId>, ()> {
     match function.dfg[block].terminator() {
         Some(TerminatorInstruction::Jmp { arguments, .. }) => {
             // This assumption will no longer be valid if e.g. mutable variables are represented as
             // block parameters. If that becomes the case we'll need to figure out which variable
             // is generally constant and increasing to guess which parameter is the induction
             // variable.
-            assert_eq!(arguments.len(), 1, "It is expected that a loop's induction variable is the only block parameter of the loop header");
-            let value = arguments[0];
-            if function.dfg.get_numeric_constant(value).is_some() {
-                Ok(value)
+            assert!(!arguments.is_empty(), "It is expected that a loop's induction variable is the first block parameter of the loop header");
+            if arguments.iter().all(|value| function.dfg.get_numeric_constant(*value).is_some()) {
+                Ok(arguments.clone())
             } else {
                 Err(())
             }
@@ -207,7 +217,7 @@ fn unroll_loop_header<'a>(
     function: &'a mut Function,
     loop_: &'a Loop,
     unroll_into: BasicBlockId,
-    induction_value: ValueId,
+    induction_values: Vec<ValueId>,
 ) -> Result<Option<LoopIteration<'a>>, ()> {
     // We insert into a fresh block first and move instructions into the unroll_into block later
     // only once we verify the jmpif instruction has a constant condition. If it does not, we can
@@ -215,12 +225,17 @@ fn unroll_loop_header<'a>(
     let fresh_block = function.dfg.make_block();
 
     let mut context = LoopIteration::new(function, loop_, fresh_block, loop_.header);
-    let source_block = &context.dfg()[context.source_block];
-    assert_eq!(source_block.parameters().len(), 1, "Expected only 1 argument in loop header");
-
-    // Insert the current value of the loop induction variable into our context.
-    let first_param = source_block.parameters()[0];
-    context.inserter.try_map_value(first_param, induction_value);
+    let parameters = context.dfg()[context.source_block].parameters().to_vec();
+    assert_eq!(
+        parameters.len(),
+        induction_values.len(),
+        "Expected an argument for each parameter of the loop header"
+    );
+
+    // Insert the current values of the loop induction variables into our context.
+    for (parameter, induction_value) in parameters.into_iter().zip(induction_values) {
+        context.inserter.try_map_value(parameter, induction_value);
+    }
     context.inline_instructions_from_block();
 
     match context.dfg()[fresh_block].unwrap_terminator() {
@@ -264,11 +279,11 @@ struct LoopIteration<'f> {
     insert_block: BasicBlockId,
     source_block: BasicBlockId,
 
-    /// The induction value (and the block it was found in) is the new value for
-    /// the variable traditionally called `i` on each iteration of the loop.
-    /// This is None until we visit the block which jumps back to the start of the
-    /// loop, at which point we record its value and the block it was found in.
-    induction_value: Option<(BasicBlockId, ValueId)>,
+    /// The induction values (and the block they were found in) are the new values for
+    /// the variable traditionally called `i`, and any variables derived from it, on each
+    /// iteration of the loop. This is None until we visit the block which jumps back to the
+    /// start of the loop, at which point we record its values and the block it was found in.
+    induction_value: Option<(BasicBlockId, Vec<ValueId>)>,
 }
 
 impl<'f> LoopIteration<'f> {
@@ -296,7 +311,7 @@ impl<'f> LoopIteration<'f> {
     /// It is expected the terminator instructions are set up to branch into an empty block
     /// for further unrolling. When the loop is finished this will need to be mutated to
     /// jump to the end of the loop instead.
-    fn unroll_loop_iteration(mut self) -> (BasicBlockId, ValueId) {
+    fn unroll_loop_iteration(mut self) -> (BasicBlockId, Vec<ValueId>) {
         let mut next_blocks = self.unroll_loop_block();
 
         while let Some(block) = next_blocks.pop() {
@@ -334,8 +349,7 @@ impl<'f> LoopIteration<'f> {
             }
             TerminatorInstruction::Jmp { destination, arguments } => {
                 if self.get_original_block(*destination) == self.loop_.header {
-                    assert_eq!(arguments.len(), 1);
-                    self.induction_value = Some((self.insert_block, arguments[0]));
+                    self.induction_value = Some((self.insert_block, arguments.clone()));
                 }
                 vec![*destination]
             }
@@ -0,0 +1,782 @@
+//! This module parses the human-readable form of the SSA IR, as displayed by
+//! [`printer`][super::ir::printer], back into an [`Ssa`]. This lets passes be tested against
+//! text snapshots of their input and expected output, and lets bugs be reproduced from the
+//! output of `--show-ssa`.
+//!
+//! Instructions are inserted exactly as written: unlike the [`FunctionBuilder`][builder], the
+//! parser never simplifies them, so the input of a pass is the program the test describes.
+//!
+//! [builder]: super::ssa_builder::FunctionBuilder
+use std::{collections::HashMap, rc::Rc, str::FromStr};
+
+use acvm::FieldElement;
+use thiserror::Error;
+
+use super::{
+    ir::{
+        basic_block::BasicBlockId,
+        function::{Function, RuntimeType},
+        instruction::{Binary, BinaryOp, Instruction, Intrinsic, TerminatorInstruction},
+        map::Id,
+        types::Type,
+        value::ValueId,
+    },
+    ssa_gen::Ssa,
+};
+
+#[derive(Debug, Error)]
+#[error("line {line}: {message}")]
+pub(crate) struct SsaParseError {
+    line: usize,
+    message: String,
+}
+
+impl FromStr for Ssa {
+    type Err = SsaParseError;
+
+    fn from_str(src: &str) -> Result<Ssa, SsaParseError> {
+        let mut functions = Vec::new();
+        let mut current_function: Option<FunctionParser> = None;
+        let mut last_line = 0;
+
+        for (index, text) in src.lines().enumerate() {
+            // Comments are allowed, so that snapshots can explain the program they describe
+            let text = text.split("//").next().unwrap_or_default();
+            let mut line = Line::new(text, index + 1);
+            if line.is_empty() {
+                continue;
+            }
+            last_line = line.number;
+
+            let Some(function) = &mut current_function else {
+                current_function = Some(FunctionParser::from_header(&mut line)?);
+                continue;
+            };
+            if line.eat("}") {
+                line.expect_end()?;
+                let function = current_function.take().expect("a function is being parsed");
+                functions.push(function.finish(&line)?);
+            } else {
+                function.parse_line(&mut line)?;
+            }
+        }
+
+        if current_function.is_some() {
+            let message = "expected `}` to close the function".to_owned();
+            return Err(SsaParseError { line: last_line, message });
+        }
+        if functions.is_empty() {
+            let message = "expected at least one function".to_owned();
+            return Err(SsaParseError { line: last_line, message });
+        }
+        Ok(Ssa::new(functions))
+    }
+}
+
+/// The tokens of a single line of the textual SSA.
+struct Line<'a> {
+    tokens: Vec<&'a str>,
+    position: usize,
+    number: usize,
+}
+
+impl<'a> Line<'a> {
+    fn new(text: &'a str, number: usize) -> Self {
+        let mut tokens = Vec::new();
+        let mut token_start = None;
+        for (index, character) in text.char_indices() {
+            if character.is_whitespace() || "(),:;=[]{}".contains(character) {
+                if let Some(start) = token_start.take() {
+                    tokens.push(&text[start..index]);
+                }
+                if !character.is_whitespace() {
+                    tokens.push(&text[index..index + character.len_utf8()]);
+                }
+            } else if token_start.is_none() {
+                token_start = Some(index);
+            }
+        }
+        if let Some(start) = token_start {
+            tokens.push(&text[start..]);
+        }
+        Self { tokens, position: 0, number }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position == self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn peek_second(&self) -> Option<&'a str> {
+        self.tokens.get(self.position + 1).copied()
+    }
+
+    fn next_token(&mut self) -> Result<&'a str, SsaParseError> {
+        let token = self.peek().ok_or_else(|| self.error("unexpected end of line"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    /// Consumes the next token if it is `expected`, returning whether it was.
+    fn eat(&mut self, expected: &str) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), SsaParseError> {
+        match self.next_token()? {
+            token if token == expected => Ok(()),
+            token => Err(self.error(format!("expected `{expected}`, found `{token}`"))),
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), SsaParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => {
+                Err(self.error(format!("expected the end of the line, found `{token}`")))
+            }
+        }
+    }
+
+    fn parse_number<T: FromStr>(&mut self) -> Result<T, SsaParseError> {
+        let token = self.next_token()?;
+        token.parse().map_err(|_| self.error(format!("expected a number, found `{token}`")))
+    }
+
+    fn parse_type(&mut self) -> Result<Type, SsaParseError> {
+        let token = self.next_token()?;
+        match token {
+            "reference" => Ok(Type::Reference),
+            "function" => Ok(Type::Function),
+            "[" => {
+                let mut element_types = vec![self.parse_type()?];
+                while self.eat(",") {
+                    element_types.push(self.parse_type()?);
+                }
+                if self.eat(";") {
+                    let length = self.parse_number()?;
+                    self.expect("]")?;
+                    Ok(Type::Array(Rc::new(element_types), length))
+                } else {
+                    self.expect("]")?;
+                    Ok(Type::Slice(Rc::new(element_types)))
+                }
+            }
+            _ => numeric_type(token)
+                .ok_or_else(|| self.error(format!("expected a type, found `{token}`"))),
+        }
+    }
+
+    /// Parses a comma separated list of types, e.g. the result types following a `->`.
+    fn parse_types(&mut self) -> Result<Vec<Type>, SsaParseError> {
+        let mut types = vec![self.parse_type()?];
+        while self.eat(",") {
+            types.push(self.parse_type()?);
+        }
+        Ok(types)
+    }
+
+    fn error(&self, message: impl Into<String>) -> SsaParseError {
+        SsaParseError { line: self.number, message: message.into() }
+    }
+}
+
+/// The state of a function being parsed, one line at a time.
+struct FunctionParser<'a> {
+    function: Function,
+    values: HashMap<&'a str, ValueId>,
+    blocks: HashMap<&'a str, BasicBlockId>,
+    /// The names of the blocks which have been referenced before their header was parsed.
+    undefined_blocks: Vec<&'a str>,
+    /// The block instructions are inserted into. This is None after a terminator.
+    current_block: Option<BasicBlockId>,
+}
+
+impl<'a> FunctionParser<'a> {
+    /// Parses a function header, e.g. `acir fn main f0 {`
+    fn from_header(line: &mut Line<'a>) -> Result<Self, SsaParseError> {
+        let runtime = match line.next_token()? {
+            "acir" => RuntimeType::Acir,
+            "brillig" => RuntimeType::Brillig,
+            other => return Err(line.error(format!("expected a runtime, found `{other}`"))),
+        };
+        line.expect("fn")?;
+        let name = line.next_token()?;
+        let id = line.next_token()?;
+        let id = parse_id(id, 'f')
+            .ok_or_else(|| line.error(format!("expected a function id, found `{id}`")))?;
+        line.expect("{")?;
+        line.expect_end()?;
+
+        let mut function = Function::new(name.to_owned(), Id::test_new(id));
+        function.set_runtime(runtime);
+        Ok(Self {
+            function,
+            values: HashMap::new(),
+            blocks: HashMap::new(),
+            undefined_blocks: Vec::new(),
+            current_block: None,
+        })
+    }
+
+    fn finish(self, line: &Line) -> Result<Function, SsaParseError> {
+        if let Some(block) = self.undefined_blocks.first() {
+            return Err(line.error(format!("block `{block}` is never defined")));
+        }
+        if self.current_block.is_some() {
+            return Err(line.error("expected the last block to end with a terminator"));
+        }
+        Ok(self.function)
+    }
+
+    fn parse_line(&mut self, line: &mut Line<'a>) -> Result<(), SsaParseError> {
+        match line.peek() {
+            Some(token) if parse_id(token, 'b').is_some() && line.peek_second() == Some("(") => {
+                self.parse_block_header(line)
+            }
+            _ => self.parse_instruction(line),
+        }
+    }
+
+    /// Parses a block header, e.g. `b1(v2: Field, v3: u1):`
+    fn parse_block_header(&mut self, line: &mut Line<'a>) -> Result<(), SsaParseError> {
+        if self.current_block.is_some() {
+            return Err(line.error("expected the previous block to end with a terminator"));
+        }
+        let name = line.next_token()?;
+        let block = self.block(name);
+        if let Some(index) = self.undefined_blocks.iter().position(|block| *block == name) {
+            self.undefined_blocks.remove(index);
+        } else if !self.function.dfg[block].parameters().is_empty()
+            || self.function.dfg[block].terminator().is_some()
+        {
+            return Err(line.error(format!("block `{name}` is defined twice")));
+        }
+
+        line.expect("(")?;
+        if !line.eat(")") {
+            loop {
+                let name = line.next_token()?;
+                line.expect(":")?;
+                let typ = line.parse_type()?;
+                let parameter = self.function.dfg.add_block_parameter(block, typ);
+                self.define_value(line, name, parameter)?;
+                if !line.eat(",") {
+                    break;
+                }
+            }
+            line.expect(")")?;
+        }
+        line.expect(":")?;
+        line.expect_end()?;
+
+        self.current_block = Some(block);
+        Ok(())
+    }
+
+    fn parse_instruction(&mut self, line: &mut Line<'a>) -> Result<(), SsaParseError> {
+        let block = self.current_block.ok_or_else(|| line.error("expected a block header"))?;
+
+        let mut result_names = Vec::new();
+        if line.peek().and_then(|token| parse_id(token, 'v')).is_some() {
+            result_names.push(line.next_token()?);
+            while line.eat(",") {
+                result_names.push(line.next_token()?);
+            }
+            line.expect("=")?;
+        }
+
+        let keyword = line.next_token()?;
+        if let Some(terminator) = self.parse_terminator(line, keyword)? {
+            if !result_names.is_empty() {
+                return Err(line.error(format!("`{keyword}` does not have results")));
+            }
+            line.expect_end()?;
+            self.function.dfg.set_block_terminator(block, terminator);
+            self.current_block = None;
+            return Ok(());
+        }
+
+        let mut result_types = Vec::new();
+        let instruction = match keyword {
+            "cast" => {
+                let value = self.parse_value(line)?;
+                line.expect("as")?;
+                Instruction::Cast(value, line.parse_type()?)
+            }
+            "not" => Instruction::Not(self.parse_value(line)?),
+            "truncate" => {
+                let value = self.parse_value(line)?;
+                line.expect("to")?;
+                let bit_size = line.parse_number()?;
+                line.expect("bits")?;
+                line.expect(",")?;
+                line.expect("max_bit_size")?;
+                line.expect(":")?;
+                let max_bit_size = line.parse_number()?;
+                Instruction::Truncate { value, bit_size, max_bit_size }
+            }
+            "constrain" => Instruction::Constrain(self.parse_value(line)?),
+            "call" => {
+                let func = self.parse_value(line)?;
+                line.expect("(")?;
+                let arguments = self.parse_values_until(line, ")")?;
+                if line.eat("->") {
+                    result_types = line.parse_types()?;
+                }
+                Instruction::Call { func, arguments }
+            }
+            "allocate" => Instruction::Allocate,
+            "load" => {
+                let address = self.parse_value(line)?;
+                if line.eat("->") {
+                    result_types = line.parse_types()?;
+                }
+                Instruction::Load { address }
+            }
+            "store" => {
+                let value = self.parse_value(line)?;
+                line.expect("at")?;
+                let address = self.parse_value(line)?;
+                Instruction::Store { address, value }
+            }
+            "enable_side_effects" => {
+                Instruction::EnableSideEffects { condition: self.parse_value(line)? }
+            }
+            "array_get" => {
+                let array = self.parse_value(line)?;
+                line.expect(",")?;
+                line.expect("index")?;
+                let index = self.parse_value(line)?;
+                if line.eat("->") {
+                    result_types = line.parse_types()?;
+                }
+                Instruction::ArrayGet { array, index }
+            }
+            "array_set" => {
+                let array = self.parse_value(line)?;
+                line.expect(",")?;
+                line.expect("index")?;
+                let index = self.parse_value(line)?;
+                line.expect(",")?;
+                line.expect("value")?;
+                let value = self.parse_value(line)?;
+                Instruction::ArraySet { array, index, value }
+            }
+            _ => {
+                let operator = binary_operator(keyword)
+                    .ok_or_else(|| line.error(format!("unknown instruction `{keyword}`")))?;
+                let lhs = self.parse_value(line)?;
+                line.expect(",")?;
+                let rhs = self.parse_value(line)?;
+                Instruction::Binary(Binary { lhs, rhs, operator })
+            }
+        };
+        line.expect_end()?;
+
+        let instruction = self.function.dfg.make_instruction(instruction, Some(result_types));
+        self.function.dfg[block].insert_instruction(instruction);
+
+        let results = self.function.dfg.instruction_results(instruction).to_vec();
+        if results.len() != result_names.len() {
+            return Err(line.error(format!(
+                "`{keyword}` has {} results, but {} were named",
+                results.len(),
+                result_names.len()
+            )));
+        }
+        for (name, result) in result_names.into_iter().zip(results) {
+            self.define_value(line, name, result)?;
+        }
+        Ok(())
+    }
+
+    /// Parses the terminator beginning with `keyword`, or returns None if `keyword` does not
+    /// begin a terminator.
+    fn parse_terminator(
+        &mut self,
+        line: &mut Line<'a>,
+        keyword: &str,
+    ) -> Result<Option<TerminatorInstruction>, SsaParseError> {
+        let terminator = match keyword {
+            "jmp" => {
+                let destination = self.block(line.next_token()?);
+                line.expect("(")?;
+                let arguments = self.parse_values_until(line, ")")?;
+                TerminatorInstruction::Jmp { destination, arguments }
+            }
+            "jmpif" => {
+                let condition = self.parse_value(line)?;
+                line.expect("then")?;
+                line.expect(":")?;
+                let then_destination = self.block(line.next_token()?);
+                line.expect(",")?;
+                line.expect("else")?;
+                line.expect(":")?;
+                let else_destination = self.block(line.next_token()?);
+                TerminatorInstruction::JmpIf { condition, then_destination, else_destination }
+            }
+            "return" => {
+                let mut return_values = Vec::new();
+                if !line.is_empty() {
+                    return_values.push(self.parse_value(line)?);
+                    while line.eat(",") {
+                        return_values.push(self.parse_value(line)?);
+                    }
+                }
+                TerminatorInstruction::Return { return_values }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(terminator))
+    }
+
+    /// Parses a value used by an instruction: a value id, a numeric or array constant, a
+    /// function id, an intrinsic, or the name of a foreign function.
+    fn parse_value(&mut self, line: &mut Line<'a>) -> Result<ValueId, SsaParseError> {
+        let token = line.peek().ok_or_else(|| line.error("expected a value"))?;
+
+        if token == "[" || numeric_type(token).is_some() {
+            return match line.parse_type()? {
+                Type::Array(element_types, length) => {
+                    line.expect("[")?;
+                    let elements = self.parse_values_until(line, "]")?;
+                    if elements.len() != length * element_types.len() {
+                        return Err(line.error(format!(
+                            "expected {} array elements, found {}",
+                            length * element_types.len(),
+                            elements.len()
+                        )));
+                    }
+                    Ok(self.function.dfg.make_array(elements.into(), element_types))
+                }
+                typ @ Type::Numeric(_) => {
+                    let constant = line.next_token()?;
+                    let constant = parse_field(constant).ok_or_else(|| {
+                        line.error(format!("expected a numeric constant, found `{constant}`"))
+                    })?;
+                    Ok(self.function.dfg.make_constant(constant, typ))
+                }
+                typ => Err(line.error(format!("constants of type `{typ}` are not supported"))),
+            };
+        }
+
+        line.next_token()?;
+        if parse_id(token, 'v').is_some() {
+            self.values
+                .get(token)
+                .copied()
+                .ok_or_else(|| line.error(format!("value `{token}` is not defined")))
+        } else if let Some(id) = parse_id(token, 'f') {
+            Ok(self.function.dfg.import_function(Id::test_new(id)))
+        } else if let Some(intrinsic) = Intrinsic::lookup(token) {
+            Ok(self.function.dfg.import_intrinsic(intrinsic))
+        } else {
+            Ok(self.function.dfg.import_foreign_function(token))
+        }
+    }
+
+    /// Parses a comma separated list of values up to and including the `closing` token.
+    fn parse_values_until(
+        &mut self,
+        line: &mut Line<'a>,
+        closing: &str,
+    ) -> Result<Vec<ValueId>, SsaParseError> {
+        let mut values = Vec::new();
+        if line.eat(closing) {
+            return Ok(values);
+        }
+        loop {
+            values.push(self.parse_value(line)?);
+            if !line.eat(",") {
+                break;
+            }
+        }
+        line.expect(closing)?;
+        Ok(values)
+    }
+
+    fn define_value(
+        &mut self,
+        line: &Line,
+        name: &'a str,
+        value: ValueId,
+    ) -> Result<(), SsaParseError> {
+        if parse_id(name, 'v').is_none() {
+            return Err(line.error(format!("expected a value id, found `{name}`")));
+        }
+        if self.values.insert(name, value).is_some() {
+            return Err(line.error(format!("value `{name}` is defined twice")));
+        }
+        Ok(())
+    }
+
+    /// Returns the block with the given name, creating it if this is its first mention.
+    /// The first block of a function is always its entry block.
+    fn block(&mut self, name: &'a str) -> BasicBlockId {
+        if let Some(block) = self.blocks.get(name) {
+            return *block;
+        }
+        let block = if self.blocks.is_empty() {
+            self.function.entry_block()
+        } else {
+            self.undefined_blocks.push(name);
+            self.function.dfg.make_block()
+        };
+        self.blocks.insert(name, block);
+        block
+    }
+}
+
+/// Parses an id such as `v3`, `b1` or `f0`, returning its index.
+fn parse_id(token: &str, prefix: char) -> Option<usize> {
+    token.strip_prefix(prefix)?.parse().ok()
+}
+
+fn numeric_type(token: &str) -> Option<Type> {
+    if token == "Field" {
+        return Some(Type::field());
+    }
+    if let Some(bit_size) = token.strip_prefix('u').and_then(|bits| bits.parse().ok()) {
+        return Some(Type::unsigned(bit_size));
+    }
+    let bit_size = token.strip_prefix('i')?.parse().ok()?;
+    Some(Type::signed(bit_size))
+}
+
+fn binary_operator(keyword: &str) -> Option<BinaryOp> {
+    let operator = match keyword {
+        "add" => BinaryOp::Add,
+        "sub" => BinaryOp::Sub,
+        "mul" => BinaryOp::Mul,
+        "div" => BinaryOp::Div,
+        "eq" => BinaryOp::Eq,
+        "mod" => BinaryOp::Mod,
+        "lt" => BinaryOp::Lt,
+        "and" => BinaryOp::And,
+        "or" => BinaryOp::Or,
+        "xor" => BinaryOp::Xor,
+        "shl" => BinaryOp::Shl,
+        "shr" => BinaryOp::Shr,
+        _ => return None,
+    };
+    Some(operator)
+}
+
+/// Parses a field element in any of the forms it is displayed in: a decimal or hexadecimal
+/// number, a power of two such as `2⁶⁴`, or a multiple of one such as `2³²×3`. Each of these
+/// may be negated with a leading `-`.
+fn parse_field(token: &str) -> Option<FieldElement> {
+    if let Some(token) = token.strip_prefix('-') {
+        return parse_field(token).map(|field| -field);
+    }
+
+    const SUPERSCRIPTS: &str = "⁰¹²³⁴⁵⁶⁷⁸⁹";
+    let is_superscript = |character: char| SUPERSCRIPTS.contains(character);
+    let Some(power) = token.strip_prefix('2').filter(|power| power.starts_with(is_superscript))
+    else {
+        return FieldElement::try_from_str(token);
+    };
+
+    let (exponent, factor) = match power.split_once('×') {
+        Some((exponent, factor)) => (exponent, FieldElement::try_from_str(factor)?),
+        None => (power, FieldElement::one()),
+    };
+    let mut bits = 0;
+    for digit in exponent.chars() {
+        bits = bits * 10 + SUPERSCRIPTS.chars().position(|superscript| superscript == digit)?;
+    }
+    Some((0..bits).fold(factor, |field, _| field + field))
+}
+
+/// Asserts that `ssa` is the program described by `expected`, in the textual form of the SSA.
+///
+/// Value and block ids are compared by the order they first appear in, rather than by their
+/// index, as passes leave gaps in the ids they allocate.
+pub(crate) fn assert_ssa_equals(ssa: &Ssa, expected: &str) {
+    let expected: Ssa = match expected.parse() {
+        Ok(expected) => expected,
+        Err(error) => panic!("the expected SSA should parse: {error}"),
+    };
+    let (found, expected) = (ssa.to_string(), expected.to_string());
+    if normalize_ids(&found) != normalize_ids(&expected) {
+        panic!(
+            "the SSA does not match the expected program\nexpected:\n{expected}\nfound:\n{found}"
+        )
+    }
+}
+
+/// Renumbers the value and block ids in the textual SSA by the order they first appear in.
+fn normalize_ids(ssa: &str) -> String {
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    let mut counts = [0, 0];
+    let mut normalized = String::with_capacity(ssa.len());
+
+    let is_word = |character: char| character.is_alphanumeric() || character == '_';
+    let prefixes = ['v', 'b'];
+
+    let mut rest = ssa;
+    while let Some(start) = rest.find(is_word) {
+        normalized.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|character| !is_word(character)).unwrap_or(rest.len());
+        let (word, remaining) = rest.split_at(end);
+
+        match prefixes.iter().position(|prefix| parse_id(word, *prefix).is_some()) {
+            Some(kind) => {
+                let id = ids.entry(word).or_insert_with(|| {
+                    counts[kind] += 1;
+                    format!("{}{}", prefixes[kind], counts[kind] - 1)
+                });
+                normalized.push_str(id);
+            }
+            None => normalized.push_str(word),
+        }
+        rest = remaining;
+    }
+    normalized.push_str(rest);
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        ssa_builder::FunctionBuilder,
+        ssa_gen::Ssa,
+    };
+
+    use super::assert_ssa_equals;
+
+    #[test]
+    fn parses_the_printed_ssa() {
+        // fn main f0 {
+        //   b0(v0: Field, v1: u32):
+        //     v2 = allocate
+        //     store v0 at v2
+        //     v3 = load v2 -> Field
+        //     v4 = cast v1 as Field
+        //     v5 = call f1(v3, v4) -> Field
+        //     jmp b1(v5)
+        //   b1(v6: Field):
+        //     return v6
+        // }
+        // brillig fn double f1 {
+        //   b0(v0: Field, v1: Field):
+        //     v2 = mul v0, Field 2
+        //     v3 = add v2, v1
+        //     return v3
+        // }
+        let main_id = Id::test_new(0);
+        let double_id = Id::test_new(1);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::unsigned(32));
+        let v2 = builder.insert_allocate();
+        builder.insert_store(v2, v0);
+        let v3 = builder.insert_load(v2, Type::field());
+        let v4 = builder.insert_cast(v1, Type::field());
+        let double = builder.import_function(double_id);
+        let v5 = builder.insert_call(double, vec![v3, v4], vec![Type::field()])[0];
+        let b1 = builder.insert_block();
+        builder.terminate_with_jmp(b1, vec![v5]);
+        builder.switch_to_block(b1);
+        let v6 = builder.add_block_parameter(b1, Type::field());
+        builder.terminate_with_return(vec![v6]);
+
+        builder.new_brillig_function("double".into(), double_id);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+        let two = builder.field_constant(2u128);
+        let v2 = builder.insert_binary(v0, BinaryOp::Mul, two);
+        let v3 = builder.insert_binary(v2, BinaryOp::Add, v1);
+        builder.terminate_with_return(vec![v3]);
+
+        let ssa = builder.finish();
+        let parsed: Ssa = ssa.to_string().parse().unwrap();
+        assert_ssa_equals(&parsed, &ssa.to_string());
+    }
+
+    #[test]
+    fn parses_every_instruction() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field, v1: u8, v2: [Field, u1; 2]):
+                v3 = not v1
+                v4 = truncate v3 to 4 bits, max_bit_size: 8
+                v5 = lt v4, u8 3
+                enable_side_effects v5
+                constrain v5
+                v6 = array_get v2, index Field 1 -> u1
+                v7 = array_set v2, index Field 0, value v0
+                v8 = call to_le_bits(v0, u32 8) -> [u1; 8]
+                call println([Field; 2] [v0, Field -1])
+                v9 = call get_number(v0) -> Field
+                jmpif v6 then: b1, else: b2
+              b1():
+                return v7
+              b2():
+                return [Field, u1; 2] [Field 2⁶⁴, u1 1, Field 2³²×3, u1 0]
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        assert_ssa_equals(&ssa, src);
+    }
+
+    #[test]
+    fn does_not_simplify_instructions() {
+        let src = "
+            acir fn main f0 {
+              b0():
+                v0 = add Field 1, Field 2
+                return v0
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        let main = ssa.main();
+        assert_eq!(main.dfg[main.entry_block()].instructions().len(), 1);
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field):
+                v1 = add v0, v2
+                return v1
+            }
+        ";
+        let error = src.parse::<Ssa>().err().expect("v2 is not defined");
+        assert_eq!(error.to_string(), "line 4: value `v2` is not defined");
+    }
+
+    #[test]
+    fn ignores_the_numbering_of_ids() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field):
+                jmp b1(v0)
+              b1(v1: Field):
+                return v1
+            }
+        ";
+        let renumbered = "
+            acir fn main f0 {
+              b0(v3: Field):
+                jmp b7(v3)
+              b7(v5: Field):
+                return v5
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        assert_ssa_equals(&ssa, renumbered);
+    }
+}
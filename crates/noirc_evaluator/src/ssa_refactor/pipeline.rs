@@ -0,0 +1,426 @@
+//! The pipeline of optimization passes run over the SSA of an ACIR program, after
+//! defunctionalization and before the program is converted into ACIR.
+//!
+//! A pipeline is written as a comma separated list of pass names, such as
+//! `inline,licm,strength-reduce,unroll,simplify-cfg,flatten,mem2reg,fold-constants,gvn,die`.
+//! Passes grouped as `repeat(mem2reg,fold-constants,gvn,die)` are run again and again until an
+//! iteration no longer removes any instructions.
+use std::{fmt::Display, str::FromStr};
+
+use iter_extended::vecmap;
+
+use super::ssa_gen::Ssa;
+
+/// The passes which every pipeline must run for the SSA to be converted into ACIR, in the order
+/// they must first be run in.
+const REQUIRED_PASSES: [SsaPass; 5] = [
+    SsaPass::Inlining,
+    SsaPass::Unrolling,
+    SsaPass::Flattening,
+    SsaPass::Mem2Reg,
+    SsaPass::DeadInstructionElimination,
+];
+
+/// The most iterations a `repeat(...)` group is run for, in case its passes keep finding
+/// instructions to remove.
+const MAX_REPETITIONS: usize = 16;
+
+/// The options to compile the SSA of a program with.
+#[derive(Debug, Clone)]
+pub struct SsaOptions {
+    /// The passes run over ACIR programs
+    pub pipeline: SsaPipeline,
+    /// Print the SSA after every pass
+    pub print_ssa_passes: bool,
+    /// Print the SSA after each run of these passes
+    pub show_ssa_after: Vec<SsaPass>,
+    /// Check that the SSA is well formed after every pass
+    pub verify_ssa: bool,
+    /// Constrain integer `+`, `-` and `*` not to overflow in every function, rather than only in
+    /// those marked `#[check_overflow]`
+    pub check_overflow: bool,
+}
+
+impl Default for SsaOptions {
+    fn default() -> Self {
+        Self {
+            pipeline: SsaPipeline::for_optimization_level(1),
+            print_ssa_passes: false,
+            show_ssa_after: Vec::new(),
+            verify_ssa: false,
+            check_overflow: false,
+        }
+    }
+}
+
+/// An optimization pass over the SSA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SsaPass {
+    Inlining,
+    LoopInvariantCodeMotion,
+    StrengthReduction,
+    Unrolling,
+    SimplifyCfg,
+    Flattening,
+    Mem2Reg,
+    ConstantFolding,
+    GlobalValueNumbering,
+    RemoveTruncates,
+    DeadInstructionElimination,
+}
+
+impl SsaPass {
+    const ALL: [SsaPass; 11] = [
+        SsaPass::Inlining,
+        SsaPass::LoopInvariantCodeMotion,
+        SsaPass::StrengthReduction,
+        SsaPass::Unrolling,
+        SsaPass::SimplifyCfg,
+        SsaPass::Flattening,
+        SsaPass::Mem2Reg,
+        SsaPass::ConstantFolding,
+        SsaPass::GlobalValueNumbering,
+        SsaPass::RemoveTruncates,
+        SsaPass::DeadInstructionElimination,
+    ];
+
+    /// The name the pass is selected by in a pipeline.
+    pub fn name(self) -> &'static str {
+        match self {
+            SsaPass::Inlining => "inline",
+            SsaPass::LoopInvariantCodeMotion => "licm",
+            SsaPass::StrengthReduction => "strength-reduce",
+            SsaPass::Unrolling => "unroll",
+            SsaPass::SimplifyCfg => "simplify-cfg",
+            SsaPass::Flattening => "flatten",
+            SsaPass::Mem2Reg => "mem2reg",
+            SsaPass::ConstantFolding => "fold-constants",
+            SsaPass::GlobalValueNumbering => "gvn",
+            SsaPass::RemoveTruncates => "remove-truncates",
+            SsaPass::DeadInstructionElimination => "die",
+        }
+    }
+
+    /// The heading the SSA is printed under after this pass.
+    fn heading(self) -> &'static str {
+        match self {
+            SsaPass::Inlining => "After Inlining:",
+            SsaPass::LoopInvariantCodeMotion => "After Loop Invariant Code Motion:",
+            SsaPass::StrengthReduction => "After Strength Reduction:",
+            SsaPass::Unrolling => "After Unrolling:",
+            SsaPass::SimplifyCfg => "After Simplifying:",
+            SsaPass::Flattening => "After Flattening:",
+            SsaPass::Mem2Reg => "After Mem2Reg:",
+            SsaPass::ConstantFolding => "After Constant Folding:",
+            SsaPass::GlobalValueNumbering => "After Global Value Numbering:",
+            SsaPass::RemoveTruncates => "After Removing Redundant Truncates:",
+            SsaPass::DeadInstructionElimination => "After Dead Instruction Elimination:",
+        }
+    }
+
+    fn run(self, ssa: Ssa, options: &SsaOptions) -> Ssa {
+        let ssa = match self {
+            SsaPass::Inlining => ssa.inline_functions(),
+            SsaPass::LoopInvariantCodeMotion => ssa.loop_invariant_code_motion(),
+            SsaPass::StrengthReduction => ssa.strength_reduction(),
+            SsaPass::Unrolling => ssa.unroll_loops(),
+            SsaPass::SimplifyCfg => ssa.simplify_cfg(),
+            SsaPass::Flattening => ssa.flatten_cfg(),
+            SsaPass::Mem2Reg => ssa.mem2reg(),
+            SsaPass::ConstantFolding => ssa.fold_constants(),
+            SsaPass::GlobalValueNumbering => ssa.global_value_numbering(),
+            SsaPass::RemoveTruncates => ssa.remove_redundant_truncates(),
+            SsaPass::DeadInstructionElimination => ssa.dead_instruction_elimination(),
+        };
+        let print = options.print_ssa_passes || options.show_ssa_after.contains(&self);
+        ssa.print(print, self.heading()).verify(options.verify_ssa, self.name())
+    }
+}
+
+impl Display for SsaPass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SsaPass {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        SsaPass::ALL.into_iter().find(|pass| pass.name() == name).ok_or_else(|| {
+            let names = vecmap(SsaPass::ALL, SsaPass::name);
+            format!("unknown SSA pass `{name}`, expected one of {}", names.join(", "))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Pass(SsaPass),
+    /// Passes which are run until they stop removing instructions
+    Repeat(Vec<SsaPass>),
+}
+
+/// The sequence of passes run over the SSA of an ACIR program.
+///
+/// ACIR generation expects a single function with a single block and no memory instructions,
+/// so a pipeline which is parsed must run inlining, unrolling, flattening, mem2reg and dead
+/// instruction elimination, each of them at some point after the one before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsaPipeline {
+    steps: Vec<Step>,
+}
+
+impl SsaPipeline {
+    /// The pipeline run at the given optimization level:
+    /// - 0 only runs the passes ACIR generation requires,
+    /// - 1 also hoists loop invariants and strength reduces induction variables before
+    ///   unrolling, then folds constants, merges repeated instructions and removes redundant
+    ///   truncations, running each pass once,
+    /// - 2 and above repeat the passes following flattening until the program stops shrinking.
+    pub fn for_optimization_level(level: u8) -> SsaPipeline {
+        use SsaPass::*;
+        let mut steps = vec![Step::Pass(Inlining)];
+        if level > 0 {
+            steps.extend([LoopInvariantCodeMotion, StrengthReduction].map(Step::Pass));
+        }
+        steps.extend([Unrolling, SimplifyCfg, Flattening].map(Step::Pass));
+
+        let optimizations = match level {
+            0 => vec![Mem2Reg, DeadInstructionElimination],
+            _ => vec![
+                Mem2Reg,
+                ConstantFolding,
+                GlobalValueNumbering,
+                RemoveTruncates,
+                DeadInstructionElimination,
+            ],
+        };
+        if level < 2 {
+            steps.extend(optimizations.into_iter().map(Step::Pass));
+        } else {
+            steps.push(Step::Repeat(optimizations));
+        }
+        SsaPipeline { steps }
+    }
+
+    /// Returns true if `pass` is run by the pipeline, on its own or in a `repeat(...)` group.
+    fn contains(&self, pass: SsaPass) -> bool {
+        self.runs(pass).next().is_some()
+    }
+
+    /// The positions at which `pass` appears in the pipeline, in order, as the index of its step
+    /// and its index within that step.
+    fn runs(&self, pass: SsaPass) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.steps.iter().enumerate().flat_map(move |(step_index, step)| {
+            let passes = match step {
+                Step::Pass(single) => std::slice::from_ref(single),
+                Step::Repeat(passes) => passes.as_slice(),
+            };
+            let positions = passes.iter().enumerate().filter(move |(_, step)| **step == pass);
+            positions.map(move |(index, _)| (step_index, index))
+        })
+    }
+
+    /// Runs each pass of the pipeline over `ssa` in order.
+    pub(crate) fn run(&self, mut ssa: Ssa, options: &SsaOptions) -> Ssa {
+        for step in &self.steps {
+            ssa = match step {
+                Step::Pass(pass) => pass.run(ssa, options),
+                Step::Repeat(passes) => {
+                    let mut instructions = count_instructions(&ssa);
+                    for _ in 0..MAX_REPETITIONS {
+                        ssa = passes.iter().fold(ssa, |ssa, pass| pass.run(ssa, options));
+                        let remaining = count_instructions(&ssa);
+                        if remaining >= instructions {
+                            break;
+                        }
+                        instructions = remaining;
+                    }
+                    ssa
+                }
+            };
+        }
+        ssa
+    }
+}
+
+/// Counts the instructions in the reachable blocks of each function, to tell whether a pass
+/// made progress.
+fn count_instructions(ssa: &Ssa) -> usize {
+    let blocks = ssa.functions.values().flat_map(|function| {
+        function.reachable_blocks().into_iter().map(move |block| &function.dfg[block])
+    });
+    blocks.map(|block| block.instructions().len()).sum()
+}
+
+impl Display for SsaPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let steps = vecmap(&self.steps, |step| match step {
+            Step::Pass(pass) => pass.to_string(),
+            Step::Repeat(passes) => {
+                format!("repeat({})", vecmap(passes, ToString::to_string).join(","))
+            }
+        });
+        write!(f, "{}", steps.join(","))
+    }
+}
+
+impl FromStr for SsaPipeline {
+    type Err = String;
+
+    fn from_str(pipeline: &str) -> Result<Self, Self::Err> {
+        let mut steps = Vec::new();
+        let mut rest = pipeline.trim();
+        while !rest.is_empty() {
+            let remaining = if let Some(group) = rest.strip_prefix("repeat(") {
+                let (passes, remaining) =
+                    group.split_once(')').ok_or("expected `)` to close `repeat(`")?;
+                let passes = passes.split(',').map(|name| name.trim().parse());
+                steps.push(Step::Repeat(passes.collect::<Result<_, _>>()?));
+                remaining.trim_start()
+            } else {
+                let end = rest.find(',').unwrap_or(rest.len());
+                steps.push(Step::Pass(rest[..end].trim().parse()?));
+                &rest[end..]
+            };
+
+            rest = match remaining.strip_prefix(',') {
+                Some(remaining) if remaining.trim().is_empty() => {
+                    return Err("expected a pass after the last `,`".to_owned())
+                }
+                Some(remaining) => remaining.trim_start(),
+                None if remaining.is_empty() => remaining,
+                None => return Err(format!("expected `,` before `{remaining}`")),
+            };
+        }
+
+        if steps.is_empty() {
+            return Err("expected at least one pass".to_owned());
+        }
+        let pipeline = SsaPipeline { steps };
+
+        let missing: Vec<_> =
+            REQUIRED_PASSES.into_iter().filter(|pass| !pipeline.contains(*pass)).collect();
+        if !missing.is_empty() {
+            let missing = vecmap(missing, SsaPass::name);
+            return Err(format!(
+                "the pipeline is missing the {} pass{}, which ACIR generation requires",
+                missing.join(", "),
+                if missing.len() == 1 { "" } else { "es" }
+            ));
+        }
+
+        for passes in REQUIRED_PASSES.windows(2) {
+            let (earlier, later) = (passes[0], passes[1]);
+            if pipeline.runs(later).last() < pipeline.runs(earlier).next() {
+                return Err(format!(
+                    "the pipeline must run the {later} pass after the {earlier} pass, which ACIR \
+                    generation requires"
+                ));
+            }
+        }
+        Ok(pipeline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{parser::assert_ssa_equals, ssa_gen::Ssa};
+
+    use super::{SsaOptions, SsaPass, SsaPipeline, Step};
+
+    #[test]
+    fn parses_pipelines() {
+        let pipeline = "inline,licm,strength-reduce,unroll,simplify-cfg,flatten,\
+            repeat(mem2reg,fold-constants,gvn,remove-truncates,die)";
+        let parsed: SsaPipeline = pipeline.parse().unwrap();
+        assert_eq!(parsed, SsaPipeline::for_optimization_level(2));
+        assert_eq!(parsed.to_string(), pipeline);
+
+        let default = "inline,licm,strength-reduce,unroll,simplify-cfg,flatten,\
+            mem2reg,fold-constants,gvn,remove-truncates,die";
+        assert_eq!(SsaPipeline::for_optimization_level(1).to_string(), default);
+
+        let minimal = "inline,unroll,simplify-cfg,flatten,mem2reg,die";
+        assert_eq!(SsaPipeline::for_optimization_level(0).to_string(), minimal);
+
+        let with_spaces: SsaPipeline =
+            " inline, unroll , flatten,repeat( mem2reg, die ) ".parse().unwrap();
+        assert_eq!(with_spaces.to_string(), "inline,unroll,flatten,repeat(mem2reg,die)");
+    }
+
+    #[test]
+    fn rejects_malformed_pipelines() {
+        let error = "inline,inlining".parse::<SsaPipeline>().unwrap_err();
+        assert!(error.starts_with("unknown SSA pass `inlining`"), "{error}");
+
+        assert!("inline,".parse::<SsaPipeline>().is_err());
+        assert!("repeat(die".parse::<SsaPipeline>().is_err());
+        let error = "repeat(die)die".parse::<SsaPipeline>().unwrap_err();
+        assert_eq!(error, "expected `,` before `die`");
+        assert!("".parse::<SsaPipeline>().is_err());
+    }
+
+    #[test]
+    fn rejects_pipelines_missing_required_passes() {
+        let error = "inline,unroll,flatten,mem2reg".parse::<SsaPipeline>().unwrap_err();
+        assert_eq!(error, "the pipeline is missing the die pass, which ACIR generation requires");
+
+        let error = "repeat(mem2reg,fold-constants,die)".parse::<SsaPipeline>().unwrap_err();
+        assert_eq!(
+            error,
+            "the pipeline is missing the inline, unroll, flatten passes, which ACIR generation \
+            requires"
+        );
+    }
+
+    #[test]
+    fn rejects_pipelines_running_required_passes_out_of_order() {
+        let error = "die,mem2reg,flatten,unroll,inline".parse::<SsaPipeline>().unwrap_err();
+        assert_eq!(
+            error,
+            "the pipeline must run the unroll pass after the inline pass, which ACIR generation \
+            requires"
+        );
+
+        let error = "inline,unroll,flatten,die,mem2reg".parse::<SsaPipeline>().unwrap_err();
+        assert_eq!(
+            error,
+            "the pipeline must run the die pass after the mem2reg pass, which ACIR generation \
+            requires"
+        );
+
+        // Passes may run before the required passes as long as they also run after them
+        assert!("inline,mem2reg,die,unroll,flatten,mem2reg,die".parse::<SsaPipeline>().is_ok());
+    }
+
+    #[test]
+    fn repeats_passes_until_they_stop_removing_instructions() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field):
+                v1 = allocate
+                store Field 2 at v1
+                v2 = load v1 -> Field
+                v3 = mul v2, Field 3
+                v4 = add v3, v0
+                return v4
+            }
+        ";
+        let ssa: Ssa = src.parse().unwrap();
+        let passes =
+            vec![SsaPass::Mem2Reg, SsaPass::ConstantFolding, SsaPass::DeadInstructionElimination];
+        let pipeline = SsaPipeline { steps: vec![Step::Repeat(passes)] };
+        let ssa = pipeline.run(ssa, &SsaOptions::default());
+
+        let expected = "
+            acir fn main f0 {
+              b0(v0: Field):
+                v1 = add Field 6, v0
+                return v1
+            }
+        ";
+        assert_ssa_equals(&ssa, expected);
+    }
+}
@@ -1,6 +1,7 @@
 use std::{borrow::Cow, rc::Rc};
 
 use acvm::FieldElement;
+use noirc_errors::Location;
 
 use crate::ssa_refactor::ir::{
     basic_block::BasicBlockId,
@@ -13,7 +14,7 @@ use crate::ssa_refactor::ir::{
 use super::{
     ir::{
         basic_block::BasicBlock,
-        dfg::InsertInstructionResult,
+        dfg::{CallStack, InsertInstructionResult, InstructionSource},
         function::RuntimeType,
         instruction::{InstructionId, Intrinsic},
         types::CompositeType,
@@ -32,6 +33,8 @@ pub(crate) struct FunctionBuilder {
     pub(super) current_function: Function,
     current_block: BasicBlockId,
     finished_functions: Vec<Function>,
+    /// The source location recorded for each instruction inserted via `insert_instruction`.
+    current_location: Option<Location>,
 }
 
 impl FunctionBuilder {
@@ -48,7 +51,12 @@ impl FunctionBuilder {
         new_function.set_runtime(runtime);
         let current_block = new_function.entry_block();
 
-        Self { current_function: new_function, current_block, finished_functions: Vec::new() }
+        Self {
+            current_function: new_function,
+            current_block,
+            finished_functions: Vec::new(),
+            current_location: None,
+        }
     }
 
     /// Finish the current function and create a new function.
@@ -65,6 +73,7 @@ impl FunctionBuilder {
         let mut new_function = Function::new(name, function_id);
         new_function.set_runtime(runtime_type);
         self.current_block = new_function.entry_block();
+        self.current_location = None;
 
         let old_function = std::mem::replace(&mut self.current_function, new_function);
         self.finished_functions.push(old_function);
@@ -144,13 +153,40 @@ impl FunctionBuilder {
         instruction: Instruction,
         ctrl_typevars: Option<Vec<Type>>,
     ) -> InsertInstructionResult {
-        self.current_function.dfg.insert_instruction_and_results(
+        let source = InstructionSource {
+            origin: None,
+            location: self.current_location,
+            call_stack: CallStack::new(),
+        };
+        self.insert_instruction_with_source(instruction, ctrl_typevars, source)
+    }
+
+    /// Inserts a new instruction at the end of the current block, recording that it
+    /// comes from `source`. Returns the results of the instruction.
+    pub(crate) fn insert_instruction_with_source(
+        &mut self,
+        instruction: Instruction,
+        ctrl_typevars: Option<Vec<Type>>,
+        source: InstructionSource,
+    ) -> InsertInstructionResult {
+        self.current_function.dfg.insert_instruction_with_source(
             instruction,
             self.current_block,
             ctrl_typevars,
+            source,
         )
     }
 
+    /// Sets the source location recorded for instructions inserted from now on.
+    pub(crate) fn set_location(&mut self, location: Location) {
+        self.current_location = Some(location);
+    }
+
+    /// Records that `value` holds the source variable `name`, unless it already holds another.
+    pub(crate) fn name_value(&mut self, value: ValueId, name: Rc<str>) {
+        self.current_function.dfg.set_variable_name(value, name);
+    }
+
     /// Switch to inserting instructions in the given block.
     /// Expects the given block to be within the same function. If you want to insert
     /// instructions into a new function, call new_function instead.
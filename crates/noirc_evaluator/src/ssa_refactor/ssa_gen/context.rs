@@ -35,6 +35,9 @@ pub(super) struct FunctionContext<'a> {
 
     pub(super) builder: FunctionBuilder,
     shared_context: &'a SharedContext,
+
+    /// True if integer `+`, `-` and `*` in the current function are constrained not to overflow
+    check_overflow: bool,
 }
 
 /// Shared context for all functions during ssa codegen. This is the only
@@ -66,6 +69,10 @@ pub(super) struct SharedContext {
 
     /// The entire monomorphized source program
     pub(super) program: Program,
+
+    /// True if integer arithmetic is checked for overflow in every function, rather than only in
+    /// those marked `#[check_overflow]`
+    check_overflow: bool,
 }
 
 /// The queue of functions remaining to compile
@@ -79,12 +86,14 @@ impl<'a> FunctionContext<'a> {
     /// queue is non-empty at the time of calling this function. This can be ensured by calling
     /// `shared_context.get_or_queue_function(function_to_queue)` before calling this constructor.
     ///
-    /// `function_name` and `parameters` are expected to be the name and parameters of the function
-    /// this constructor will pop from the function queue.
+    /// `function_name`, `parameters` and `check_overflow` are expected to be the name, parameters
+    /// and overflow checking attribute of the function this constructor will pop from the
+    /// function queue.
     pub(super) fn new(
         function_name: String,
         parameters: &Parameters,
         runtime: RuntimeType,
+        check_overflow: bool,
         shared_context: &'a SharedContext,
     ) -> Self {
         let function_id = shared_context
@@ -93,7 +102,9 @@ impl<'a> FunctionContext<'a> {
             .1;
 
         let builder = FunctionBuilder::new(function_name, function_id, runtime);
-        let mut this = Self { definitions: HashMap::new(), builder, shared_context };
+        let check_overflow = check_overflow || shared_context.check_overflow;
+        let mut this =
+            Self { definitions: HashMap::new(), builder, shared_context, check_overflow };
         this.add_parameters_to_scope(parameters);
         this
     }
@@ -110,6 +121,7 @@ impl<'a> FunctionContext<'a> {
         } else {
             self.builder.new_function(func.name.clone(), id);
         }
+        self.check_overflow = func.check_overflow || self.shared_context.check_overflow;
         self.add_parameters_to_scope(&func.parameters);
     }
 
@@ -118,8 +130,8 @@ impl<'a> FunctionContext<'a> {
     /// The returned parameter type list will be flattened, so any struct parameters will
     /// be returned as one entry for each field (recursively).
     fn add_parameters_to_scope(&mut self, parameters: &Parameters) {
-        for (id, mutable, _, typ) in parameters {
-            self.add_parameter_to_scope(*id, typ, *mutable);
+        for (id, mutable, name, typ) in parameters {
+            self.add_parameter_to_scope(*id, name, typ, *mutable);
         }
     }
 
@@ -130,6 +142,7 @@ impl<'a> FunctionContext<'a> {
     fn add_parameter_to_scope(
         &mut self,
         parameter_id: LocalId,
+        parameter_name: &str,
         parameter_type: &ast::Type,
         mutable: bool,
     ) {
@@ -143,9 +156,24 @@ impl<'a> FunctionContext<'a> {
             }
         });
 
+        self.name_values(&parameter_value, parameter_name);
         self.definitions.insert(parameter_id, parameter_value);
     }
 
+    /// Records `name` as the source variable held by each immutable value in `values`, so that
+    /// it can be shown when debugging. Tuple fields are named `name.0`, `name.1`, and so on.
+    pub(super) fn name_values(&mut self, values: &Values, name: &str) {
+        match values {
+            Tree::Branch(fields) => {
+                for (index, field) in fields.iter().enumerate() {
+                    self.name_values(field, &format!("{name}.{index}"));
+                }
+            }
+            Tree::Leaf(Value::Normal(value)) => self.builder.name_value(*value, name.into()),
+            Tree::Leaf(Value::Mutable(..)) => (),
+        }
+    }
+
     /// Allocate a single slot of memory and store into it the given initial value of the variable.
     /// Always returns a Value::Mutable wrapping the allocate instruction.
     pub(super) fn new_mutable_variable(&mut self, value_to_store: ValueId) -> Value {
@@ -227,11 +255,35 @@ impl<'a> FunctionContext<'a> {
     /// Converts the form of the binary instruction as necessary
     /// (e.g. swapping arguments, inserting a not) to represent it in the IR.
     /// For example, (a <= b) is represented as !(b < a)
+    ///
+    /// If overflow checking is enabled for the current function, integer `+`, `-` and `*`
+    /// are constrained not to overflow.
     pub(super) fn insert_binary(
+        &mut self,
+        lhs: ValueId,
+        operator: noirc_frontend::BinaryOpKind,
+        rhs: ValueId,
+    ) -> Values {
+        self.insert_binary_with_overflow_check(lhs, operator, rhs, self.check_overflow)
+    }
+
+    /// Insert a binary instruction which wraps on overflow, as used by the `wrapping_add`,
+    /// `wrapping_sub` and `wrapping_mul` functions of the standard library.
+    pub(super) fn insert_wrapping_binary(
+        &mut self,
+        lhs: ValueId,
+        operator: noirc_frontend::BinaryOpKind,
+        rhs: ValueId,
+    ) -> Values {
+        self.insert_binary_with_overflow_check(lhs, operator, rhs, false)
+    }
+
+    fn insert_binary_with_overflow_check(
         &mut self,
         mut lhs: ValueId,
         operator: noirc_frontend::BinaryOpKind,
         mut rhs: ValueId,
+        check_overflow: bool,
     ) -> Values {
         let op = convert_operator(operator);
 
@@ -259,7 +311,21 @@ impl<'a> FunctionContext<'a> {
                     unreachable!("ICE: Truncation attempted on non-integer");
                 }
             };
-            result = self.builder.insert_truncate(result, bit_size, max_bit_size);
+            let truncated = self.builder.insert_truncate(result, bit_size, max_bit_size);
+
+            let can_overflow = matches!(op, BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul);
+            if check_overflow && can_overflow {
+                match result_type {
+                    Type::Numeric(NumericType::Unsigned { .. }) => {
+                        self.insert_overflow_check(lhs, op, rhs, result, truncated);
+                        if op == BinaryOp::Mul && 2 * bit_size >= FieldElement::max_num_bits() {
+                            self.insert_wide_multiplication_overflow_check(lhs, rhs, bit_size);
+                        }
+                    }
+                    _ => self.insert_signed_overflow_check(lhs, op, rhs, truncated, bit_size),
+                }
+            }
+            result = truncated;
         }
 
         if operator_requires_not(operator) {
@@ -268,6 +334,153 @@ impl<'a> FunctionContext<'a> {
         result.into()
     }
 
+    /// Constrains the result of an unsigned `+`, `-` or `*` to be unchanged by truncating it to
+    /// the bit size of its type, so that the operation fails rather than wrapping on overflow.
+    fn insert_overflow_check(
+        &mut self,
+        lhs: ValueId,
+        operator: BinaryOp,
+        rhs: ValueId,
+        result: ValueId,
+        truncated: ValueId,
+    ) {
+        let (result, truncated) = match self.builder.current_function.runtime() {
+            // ACIR computes the result in the field, and a subtraction which underflows wraps
+            // around the field modulus, so only a result which fits is unchanged by truncation.
+            RuntimeType::Acir => (result, truncated),
+            // Brillig computes the result with wrapping integer arithmetic, so it is recomputed in
+            // the field to compare against.
+            RuntimeType::Brillig => {
+                let lhs = self.builder.insert_cast(lhs, Type::field());
+                let rhs = self.builder.insert_cast(rhs, Type::field());
+                let result = self.builder.insert_binary(lhs, operator, rhs);
+                (result, self.builder.insert_cast(truncated, Type::field()))
+            }
+        };
+        let no_overflow = self.builder.insert_binary(result, BinaryOp::Eq, truncated);
+        self.builder.insert_constrain(no_overflow);
+    }
+
+    /// Constrains a signed `+`, `-` or `*` of integers of `bit_size` bits not to overflow.
+    ///
+    /// Signed integers are represented in two's complement, so an addition overflows exactly
+    /// when both operands have the same sign and the sign of the truncated result differs from
+    /// it, and a subtraction when the operands have different signs and the sign of the
+    /// truncated result differs from that of `lhs`. A multiplication is instead checked by
+    /// multiplying the absolute values of its operands with an unsigned overflow check and
+    /// bounding the product by the magnitude of the smallest or largest value of the type.
+    fn insert_signed_overflow_check(
+        &mut self,
+        lhs: ValueId,
+        operator: BinaryOp,
+        rhs: ValueId,
+        truncated: ValueId,
+        bit_size: u32,
+    ) {
+        let unsigned = Type::unsigned(bit_size);
+        let lhs = self.builder.insert_cast(lhs, unsigned.clone());
+        let rhs = self.builder.insert_cast(rhs, unsigned.clone());
+        let lhs_negative = self.insert_is_negative(lhs, bit_size);
+        let rhs_negative = self.insert_is_negative(rhs, bit_size);
+        let same_sign = self.builder.insert_binary(lhs_negative, BinaryOp::Eq, rhs_negative);
+
+        let no_overflow = match operator {
+            BinaryOp::Add | BinaryOp::Sub => {
+                let result = self.builder.insert_cast(truncated, unsigned);
+                let result_negative = self.insert_is_negative(result, bit_size);
+                let sign_changed =
+                    self.builder.insert_binary(result_negative, BinaryOp::Eq, lhs_negative);
+                let sign_changed = self.builder.insert_not(sign_changed);
+                let operands_can_overflow = if operator == BinaryOp::Add {
+                    same_sign
+                } else {
+                    self.builder.insert_not(same_sign)
+                };
+                let overflow =
+                    self.builder.insert_binary(operands_can_overflow, BinaryOp::And, sign_changed);
+                self.builder.insert_not(overflow)
+            }
+            BinaryOp::Mul => {
+                let lhs_abs = self.insert_absolute_value(lhs, lhs_negative, bit_size);
+                let rhs_abs = self.insert_absolute_value(rhs, rhs_negative, bit_size);
+                let product = self
+                    .insert_binary_with_overflow_check(
+                        lhs_abs,
+                        noirc_frontend::BinaryOpKind::Multiply,
+                        rhs_abs,
+                        true,
+                    )
+                    .into_leaf()
+                    .eval(self);
+
+                // The product may be at most 2^(bit_size - 1) if it is negative, and must be
+                // less than it otherwise.
+                let half = 1u128 << (bit_size - 1);
+                let half = self.builder.numeric_constant(half, Type::unsigned(bit_size));
+                let below_half = self.builder.insert_binary(product, BinaryOp::Lt, half);
+                let at_half = self.builder.insert_binary(product, BinaryOp::Eq, half);
+                let negative = self.builder.insert_not(same_sign);
+                let negative_at_half = self.builder.insert_binary(negative, BinaryOp::And, at_half);
+                self.builder.insert_binary(below_half, BinaryOp::Or, negative_at_half)
+            }
+            _ => unreachable!("ICE: Overflow check attempted on {operator}"),
+        };
+        self.builder.insert_constrain(no_overflow);
+    }
+
+    /// Returns a boolean which is true if `value`, an unsigned reinterpretation of a signed
+    /// integer of `bit_size` bits, represents a negative number.
+    fn insert_is_negative(&mut self, value: ValueId, bit_size: u32) -> ValueId {
+        let max_positive = (1u128 << (bit_size - 1)) - 1;
+        let max_positive = self.builder.numeric_constant(max_positive, Type::unsigned(bit_size));
+        self.builder.insert_binary(max_positive, BinaryOp::Lt, value)
+    }
+
+    /// Returns the absolute value of `value`, an unsigned reinterpretation of a signed integer
+    /// of `bit_size` bits, as an unsigned integer of the same bit size.
+    ///
+    /// The value is selected by multiplying by the sign rather than with a conditional so that
+    /// it remains within `bit_size` bits when multiplied by another absolute value.
+    fn insert_absolute_value(
+        &mut self,
+        value: ValueId,
+        is_negative: ValueId,
+        bit_size: u32,
+    ) -> ValueId {
+        let unsigned = Type::unsigned(bit_size);
+        let zero = self.builder.numeric_constant(0u128, unsigned.clone());
+        let negated = self
+            .insert_wrapping_binary(zero, noirc_frontend::BinaryOpKind::Subtract, value)
+            .into_leaf()
+            .eval(self);
+        let is_positive = self.builder.insert_not(is_negative);
+        let is_positive = self.builder.insert_cast(is_positive, unsigned.clone());
+        let is_negative = self.builder.insert_cast(is_negative, unsigned);
+        let positive = self.builder.insert_binary(value, BinaryOp::Mul, is_positive);
+        let negative = self.builder.insert_binary(negated, BinaryOp::Mul, is_negative);
+        self.builder.insert_binary(positive, BinaryOp::Add, negative)
+    }
+
+    /// Constrains the product of the high halves of the operands of a multiplication of integers
+    /// of `bit_size` bits to be zero. Such integers are too wide for their product to fit within
+    /// the field, so ACIR drops this product when multiplying them, and it could otherwise
+    /// overflow without changing the result.
+    fn insert_wide_multiplication_overflow_check(
+        &mut self,
+        lhs: ValueId,
+        rhs: ValueId,
+        bit_size: u32,
+    ) {
+        let typ = self.builder.type_of_value(lhs);
+        let limb_bits = self.builder.numeric_constant((bit_size as u128 + 1) / 2, typ.clone());
+        let lhs_high = self.builder.insert_binary(lhs, BinaryOp::Shr, limb_bits);
+        let rhs_high = self.builder.insert_binary(rhs, BinaryOp::Shr, limb_bits);
+        let high_product = self.builder.insert_binary(lhs_high, BinaryOp::Mul, rhs_high);
+        let zero = self.builder.numeric_constant(0u128, typ);
+        let no_overflow = self.builder.insert_binary(high_product, BinaryOp::Eq, zero);
+        self.builder.insert_constrain(no_overflow);
+    }
+
     /// The frontend claims to support equality (==) on arrays, so we must support it in SSA here.
     /// The actual BinaryOp::Eq in SSA is meant only for primitive numeric types so we encode an
     /// entire equality loop on each array element. The generated IR is as follows:
@@ -379,10 +592,12 @@ impl<'a> FunctionContext<'a> {
         reshaped_return_values
     }
 
-    /// Create a const offset of an address for an array load or store
+    /// Create a const offset of an address for an array load or store.
+    /// The offset has the same type as the address, so that both operands of the addition agree.
     pub(super) fn make_offset(&mut self, mut address: ValueId, offset: u128) -> ValueId {
         if offset != 0 {
-            let offset = self.builder.field_constant(offset);
+            let typ = self.builder.type_of_value(address);
+            let offset = self.builder.numeric_constant(offset, typ);
             address = self.builder.insert_binary(address, BinaryOp::Add, offset);
         }
         address
@@ -476,7 +691,10 @@ impl<'a> FunctionContext<'a> {
     pub(super) fn extract_current_value(&mut self, lvalue: &ast::LValue) -> LValue {
         match lvalue {
             ast::LValue::Ident(ident) => LValue::Ident(self.ident_lvalue(ident)),
-            ast::LValue::Index { array, index, .. } => self.index_lvalue(array, index).2,
+            ast::LValue::Index { array, index, location, .. } => {
+                self.builder.set_location(*location);
+                self.index_lvalue(array, index).2
+            }
             ast::LValue::MemberAccess { object, field_index } => {
                 let (old_object, object_lvalue) = self.extract_current_value_recursive(object);
                 let object_lvalue = Box::new(object_lvalue);
@@ -526,7 +744,8 @@ impl<'a> FunctionContext<'a> {
                 let variable = self.ident_lvalue(ident);
                 (variable.clone(), LValue::Ident(variable))
             }
-            ast::LValue::Index { array, index, element_type, location: _ } => {
+            ast::LValue::Index { array, index, element_type, location } => {
+                self.builder.set_location(*location);
                 let (old_array, index, index_lvalue) = self.index_lvalue(array, index);
                 let element = self.codegen_array_index(old_array, index, element_type);
                 (element, index_lvalue)
@@ -693,12 +912,13 @@ fn convert_operator(op: noirc_frontend::BinaryOpKind) -> BinaryOp {
 
 impl SharedContext {
     /// Create a new SharedContext for the given monomorphized program.
-    pub(super) fn new(program: Program) -> Self {
+    pub(super) fn new(program: Program, check_overflow: bool) -> Self {
         Self {
             functions: Default::default(),
             function_queue: Default::default(),
             function_counter: Default::default(),
             program,
+            check_overflow,
         }
     }
 
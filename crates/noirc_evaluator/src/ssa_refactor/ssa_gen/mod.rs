@@ -26,8 +26,9 @@ use super::ir::{
 /// Generates SSA for the given monomorphized program.
 ///
 /// This function will generate the SSA but does not perform any optimizations on it.
-pub(crate) fn generate_ssa(program: Program) -> Ssa {
-    let context = SharedContext::new(program);
+/// If `check_overflow` is set, integer arithmetic is checked for overflow in every function.
+pub(crate) fn generate_ssa(program: Program, check_overflow: bool) -> Ssa {
+    let context = SharedContext::new(program, check_overflow);
 
     let main_id = Program::main_id();
     let main = context.program.main();
@@ -39,6 +40,7 @@ pub(crate) fn generate_ssa(program: Program) -> Ssa {
         main.name.clone(),
         &main.parameters,
         if main.unconstrained { RuntimeType::Brillig } else { RuntimeType::Acir },
+        main.check_overflow,
         &context,
     );
     function_context.codegen_function_body(&main.body);
@@ -98,6 +100,9 @@ impl<'a> FunctionContext<'a> {
 
     /// Codegen for identifiers
     fn codegen_ident(&mut self, ident: &ast::Ident) -> Values {
+        if let Some(location) = ident.location {
+            self.builder.set_location(location);
+        }
         match &ident.definition {
             ast::Definition::Local(id) => self.lookup(*id),
             ast::Definition::Function(id) => self.get_or_queue_function(*id),
@@ -198,12 +203,14 @@ impl<'a> FunctionContext<'a> {
     fn codegen_binary(&mut self, binary: &ast::Binary) -> Values {
         let lhs = self.codegen_non_tuple_expression(&binary.lhs);
         let rhs = self.codegen_non_tuple_expression(&binary.rhs);
+        self.builder.set_location(binary.location);
         self.insert_binary(lhs, binary.operator, rhs)
     }
 
     fn codegen_index(&mut self, index: &ast::Index) -> Values {
         let array = self.codegen_non_tuple_expression(&index.collection);
         let index_value = self.codegen_non_tuple_expression(&index.index);
+        self.builder.set_location(index.location);
         self.codegen_array_index(array, index_value, &index.element_type)
     }
 
@@ -221,7 +228,8 @@ impl<'a> FunctionContext<'a> {
     ) -> Values {
         // base_index = index * type_size
         let type_size = Self::convert_type(element_type).size_of_type();
-        let type_size = self.builder.field_constant(type_size as u128);
+        let index_type = self.builder.type_of_value(index);
+        let type_size = self.builder.numeric_constant(type_size as u128, index_type);
         let base_index = self.builder.insert_binary(index, BinaryOp::Mul, type_size);
 
         let mut field_index = 0u128;
@@ -362,6 +370,17 @@ impl<'a> FunctionContext<'a> {
     /// Generate SSA for a function call. Note that calls to built-in functions
     /// and intrinsics are also represented by the function call instruction.
     fn codegen_call(&mut self, call: &ast::Call) -> Values {
+        // Unconstrained functions are compiled to Brillig and executed outside of the circuit.
+        if is_builtin(&call.func, "unconstrained_is_supported") {
+            return self.builder.numeric_constant(1u128, Type::bool()).into();
+        }
+        if let Some(operator) = wrapping_operator(&call.func) {
+            let lhs = self.codegen_non_tuple_expression(&call.arguments[0]);
+            let rhs = self.codegen_non_tuple_expression(&call.arguments[1]);
+            self.builder.set_location(call.location);
+            return self.insert_wrapping_binary(lhs, operator, rhs);
+        }
+
         let function = self.codegen_non_tuple_expression(&call.func);
         let arguments = call
             .arguments
@@ -369,6 +388,7 @@ impl<'a> FunctionContext<'a> {
             .flat_map(|argument| self.codegen_expression(argument).into_value_list(self))
             .collect();
 
+        self.builder.set_location(call.location);
         self.insert_call(function, arguments, &call.return_type)
     }
 
@@ -384,14 +404,17 @@ impl<'a> FunctionContext<'a> {
                 let value = value.eval(self);
                 Tree::Leaf(self.new_mutable_variable(value))
             });
+        } else {
+            self.name_values(&values, &let_expr.name);
         }
 
         self.define(let_expr.id, values);
         Self::unit_value()
     }
 
-    fn codegen_constrain(&mut self, expr: &Expression, _location: Location) -> Values {
+    fn codegen_constrain(&mut self, expr: &Expression, location: Location) -> Values {
         let boolean = self.codegen_non_tuple_expression(expr);
+        self.builder.set_location(location);
         self.builder.insert_constrain(boolean);
         Self::unit_value()
     }
@@ -409,3 +432,103 @@ impl<'a> FunctionContext<'a> {
         Self::unit_value()
     }
 }
+
+/// Returns true if `func` is the builtin function named `name`.
+pub(crate) fn is_builtin(func: &Expression, name: &str) -> bool {
+    matches!(
+        func,
+        Expression::Ident(ast::Ident { definition: ast::Definition::Builtin(builtin), .. })
+            if builtin == name
+    )
+}
+
+/// Returns the operator applied by a call to `func` if it is one of the `wrapping_add`,
+/// `wrapping_sub` or `wrapping_mul` builtins, which are generated inline rather than as calls.
+pub(crate) fn wrapping_operator(func: &Expression) -> Option<noirc_frontend::BinaryOpKind> {
+    use noirc_frontend::BinaryOpKind;
+    let Expression::Ident(ast::Ident { definition: ast::Definition::Builtin(name), .. }) = func
+    else {
+        return None;
+    };
+    match name.as_str() {
+        "wrapping_add" => Some(BinaryOpKind::Add),
+        "wrapping_sub" => Some(BinaryOpKind::Subtract),
+        "wrapping_mul" => Some(BinaryOpKind::Multiply),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fm::FileId;
+    use noirc_abi::AbiDistinctness;
+    use noirc_errors::{Location, Span};
+    use noirc_frontend::{
+        monomorphization::ast::{self, Definition, Expression, FuncId, LocalId, Program},
+        BinaryOpKind, Signedness,
+    };
+
+    use crate::ssa_refactor::ir::instruction::Instruction;
+
+    use super::generate_ssa;
+
+    /// Builds a program whose main function applies `operator` to its two parameters of type
+    /// `typ`, located at `location`.
+    fn binary_program(typ: ast::Type, operator: BinaryOpKind, location: Location) -> Program {
+        let parameter = |id, name: &str| {
+            Box::new(Expression::Ident(ast::Ident {
+                location: None,
+                definition: Definition::Local(LocalId(id)),
+                mutable: false,
+                name: name.to_owned(),
+                typ: typ.clone(),
+            }))
+        };
+        let body = Expression::Binary(ast::Binary {
+            lhs: parameter(0, "x"),
+            operator,
+            rhs: parameter(1, "y"),
+            location,
+        });
+        let main = ast::Function {
+            id: FuncId(0),
+            name: "main".to_owned(),
+            parameters: vec![
+                (LocalId(0), false, "x".to_owned(), typ.clone()),
+                (LocalId(1), false, "y".to_owned(), typ.clone()),
+            ],
+            body,
+            return_type: typ,
+            unconstrained: false,
+            check_overflow: true,
+        };
+        Program::new(vec![main], (Vec::new(), None), AbiDistinctness::DuplicationAllowed)
+    }
+
+    #[test]
+    fn overflow_checks_are_located_at_their_operation() {
+        let location = Location::new(Span::new(10..15), FileId::default());
+        let cases = [
+            (Signedness::Unsigned, BinaryOpKind::Add),
+            (Signedness::Unsigned, BinaryOpKind::Multiply),
+            (Signedness::Signed, BinaryOpKind::Subtract),
+            (Signedness::Signed, BinaryOpKind::Multiply),
+        ];
+        for (signedness, operator) in cases {
+            let typ = ast::Type::Integer(signedness, 8);
+            let ssa = generate_ssa(binary_program(typ, operator, location), false);
+
+            let main = ssa.main();
+            let block = &main.dfg[main.entry_block()];
+            let constraints: Vec<_> = block
+                .instructions()
+                .iter()
+                .filter(|instruction| matches!(main.dfg[**instruction], Instruction::Constrain(..)))
+                .collect();
+            assert!(!constraints.is_empty(), "{signedness:?} {operator} is not checked");
+            for constraint in constraints {
+                assert_eq!(main.dfg.location(*constraint), Some(location));
+            }
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! The verifier checks that the SSA upholds the invariants the passes and ACIR generation rely
+//! on. It is run after each pass when the SSA is compiled with verification enabled, so that a
+//! miscompile is reported by the pass which introduced it rather than by a later one.
+//!
+//! A function is well formed when:
+//! - every reachable block ends with a terminator,
+//! - every value is defined before it is used: instruction results earlier in the same block or
+//!   in a dominating block, and block parameters in the same or a dominating block,
+//! - each `jmp` passes as many arguments as its destination has parameters, and each `jmpif`
+//!   branches to blocks without parameters,
+//! - both operands of each binary instruction have the same type,
+//! - exactly one reachable block returns.
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use super::{
+    ir::{
+        basic_block::BasicBlockId,
+        dom::DominatorTree,
+        function::Function,
+        instruction::{Instruction, InstructionId, TerminatorInstruction},
+        printer::{display_instruction, display_terminator},
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+#[derive(Debug, Error)]
+#[error("{message}\n  in function {function}, {location}")]
+pub(crate) struct SsaVerificationError {
+    function: String,
+    /// The block, and the instruction within it, which is malformed
+    location: String,
+    message: String,
+}
+
+impl Ssa {
+    /// Checks that each function of the SSA is well formed, returning the first violation found.
+    pub(crate) fn check_well_formed(&self) -> Result<(), SsaVerificationError> {
+        self.functions.values().try_for_each(check_function)
+    }
+}
+
+fn check_function(function: &Function) -> Result<(), SsaVerificationError> {
+    let mut blocks: Vec<_> = function.reachable_blocks().into_iter().collect();
+    blocks.sort();
+
+    let mut verifier = Verifier {
+        function,
+        dominator_tree: DominatorTree::with_function(function),
+        definitions: HashMap::new(),
+    };
+    for block in &blocks {
+        for (position, instruction) in function.dfg[*block].instructions().iter().enumerate() {
+            if verifier.definitions.insert(*instruction, (*block, position)).is_some() {
+                let location = verifier.describe_instruction(*block, *instruction);
+                return Err(verifier.error(location, "the instruction is inserted more than once"));
+            }
+        }
+    }
+
+    let mut return_blocks = Vec::new();
+    for block in blocks {
+        verifier.check_block(block)?;
+        if let Some(TerminatorInstruction::Return { .. }) = function.dfg[block].terminator() {
+            return_blocks.push(block);
+        }
+    }
+
+    if return_blocks.len() != 1 {
+        let blocks = return_blocks.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let location = format!("blocks [{}]", blocks.join(", "));
+        let message = format!("expected exactly one return, found {}", return_blocks.len());
+        return Err(verifier.error(location, message));
+    }
+    Ok(())
+}
+
+struct Verifier<'f> {
+    function: &'f Function,
+    dominator_tree: DominatorTree,
+    /// The block and position of each instruction in a reachable block
+    definitions: HashMap<InstructionId, (BasicBlockId, usize)>,
+}
+
+impl<'f> Verifier<'f> {
+    fn check_block(&mut self, block: BasicBlockId) -> Result<(), SsaVerificationError> {
+        let function = self.function;
+        let dfg = &function.dfg;
+
+        for (position, instruction) in dfg[block].instructions().iter().enumerate() {
+            let mut values = Vec::new();
+            dfg[*instruction].for_each_value(|value| values.push(value));
+
+            let message = values
+                .into_iter()
+                .find_map(|value| self.check_use(value, block, position))
+                .or_else(|| self.check_operand_types(&dfg[*instruction]));
+            if let Some(message) = message {
+                let location = self.describe_instruction(block, *instruction);
+                return Err(self.error(location, message));
+            }
+        }
+
+        let Some(terminator) = dfg[block].terminator() else {
+            return Err(self.error(format!("block {block}"), "the block has no terminator"));
+        };
+        let mut values = Vec::new();
+        terminator.for_each_value(|value| values.push(value));
+
+        let position = dfg[block].instructions().len();
+        let message = values
+            .into_iter()
+            .find_map(|value| self.check_use(value, block, position))
+            .or_else(|| self.check_destinations(terminator));
+        if let Some(message) = message {
+            let location = self.describe_terminator(block, terminator);
+            return Err(self.error(location, message));
+        }
+        Ok(())
+    }
+
+    /// Checks that `value` is defined before the instruction at `position` in `block` which uses
+    /// it, returning why it is not otherwise.
+    fn check_use(
+        &mut self,
+        value: ValueId,
+        block: BasicBlockId,
+        position: usize,
+    ) -> Option<String> {
+        let function = self.function;
+        let dfg = &function.dfg;
+        let value = dfg.resolve(value);
+        match &dfg[value] {
+            Value::Instruction { instruction, .. } => match self.definitions.get(instruction) {
+                None => Some(format!("{value} is not defined in a reachable block")),
+                Some((definition_block, definition_position)) => {
+                    let defined_before = if *definition_block == block {
+                        *definition_position < position
+                    } else {
+                        self.dominator_tree.dominates(*definition_block, block)
+                    };
+                    (!defined_before).then(|| {
+                        format!("{value} is used before it is defined in {definition_block}")
+                    })
+                }
+            },
+            Value::Param { block: parameter_block, .. } => {
+                if !dfg.block_parameters(*parameter_block).contains(&value) {
+                    Some(format!("{value} is no longer a parameter of {parameter_block}"))
+                } else if *parameter_block != block
+                    && !self.dominator_tree.dominates(*parameter_block, block)
+                {
+                    let message = format!("{value} is a parameter of {parameter_block}");
+                    Some(format!("{message}, which does not dominate {block}"))
+                } else {
+                    None
+                }
+            }
+            Value::Array { array, .. } => {
+                array.iter().find_map(|element| self.check_use(*element, block, position))
+            }
+            Value::NumericConstant { .. }
+            | Value::Function(_)
+            | Value::Intrinsic(_)
+            | Value::ForeignFunction(_) => None,
+        }
+    }
+
+    fn check_operand_types(&self, instruction: &Instruction) -> Option<String> {
+        let Instruction::Binary(binary) = instruction else {
+            return None;
+        };
+        let lhs_type = self.function.dfg.type_of_value(binary.lhs);
+        let rhs_type = self.function.dfg.type_of_value(binary.rhs);
+        let operator = binary.operator;
+        (lhs_type != rhs_type).then(|| {
+            format!("the operands of {operator} have different types: {lhs_type} and {rhs_type}")
+        })
+    }
+
+    /// Checks that the arguments of a jump match the parameters of the blocks it jumps to.
+    fn check_destinations(&self, terminator: &TerminatorInstruction) -> Option<String> {
+        let dfg = &self.function.dfg;
+        match terminator {
+            TerminatorInstruction::Jmp { destination, arguments } => {
+                let (parameters, arguments) =
+                    (dfg.block_parameters(*destination).len(), arguments.len());
+                (parameters != arguments).then(|| {
+                    let message = format!("{destination} has {parameters} parameters");
+                    format!("{message}, but is jumped to with {arguments} arguments")
+                })
+            }
+            TerminatorInstruction::JmpIf { then_destination, else_destination, .. } => {
+                [then_destination, else_destination].into_iter().find_map(|destination| {
+                    (!dfg.block_parameters(*destination).is_empty()).then(|| {
+                        format!("{destination} has parameters, but is the destination of a jmpif")
+                    })
+                })
+            }
+            TerminatorInstruction::Return { .. } => None,
+        }
+    }
+
+    fn describe_instruction(&self, block: BasicBlockId, instruction: InstructionId) -> String {
+        let instruction = display_with(|f| display_instruction(self.function, instruction, f));
+        format!("block {block}: {}", instruction.to_string().trim())
+    }
+
+    fn describe_terminator(
+        &self,
+        block: BasicBlockId,
+        terminator: &TerminatorInstruction,
+    ) -> String {
+        let terminator = display_with(|f| display_terminator(self.function, Some(terminator), f));
+        format!("block {block}: {}", terminator.to_string().trim())
+    }
+
+    fn error(&self, location: String, message: impl Into<String>) -> SsaVerificationError {
+        let function = format!("{} {}", self.function.name(), self.function.id());
+        SsaVerificationError { function, location, message: message.into() }
+    }
+}
+
+/// Displays a value with one of the printer's functions, which write to a formatter.
+struct DisplayWith<F>(F);
+
+fn display_with<F: Fn(&mut std::fmt::Formatter) -> std::fmt::Result>(f: F) -> DisplayWith<F> {
+    DisplayWith(f)
+}
+
+impl<F: Fn(&mut std::fmt::Formatter) -> std::fmt::Result> std::fmt::Display for DisplayWith<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (self.0)(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::ssa_gen::Ssa;
+
+    fn verify(src: &str) -> Result<(), String> {
+        let ssa: Ssa = src.parse().unwrap();
+        ssa.check_well_formed().map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn accepts_well_formed_loops() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u32):
+                jmp b1(u32 0)
+              b1(v1: u32):
+                v2 = lt v1, v0
+                jmpif v2 then: b2, else: b3
+              b2():
+                v3 = add v1, u32 1
+                jmp b1(v3)
+              b3():
+                return v1
+            }
+        ";
+        assert_eq!(verify(src), Ok(()));
+    }
+
+    #[test]
+    fn rejects_values_used_outside_of_the_blocks_they_dominate() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u1):
+                jmpif v0 then: b1, else: b2
+              b1():
+                v1 = not v0
+                jmp b3()
+              b2():
+                jmp b3()
+              b3():
+                return v1
+            }
+        ";
+        let error = verify(src).unwrap_err();
+        assert!(error.starts_with("v1 is used before it is defined in b1"), "{error}");
+        assert!(error.contains("in function main f0, block b3: return v1"), "{error}");
+    }
+
+    #[test]
+    fn rejects_jumps_with_the_wrong_number_of_arguments() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: Field):
+                jmp b1(v0, v0)
+              b1(v1: Field):
+                return v1
+            }
+        ";
+        let error = verify(src).unwrap_err();
+        assert!(error.starts_with("b1 has 1 parameters, but is jumped to with 2 arguments"));
+    }
+
+    #[test]
+    fn rejects_binary_operands_of_different_types() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u32):
+                v1 = add v0, Field 1
+                return v1
+            }
+        ";
+        let error = verify(src).unwrap_err();
+        assert!(error.starts_with("the operands of add have different types: u32 and Field"));
+    }
+
+    #[test]
+    fn rejects_functions_without_a_single_return() {
+        let src = "
+            acir fn main f0 {
+              b0(v0: u1):
+                jmpif v0 then: b1, else: b2
+              b1():
+                return
+              b2():
+                return
+            }
+        ";
+        let error = verify(src).unwrap_err();
+        assert!(error.starts_with("expected exactly one return, found 2"), "{error}");
+    }
+}
@@ -148,10 +148,9 @@ impl CrateDefMap {
         interner: &'a NodeInterner,
     ) -> impl Iterator<Item = FuncId> + 'a {
         self.modules.iter().flat_map(|(_, module)| {
-            module
-                .value_definitions()
-                .filter_map(|id| id.as_function())
-                .filter(|id| interner.function_meta(id).attributes == Some(Attribute::Test))
+            module.value_definitions().filter_map(|id| id.as_function()).filter(|id| {
+                matches!(interner.function_meta(id).attributes, Some(Attribute::Test(_)))
+            })
         })
     }
 
@@ -29,6 +29,27 @@ pub struct Context {
 
 pub type StorageSlot = u32;
 
+/// How function names are matched against a pattern, e.g. when selecting tests to run.
+#[derive(Debug, Clone, Copy)]
+pub enum FunctionNameMatch<'a> {
+    /// Every function matches
+    Anything,
+    /// Only the function with exactly this name matches
+    Exact(&'a str),
+    /// Every function whose name contains this string matches
+    Contains(&'a str),
+}
+
+impl FunctionNameMatch<'_> {
+    pub fn matches(&self, function_name: &str) -> bool {
+        match self {
+            FunctionNameMatch::Anything => true,
+            FunctionNameMatch::Exact(pattern) => function_name == *pattern,
+            FunctionNameMatch::Contains(pattern) => function_name.contains(pattern),
+        }
+    }
+}
+
 impl Context {
     pub fn new(file_manager: FileManager, crate_graph: CrateGraph) -> Context {
         Context {
@@ -80,18 +101,17 @@ impl Context {
     }
 
     /// Returns a list of all functions in the current crate marked with #[test]
-    /// whose names contain the given pattern string. An empty pattern string
-    /// will return all functions marked with #[test].
+    /// whose names match the given pattern.
     pub fn get_all_test_functions_in_crate_matching(
         &self,
         crate_id: &CrateId,
-        pattern: &str,
+        pattern: FunctionNameMatch,
     ) -> Vec<FuncId> {
         let interner = &self.def_interner;
         self.def_map(crate_id)
             .expect("The local crate should be analyzed already")
             .get_all_test_functions(interner)
-            .filter_map(|id| interner.function_name(&id).contains(pattern).then_some(id))
+            .filter_map(|id| pattern.matches(interner.function_name(&id)).then_some(id))
             .collect()
     }
 
@@ -35,6 +35,8 @@ pub enum TypeCheckError {
     },
     #[error("Cannot infer type of expression, type annotations needed before this point")]
     TypeAnnotationsNeeded { span: Span },
+    #[error("Integers used in binary operations may be at most {max} bits wide, found {bit_size}")]
+    IntegerTooWide { bit_size: u32, max: u32, span: Span },
     #[error("{0}")]
     ResolverError(ResolverError),
 }
@@ -106,6 +108,11 @@ impl From<TypeCheckError> for Diagnostic {
                 "Type must be known at this point".to_string(),
                 span,
             ),
+            TypeCheckError::IntegerTooWide { bit_size, max, span } => Diagnostic::simple_error(
+                format!("Arithmetic and comparisons are unsupported on {bit_size} bit integers"),
+                format!("integers used in binary operations may be at most {max} bits wide"),
+                span,
+            ),
             TypeCheckError::ResolverError(error) => error.into(),
         }
     }
@@ -16,6 +16,10 @@ use crate::{
 
 use super::{errors::TypeCheckError, TypeChecker};
 
+/// The widest integer type arithmetic and comparisons are supported on. Multiplications of wide
+/// integers are split into products of half-width limbs, which must fit within the field.
+const MAX_ARITHMETIC_BIT_SIZE: u32 = 128;
+
 impl<'interner> TypeChecker<'interner> {
     /// Infers a type for a given expression, and return this type.
     /// As a side-effect, this function will also remember this type in the NodeInterner
@@ -825,6 +829,15 @@ impl<'interner> TypeChecker<'interner> {
     ) -> Result<Type, TypeCheckError> {
         let make_error = move |msg| TypeCheckError::Unstructured { msg, span };
 
+        for typ in [lhs_type, rhs_type] {
+            if let Type::Integer(_, _, bit_size) = typ.follow_bindings() {
+                if bit_size > MAX_ARITHMETIC_BIT_SIZE {
+                    let max = MAX_ARITHMETIC_BIT_SIZE;
+                    return Err(TypeCheckError::IntegerTooWide { bit_size, max, span });
+                }
+            }
+        }
+
         if op.kind.is_comparator() {
             return self
                 .comparator_operand_type_rules(lhs_type, rhs_type, op, span)
@@ -169,6 +169,8 @@ mod test {
         parse_program, FunctionKind, Path,
     };
 
+    use super::TypeCheckError;
+
     #[test]
     fn basic_let() {
         let mut interner = NodeInterner::default();
@@ -304,6 +306,32 @@ mod test {
         type_check_src_code(src, vec![String::from("main"), String::from("foo")]);
     }
 
+    #[test]
+    fn basic_u128_arithmetic() {
+        let src = r#"
+            fn main(x : u128, y : u128) {
+                let _z = (x * y) / (x % y);
+                let _b = x < y;
+            }
+        "#;
+
+        type_check_src_code(src, vec![String::from("main")]);
+    }
+
+    #[test]
+    fn arithmetic_on_integers_wider_than_128_bits() {
+        let src = r#"
+            fn main(x : u200, y : u200) {
+                let _z = x + y;
+            }
+        "#;
+
+        let errors = type_check_src_code_errors(src, vec![String::from("main")]);
+        let is_too_wide =
+            |error: &TypeCheckError| matches!(error, TypeCheckError::IntegerTooWide { .. });
+        assert!(errors.iter().any(is_too_wide), "expected an IntegerTooWide error: {errors:?}");
+    }
+
     // This is the same Stub that is in the resolver, maybe we can pull this out into a test module and re-use?
     struct TestPathResolver(HashMap<String, ModuleDefId>);
 
@@ -339,6 +367,12 @@ mod test {
     // This function assumes that there is only one function and this is the
     // func id that is returned
     fn type_check_src_code(src: &str, func_namespace: Vec<String>) {
+        let errors = type_check_src_code_errors(src, func_namespace);
+        assert_eq!(errors, vec![]);
+    }
+
+    /// Type checks the first function of `func_namespace`, returning the type errors found.
+    fn type_check_src_code_errors(src: &str, func_namespace: Vec<String>) -> Vec<TypeCheckError> {
         let (program, errors) = parse_program(src);
         let mut interner = NodeInterner::default();
 
@@ -389,7 +423,6 @@ mod test {
         }
 
         // Type check section
-        let errors = super::type_check_func(&mut interner, func_ids.first().cloned().unwrap());
-        assert_eq!(errors, vec![]);
+        super::type_check_func(&mut interner, func_ids.first().cloned().unwrap())
     }
 }
@@ -17,6 +17,7 @@ pub struct Lexer<'a> {
     char_iter: Peekable<Zip<Chars<'a>, RangeFrom<u32>>>,
     position: Position,
     done: bool,
+    skip_comments: bool,
 }
 
 pub type SpannedTokenResult = Result<SpannedToken, LexerErrorKind>;
@@ -25,25 +26,37 @@ impl<'a> Lexer<'a> {
     /// Given a source file of noir code, return all the tokens in the file
     /// in order, along with any lexing errors that occurred.
     pub fn lex(source: &'a str) -> (Tokens, Vec<LexerErrorKind>) {
-        let lexer = Lexer::new(source);
-        let mut tokens = vec![];
-        let mut errors = vec![];
-        for result in lexer {
-            match result {
-                Ok(token) => tokens.push(token),
-                Err(error) => errors.push(error),
-            }
-        }
-        (Tokens(tokens), errors)
+        Lexer::new(source).tokenize()
     }
 
-    fn new(source: &'a str) -> Self {
+    pub fn new(source: &'a str) -> Self {
         Lexer {
             // We zip with the character index here to ensure the first char has index 0
             char_iter: source.chars().zip(0..).peekable(),
             position: 0,
             done: false,
+            skip_comments: true,
+        }
+    }
+
+    /// Controls whether comments are dropped from the token stream (the default)
+    /// or returned as `Token::LineComment`s. Tools such as the formatter need to see them.
+    pub fn skip_comments(mut self, skip_comments: bool) -> Self {
+        self.skip_comments = skip_comments;
+        self
+    }
+
+    /// Consumes the lexer, returning all the tokens in the file along with any lexing errors.
+    pub fn tokenize(self) -> (Tokens, Vec<LexerErrorKind>) {
+        let mut tokens = vec![];
+        let mut errors = vec![];
+        for result in self {
+            match result {
+                Ok(token) => tokens.push(token),
+                Err(error) => errors.push(error),
+            }
         }
+        (Tokens(tokens), errors)
     }
 
     /// Iterates the cursor and returns the char at the new cursor position
@@ -173,9 +186,10 @@ impl<'a> Lexer<'a> {
             Token::Minus => self.single_double_peek_token('>', prev_token, Token::Arrow),
             Token::Colon => self.single_double_peek_token(':', prev_token, Token::DoubleColon),
             Token::Slash => {
+                let start = self.position;
                 if self.peek_char_is('/') {
                     self.next_char();
-                    return self.parse_comment();
+                    return self.parse_comment(start);
                 }
                 Ok(spanned_prev_token)
             }
@@ -257,10 +271,24 @@ impl<'a> Lexer<'a> {
         }
         self.next_char();
 
-        let (word, start, end) = self.eat_while(None, |ch| {
-            (ch.is_ascii_alphabetic() || ch.is_numeric() || ch == '_' || ch == '(' || ch == ')')
-                && (ch != ']')
-        });
+        // Attributes may contain string arguments, which are lexed as string literals so that they
+        // may contain brackets. Everything else up to the closing bracket is taken as is.
+        let (mut word, start, mut end) =
+            self.eat_while(None, |ch| ch != ']' && ch != '\n' && ch != '"');
+        while self.peek_char_is('"') {
+            self.next_char();
+            let (string, _, _) = self.eat_while(None, |ch| ch != '"');
+            if self.next_char().is_none() {
+                let span = Span::exclusive(start, self.position);
+                return Err(LexerErrorKind::MalformedFuncAttribute { span, found: word });
+            }
+            word = format!("{word}\"{string}\"");
+
+            let (rest, _, rest_end) =
+                self.eat_while(None, |ch| ch != ']' && ch != '\n' && ch != '"');
+            word.push_str(&rest);
+            end = rest_end;
+        }
 
         if !self.peek_char_is(']') {
             return Err(LexerErrorKind::UnexpectedCharacter {
@@ -330,9 +358,12 @@ impl<'a> Lexer<'a> {
         str_literal_token.into_span(start_span, end_span)
     }
 
-    fn parse_comment(&mut self) -> SpannedTokenResult {
-        let _ = self.eat_while(None, |ch| ch != '\n');
-        self.next_token()
+    fn parse_comment(&mut self, start: Position) -> SpannedTokenResult {
+        let (comment, _, end) = self.eat_while(None, |ch| ch != '\n');
+        if self.skip_comments {
+            return self.next_token();
+        }
+        Ok(Token::LineComment(comment).into_span(start, end))
     }
 
     /// Skips white space. They are not significant in the source language
@@ -416,6 +447,64 @@ fn test_custom_gate_syntax() {
     }
 }
 
+#[test]
+fn test_test_attributes() {
+    use crate::token::TestScope;
+
+    let input = r#"#[test] #[test(should_fail)] #[test(should_fail_with = "not enough funds")]"#;
+
+    let expected = vec![
+        Token::Attribute(Attribute::Test(TestScope::None)),
+        Token::Attribute(Attribute::Test(TestScope::ShouldFailWith { reason: None })),
+        Token::Attribute(Attribute::Test(TestScope::ShouldFailWith {
+            reason: Some("not enough funds".to_string()),
+        })),
+    ];
+
+    let mut lexer = Lexer::new(input);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+}
+
+#[test]
+fn test_attribute_string_arguments_may_contain_brackets() {
+    use crate::token::TestScope;
+
+    let input = r#"#[test(should_fail_with = "index [3] is out of bounds")] fn"#;
+    let mut lexer = Lexer::new(input);
+
+    let expected = Token::Attribute(Attribute::Test(TestScope::ShouldFailWith {
+        reason: Some("index [3] is out of bounds".to_string()),
+    }));
+    assert_eq!(lexer.next_token().unwrap(), expected);
+    assert_eq!(lexer.next_token().unwrap(), Token::Keyword(Keyword::Fn));
+}
+
+#[test]
+fn test_unterminated_attribute_string_arguments() {
+    let input = r#"#[test(should_fail_with = "index [3]"#;
+    let mut lexer = Lexer::new(input);
+
+    let error = lexer.next_token().unwrap_err();
+    assert!(matches!(error, LexerErrorKind::MalformedFuncAttribute { .. }));
+}
+
+#[test]
+fn test_check_overflow_attribute() {
+    let input = "#[check_overflow]";
+    let mut lexer = Lexer::new(input);
+    assert_eq!(lexer.next_token().unwrap(), Token::Attribute(Attribute::CheckOverflow));
+}
+
+#[test]
+fn test_malformed_test_attribute() {
+    let input = "#[test(should_pass)]";
+    let mut lexer = Lexer::new(input);
+    assert!(matches!(lexer.next_token(), Err(LexerErrorKind::MalformedFuncAttribute { .. })));
+}
+
 #[test]
 fn test_int_type() {
     let input = "u16 i16 i108 u104.5";
@@ -480,6 +569,30 @@ fn test_comment() {
     }
 }
 
+#[test]
+fn test_comment_retained() {
+    let input = "let x = 5; // five
+        // trailing
+    ";
+
+    let expected = vec![
+        Token::Keyword(Keyword::Let),
+        Token::Ident("x".to_string()),
+        Token::Assign,
+        Token::Int(FieldElement::from(5_i128)),
+        Token::Semicolon,
+        Token::LineComment(" five".to_string()),
+        Token::LineComment(" trailing".to_string()),
+        Token::EOF,
+    ];
+
+    let mut lexer = Lexer::new(input).skip_comments(false);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+}
+
 #[test]
 fn test_eat_string_literal() {
     let input = "let _word = \"hello\"";
@@ -18,6 +18,8 @@ pub enum Token {
     Keyword(Keyword),
     IntType(IntType),
     Attribute(Attribute),
+    /// A `// ...` comment. Only produced when the lexer is asked to keep comments.
+    LineComment(String),
     /// <
     Less,
     /// <=
@@ -149,6 +151,7 @@ impl fmt::Display for Token {
             Token::Str(ref b) => write!(f, "{b}"),
             Token::Keyword(k) => write!(f, "{k}"),
             Token::Attribute(ref a) => write!(f, "{a}"),
+            Token::LineComment(ref s) => write!(f, "//{s}"),
             Token::IntType(ref i) => write!(f, "{i}"),
             Token::Less => write!(f, "<"),
             Token::LessEqual => write!(f, "<="),
@@ -325,7 +328,52 @@ pub enum Attribute {
     Foreign(String),
     Builtin(String),
     Oracle(String),
-    Test,
+    Test(TestScope),
+    /// `#[check_overflow]`: integer `+`, `-` and `*` in the function fail on overflow rather
+    /// than wrapping.
+    CheckOverflow,
+}
+
+/// What a `#[test]` function is expected to do when executed.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord)]
+pub enum TestScope {
+    /// `#[test(should_fail)]` or `#[test(should_fail_with = "reason")]`: the test must fail,
+    /// with an error message containing `reason` if one is given. Failing to compile counts as
+    /// failing, as a constraint which can never be satisfied is reported at compile time.
+    ShouldFailWith { reason: Option<String> },
+    /// `#[test]`: the test must succeed.
+    None,
+}
+
+impl TestScope {
+    /// Parses the arguments of a test attribute, e.g. `(should_fail)` in `#[test(should_fail)]`.
+    fn lookup_str(arguments: &str) -> Option<TestScope> {
+        let arguments = arguments.trim();
+        if arguments.is_empty() {
+            return Some(TestScope::None);
+        }
+
+        let argument = arguments.strip_prefix('(')?.strip_suffix(')')?.trim();
+        if argument == "should_fail" {
+            return Some(TestScope::ShouldFailWith { reason: None });
+        }
+
+        let reason = argument.strip_prefix("should_fail_with")?.trim_start().strip_prefix('=')?;
+        let reason = reason.trim().strip_prefix('"')?.strip_suffix('"')?;
+        Some(TestScope::ShouldFailWith { reason: Some(reason.to_owned()) })
+    }
+}
+
+impl fmt::Display for TestScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TestScope::None => Ok(()),
+            TestScope::ShouldFailWith { reason: None } => write!(f, "(should_fail)"),
+            TestScope::ShouldFailWith { reason: Some(reason) } => {
+                write!(f, "(should_fail_with = {reason:?})")
+            }
+        }
+    }
 }
 
 impl fmt::Display for Attribute {
@@ -334,7 +382,8 @@ impl fmt::Display for Attribute {
             Attribute::Foreign(ref k) => write!(f, "#[foreign({k})]"),
             Attribute::Builtin(ref k) => write!(f, "#[builtin({k})]"),
             Attribute::Oracle(ref k) => write!(f, "#[oracle({k})]"),
-            Attribute::Test => write!(f, "#[test]"),
+            Attribute::Test(ref scope) => write!(f, "#[test{scope}]"),
+            Attribute::CheckOverflow => write!(f, "#[check_overflow]"),
         }
     }
 }
@@ -343,20 +392,26 @@ impl Attribute {
     /// If the string is a fixed attribute return that, else
     /// return the custom attribute
     pub(crate) fn lookup_attribute(word: &str, span: Span) -> Result<Token, LexerErrorKind> {
+        let malformed = || LexerErrorKind::MalformedFuncAttribute { span, found: word.to_owned() };
+
+        // Test attributes are handled separately as their arguments may contain arbitrary strings
+        if let Some(arguments) = word.strip_prefix("test") {
+            let scope = TestScope::lookup_str(arguments).ok_or_else(malformed)?;
+            return Ok(Token::Attribute(Attribute::Test(scope)));
+        }
+
+        if word.trim() == "check_overflow" {
+            return Ok(Token::Attribute(Attribute::CheckOverflow));
+        }
+
         let word_segments: Vec<&str> = word
             .split(|c| c == '(' || c == ')')
+            .map(str::trim)
             .filter(|string_segment| !string_segment.is_empty())
             .collect();
 
         if word_segments.len() != 2 {
-            if word_segments.len() == 1 && word_segments[0] == "test" {
-                return Ok(Token::Attribute(Attribute::Test));
-            } else {
-                return Err(LexerErrorKind::MalformedFuncAttribute {
-                    span,
-                    found: word.to_owned(),
-                });
-            }
+            return Err(malformed());
         }
 
         let attribute_type = word_segments[0];
@@ -366,9 +421,7 @@ impl Attribute {
             "foreign" => Token::Attribute(Attribute::Foreign(attribute_name.to_string())),
             "builtin" => Token::Attribute(Attribute::Builtin(attribute_name.to_string())),
             "oracle" => Token::Attribute(Attribute::Oracle(attribute_name.to_string())),
-            _ => {
-                return Err(LexerErrorKind::MalformedFuncAttribute { span, found: word.to_owned() })
-            }
+            _ => return Err(malformed()),
         };
         Ok(tok)
     }
@@ -402,7 +455,7 @@ impl AsRef<str> for Attribute {
             Attribute::Foreign(string) => string,
             Attribute::Builtin(string) => string,
             Attribute::Oracle(string) => string,
-            Attribute::Test => "",
+            Attribute::Test(_) | Attribute::CheckOverflow => "",
         }
     }
 }
@@ -192,6 +192,8 @@ pub struct Function {
 
     pub return_type: Type,
     pub unconstrained: bool,
+    /// True if the function is marked `#[check_overflow]`
+    pub check_overflow: bool,
 }
 
 /// Compared to hir_def::types::Type, this monomorphized Type has:
@@ -192,8 +192,17 @@ impl<'interner> Monomorphizer<'interner> {
         let parameters = self.parameters(meta.parameters);
         let body = self.expr(*self.interner.function(&f).as_expr());
         let unconstrained = meta.is_unconstrained;
+        let check_overflow = matches!(meta.attributes, Some(Attribute::CheckOverflow));
 
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            check_overflow,
+        };
         self.push_function(id, function);
     }
 
@@ -960,8 +969,17 @@ impl<'interner> Monomorphizer<'interner> {
         let return_type = ret_type.clone();
         let name = lambda_name.to_owned();
         let unconstrained = false;
+        let check_overflow = false;
 
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            check_overflow,
+        };
         self.push_function(id, function);
 
         let typ = ast::Type::Function(parameter_types, Box::new(ret_type));
@@ -1043,7 +1061,16 @@ impl<'interner> Monomorphizer<'interner> {
         let name = lambda_name.to_owned();
 
         let unconstrained = false;
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let check_overflow = false;
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            check_overflow,
+        };
         self.push_function(id, function);
 
         ast::Expression::Ident(ast::Ident {